@@ -20,6 +20,8 @@ use std::path::{Path, PathBuf};
 use pest::iterators::Pair;
 use pest::Parser;
 
+use crate::FileLocation;
+
 use crate::document::bibliography::{Bibliography, LocalBibliography, LocalBibliographyEntry};
 use crate::document::text::RawCitation;
 
@@ -31,6 +33,8 @@ use super::{DocumentParser, Rule};
 
 #[derive(Debug)]
 pub struct BibliographyBuilderEntry {
+    file_location: FileLocation,
+
     id: String,
 
     raw_citation: RawCitationBuilder,
@@ -39,14 +43,18 @@ pub struct BibliographyBuilderEntry {
 }
 
 impl BibliographyBuilderEntry {
-    fn from_pest(pair: Pair<Rule>, index: usize) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>, index: usize) -> Self {
         assert_eq!(pair.as_rule(), Rule::bib_entry);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
-        let raw_citation = RawCitationBuilder::from_pest_entries(inner);
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
+        let raw_citation = RawCitationBuilder::from_pest_entries(path, inner);
 
         BibliographyBuilderEntry {
+            file_location,
+
             id,
             raw_citation,
 
@@ -64,6 +72,10 @@ impl BibliographyBuilderEntry {
         &self.id
     }
 
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     fn finish(&self) -> RawCitation {
         self.raw_citation.finish()
     }
@@ -93,10 +105,11 @@ impl BibliographyBuilder {
             return None;
         }
 
-        let contents = match std::fs::read_to_string(bib_path) {
+        let contents = match std::fs::read_to_string(&bib_path) {
             Ok(contents) => contents,
             Err(e) => {
-                errors.err(e);
+                let file_location = FileLocation::file(&bib_path);
+                errors.err(ParsingError::IoError(bib_path.clone(), file_location, e));
                 return None;
             }
         };
@@ -104,7 +117,8 @@ impl BibliographyBuilder {
         let pair = match DocumentParser::parse(Rule::bib, &contents) {
             Ok(mut pair) => pair.next().unwrap(),
             Err(e) => {
-                errors.err(e);
+                let file_location = FileLocation::from_pest_error(&bib_path, &contents, &e);
+                errors.err(ParsingError::PestError(file_location, Box::new(e)));
                 return None;
             }
         };
@@ -114,7 +128,7 @@ impl BibliographyBuilder {
             .enumerate()
             .filter_map(|(i, pair)| match pair.as_rule() {
                 Rule::EOI => None,
-                _ => Some(BibliographyBuilderEntry::from_pest(pair, i)),
+                _ => Some(BibliographyBuilderEntry::from_pest(&bib_path, pair, i)),
             })
             .collect();
 
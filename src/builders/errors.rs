@@ -13,35 +13,93 @@
 // You should have received a copy of the GNU Affero General Public License along with ckproof. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, ErrorVariant};
 use std::io::Error as IoError;
+use std::path::PathBuf;
 use url::ParseError as UrlError;
 
-use crate::eprint;
+use crate::document::language::Display;
+use crate::{Diagnostic, FileLocation};
 
 use super::bibliography::BibliographyBuilderEntry;
 use super::language::{
     DefinitionBuilder, DisplayFormulaBuilder, FormulaBuilder, ReadableBuilder, SymbolBuilder,
-    TypeBuilder, TypeSignatureBuilderGround, VariableBuilder,
+    TypeBuilder, TypeSignatureBuilder, TypeSignatureBuilderGround, VariableBuilder,
 };
 use super::structure::{BookBuilder, ChapterBuilder};
 use super::system::{
-    AxiomBuilder, DeductableBuilder, Flag, ProofBuilder, ProofBuilderStep, SystemBuilder,
-    SystemBuilderChild, TheoremBuilder,
+    AxiomBuilder, DeductableBuilder, DistinctBuilder, Flag, ProofBuilder, ProofBuilderLet,
+    ProofBuilderStep, ProofBuilderSubstitution, SystemBuilder, SystemBuilderChild, TheoremBuilder,
 };
 use super::text::{
-    ListBuilder, MathBuilderElement, ParagraphBuilder, ParagraphBuilderElement, QuoteBuilder,
-    RawCitationContainerBuilder, SystemChildReferenceBuilder, SystemReferenceBuilder, TableBuilder,
-    TextBuilder,
+    CitationBuilder, ListBuilder, MathBuilderElement, ParagraphBuilder, ParagraphBuilderElement,
+    QuoteBuilder, RawCitationContainerBuilder, SystemChildReferenceBuilder, SystemReferenceBuilder,
+    TableBuilder, TagReferenceBuilder, TextBuilder,
 };
 use super::Rule;
 
+fn missing_entry(code: &'static str, kind: &str, id: &str, entry: &str) -> Diagnostic {
+    let message = format!("The {} `{}` is missing a `{}` entry.", kind, id, entry);
+
+    Diagnostic::error(code, message)
+}
+
+fn duplicate_entry(code: &'static str, kind: &str, id: &str, entry: &str) -> Diagnostic {
+    let message = format!("The {} `{}` has more than one `{}` entry.", kind, id, entry);
+
+    Diagnostic::error(code, message)
+}
+
+// A number of things, like `1 input` or `2 inputs`.
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, singular)
+    } else {
+        format!("{} {}", n, plural)
+    }
+}
+
+// Operators and notations which don't lay out as many inputs as the symbol or definition takes.
+fn display_arity_mismatch(
+    code: &'static str,
+    kind: &str,
+    id: &str,
+    display: &Display,
+    arity: usize,
+) -> Diagnostic {
+    let message = format!(
+        "The {} `{}` takes {}, but it's written with {}, which lays out {}.",
+        kind,
+        id,
+        count(arity, "input", "inputs"),
+        display.describe(),
+        display.arity().unwrap()
+    );
+
+    Diagnostic::error(code, message)
+}
+
 #[derive(Debug)]
 pub enum MathParsingError<'a> {
     SquareRootWrongInputArity(&'a MathBuilderElement),
     PowerWrongInputArity(&'a MathBuilderElement),
 }
 
+impl<'a> MathParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::SquareRootWrongInputArity(_) => Diagnostic::error(
+                "math-square-root-arity",
+                "A square root must be given exactly one input.".to_owned(),
+            ),
+            Self::PowerWrongInputArity(_) => Diagnostic::error(
+                "math-power-arity",
+                "A power must be given exactly two inputs.".to_owned(),
+            ),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ParagraphElementParsingError<'a> {
     MathError(MathParsingError<'a>),
@@ -58,16 +116,19 @@ pub enum ParagraphElementParsingError<'a> {
 }
 
 impl<'a> ParagraphElementParsingError<'a> {
-    fn eprint_system_reference_id_not_found(builder: &SystemReferenceBuilder) {
+    fn diagnostic_system_reference_id_not_found(builder: &SystemReferenceBuilder) -> Diagnostic {
         let message = format!(
             "A System Reference contains the id `{}`, but this doesn't correspond to any known system.",
             builder.id(),
         );
 
-        eprint(&message, builder.file_location());
+        Diagnostic::error("system-reference-not-found", message)
+            .or_location(builder.file_location())
     }
 
-    fn eprint_system_child_reference_id_not_found(builder: &SystemChildReferenceBuilder) {
+    fn diagnostic_system_child_reference_id_not_found(
+        builder: &SystemChildReferenceBuilder,
+    ) -> Diagnostic {
         // TODO: Indicate whether it's the system or the child which was not found.
         let message = format!(
             "A System Child Reference contains the id `{}.{}`, but this doesn't correspond to any known system child.",
@@ -75,21 +136,63 @@ impl<'a> ParagraphElementParsingError<'a> {
             builder.child_id()
         );
 
-        eprint(&message, builder.file_location());
+        Diagnostic::error("system-child-reference-not-found", message)
+            .or_location(builder.file_location())
     }
 
-    fn eprint(&self, builder: &'a ParagraphBuilderElement<'a>) {
+    fn diagnostic_tag_reference_not_found(builder: &TagReferenceBuilder) -> Diagnostic {
+        let message = format!(
+            "A Tag Reference contains the tag `{}`, but no step of this proof has that tag.",
+            builder.tag()
+        );
+
+        Diagnostic::error("tag-reference-not-found", message).or_location(builder.file_location())
+    }
+
+    fn diagnostic_citation_key_not_found(builder: &CitationBuilder) -> Diagnostic {
+        let message = format!(
+            "A citation contains the key `{}`, but this doesn't correspond to any entry of the bibliography.",
+            builder.bib_key()
+        );
+
+        Diagnostic::error("citation-key-not-found", message).or_location(builder.file_location())
+    }
+
+    fn diagnostic(&self, builder: &'a ParagraphBuilderElement<'a>) -> Diagnostic {
         match self {
+            Self::MathError(e) => e.diagnostic(),
+
             Self::SystemReferenceIdNotFound => {
-                Self::eprint_system_reference_id_not_found(builder.system_reference().unwrap());
+                Self::diagnostic_system_reference_id_not_found(builder.system_reference().unwrap())
             }
             Self::SystemChildReferenceIdNotFound => {
-                Self::eprint_system_child_reference_id_not_found(
+                Self::diagnostic_system_child_reference_id_not_found(
                     builder.system_child_reference().unwrap(),
-                );
+                )
+            }
+            Self::TagReferenceNotFound => {
+                Self::diagnostic_tag_reference_not_found(builder.tag_reference().unwrap())
+            }
+            Self::CitationKeyNotFound => {
+                Self::diagnostic_citation_key_not_found(builder.citation().unwrap())
             }
 
-            _ => todo!("{:#?}", self),
+            Self::UnexpectedUnicornVomitBegin => Diagnostic::error(
+                "unexpected-unicorn-vomit-begin",
+                "Unicorn vomit is opened while it's already open.".to_owned(),
+            ),
+            Self::UnexpectedUnicornVomitEnd => Diagnostic::error(
+                "unexpected-unicorn-vomit-end",
+                "Unicorn vomit is closed without being opened.".to_owned(),
+            ),
+            Self::UnexpectedEmBegin => Diagnostic::error(
+                "unexpected-em-begin",
+                "Emphasis is opened while it's already open.".to_owned(),
+            ),
+            Self::UnexpectedEmEnd => Diagnostic::error(
+                "unexpected-em-end",
+                "Emphasis is closed without being opened.".to_owned(),
+            ),
         }
     }
 }
@@ -104,12 +207,21 @@ pub enum ParagraphParsingError<'a> {
 }
 
 impl<'a> ParagraphParsingError<'a> {
-    fn eprint(&self, builder: &'a ParagraphBuilder<'a>) {
-        match self {
-            Self::ElementError(i, error) => error.eprint(builder.get_element(*i)),
-
-            _ => todo!("{:#?}", self),
-        }
+    fn diagnostic(&self, builder: &'a ParagraphBuilder<'a>) -> Diagnostic {
+        let diagnostic = match self {
+            Self::ElementError(i, error) => error.diagnostic(builder.get_element(*i)),
+
+            Self::UnclosedUnicornVomit => Diagnostic::error(
+                "unclosed-unicorn-vomit",
+                "A paragraph opens unicorn vomit without closing it.".to_owned(),
+            ),
+            Self::UnclosedEm => Diagnostic::error(
+                "unclosed-em",
+                "A paragraph opens emphasis without closing it.".to_owned(),
+            ),
+        };
+
+        diagnostic.or_location(builder.file_location())
     }
 }
 
@@ -124,6 +236,33 @@ pub enum RawCitationContainerParsingError {
     DuplicateLocation,
 }
 
+impl RawCitationContainerParsingError {
+    fn diagnostic(&self) -> Diagnostic {
+        let (code, entry) = match self {
+            Self::DuplicateTitle => ("citation-container-duplicate-title", "container_title"),
+            Self::DuplicateOtherContributors => (
+                "citation-container-duplicate-other-contributors",
+                "other_contributors",
+            ),
+            Self::DuplicateVersion => ("citation-container-duplicate-version", "version"),
+            Self::DuplicateNumber => ("citation-container-duplicate-number", "number"),
+            Self::DuplicatePublisher => ("citation-container-duplicate-publisher", "publisher"),
+            Self::DuplicatePublicationDate => (
+                "citation-container-duplicate-publication-date",
+                "publication_date",
+            ),
+            Self::DuplicateLocation => ("citation-container-duplicate-location", "location"),
+        };
+
+        let message = format!(
+            "The container of a citation has more than one `{}` entry.",
+            entry
+        );
+
+        Diagnostic::error(code, message)
+    }
+}
+
 #[derive(Debug)]
 pub enum RawCitationParsingError<'a> {
     MissingTitle,
@@ -136,6 +275,27 @@ pub enum RawCitationParsingError<'a> {
     ),
 }
 
+impl<'a> RawCitationParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::MissingTitle => Diagnostic::error(
+                "citation-missing-title",
+                "A citation is missing a `title` entry.".to_owned(),
+            ),
+            Self::DuplicateAuthor => Diagnostic::error(
+                "citation-duplicate-authors",
+                "A citation has more than one `authors` entry.".to_owned(),
+            ),
+            Self::DuplicateTitle => Diagnostic::error(
+                "citation-duplicate-title",
+                "A citation has more than one `title` entry.".to_owned(),
+            ),
+
+            Self::ContainerError(_, e) => e.diagnostic(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TextParsingError<'a> {
     RawCitationError(RawCitationParsingError<'a>),
@@ -145,23 +305,45 @@ pub enum TextParsingError<'a> {
 }
 
 impl<'a> TextParsingError<'a> {
-    fn eprint(&self, builder: &'a TextBuilder<'a>) {
-        match self {
-            Self::ParagraphError(error) => error.eprint(builder.paragraph().unwrap()),
-
-            _ => todo!("{:#?}", self),
+    fn diagnostic(&self, builder: &'a TextBuilder<'a>) -> Diagnostic {
+        let diagnostic = match self {
+            Self::RawCitationError(e) => e.diagnostic(),
+            Self::SublistError(e) => e.diagnostic(),
+            Self::ParagraphError(e) => e.diagnostic(builder.paragraph().unwrap()),
+            Self::DisplayMathError(e) => e.diagnostic(),
+        };
+
+        match builder.file_location() {
+            Some(file_location) => diagnostic.or_location(file_location),
+            None => diagnostic,
         }
     }
 }
 
 #[derive(Debug)]
 pub enum BookParsingError<'a> {
-    TaglineError(ParagraphParsingError<'a>),
+    TaglineError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
+}
+
+impl<'a> BookParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::TaglineError(tagline, e) => e.diagnostic(tagline),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum ChapterParsingError<'a> {
-    TaglineError(ParagraphParsingError<'a>),
+    TaglineError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
+}
+
+impl<'a> ChapterParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::TaglineError(tagline, e) => e.diagnostic(tagline),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -170,6 +352,24 @@ pub enum BibliographyParsingError<'a> {
     RawCitationError(RawCitationParsingError<'a>),
 }
 
+impl<'a> BibliographyParsingError<'a> {
+    fn diagnostic(&self, entry: &BibliographyBuilderEntry) -> Diagnostic {
+        match self {
+            Self::KeyAlreadyTaken(old_entry) => {
+                let message = format!(
+                    "The bibliography key `{}` is used by more than one entry.",
+                    entry.id()
+                );
+
+                Diagnostic::error("bibliography-key-taken", message)
+                    .with_related(old_entry.file_location())
+            }
+
+            Self::RawCitationError(e) => e.diagnostic(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SystemParsingError<'a> {
     IdAlreadyTaken(&'a SystemBuilder<'a>),
@@ -179,24 +379,256 @@ pub enum SystemParsingError<'a> {
     DuplicateName,
     DuplicateTagline,
     DuplicateDescription,
+    DuplicateExtends,
+
+    ExtendsNotFound(&'a str),
+    ExtendsItself,
+    ExtendsForwardReference(&'a SystemBuilder<'a>),
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
 }
 
+impl<'a> SystemParsingError<'a> {
+    fn diagnostic(&self, system: &SystemBuilder) -> Diagnostic {
+        let kind = "system";
+        let id = system.id();
+
+        match self {
+            Self::IdAlreadyTaken(old_system) => {
+                let message = format!("The id `{}` is used by more than one system.", id);
+
+                Diagnostic::error("system-id-taken", message)
+                    .with_related(old_system.file_location())
+            }
+
+            Self::MissingName => missing_entry("system-missing-name", kind, id, "name"),
+            Self::MissingTagline => missing_entry("system-missing-tagline", kind, id, "tagline"),
+            Self::DuplicateName => duplicate_entry("system-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("system-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("system-duplicate-description", kind, id, "description")
+            }
+            Self::DuplicateExtends => {
+                duplicate_entry("system-duplicate-extends", kind, id, "extends")
+            }
+
+            Self::ExtendsNotFound(parent_id) => {
+                let message = format!(
+                    "The system `{}` extends `{}`, but this doesn't correspond to any known system.",
+                    id, parent_id
+                );
+
+                Diagnostic::error("system-extends-not-found", message)
+            }
+            Self::ExtendsItself => {
+                let message = format!("The system `{}` extends itself.", id);
+
+                Diagnostic::error("system-extends-itself", message)
+            }
+            Self::ExtendsForwardReference(parent) => {
+                let message = format!(
+                    "The system `{}` extends `{}`, which isn't declared until later.",
+                    id,
+                    parent.id()
+                );
+
+                Diagnostic::error("system-extends-forward-reference", message)
+                    .with_related(parent.file_location())
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SystemChildParsingError<'a> {
     ParentNotFound,
     IdAlreadyTaken(SystemBuilderChild<'a>),
 }
 
+impl<'a> SystemChildParsingError<'a> {
+    fn diagnostic(&self, child: SystemBuilderChild) -> Diagnostic {
+        match self {
+            Self::ParentNotFound => {
+                let message = format!(
+                    "`{}` belongs to the system `{}`, but this doesn't correspond to any known system.",
+                    child.id(),
+                    child.system_id()
+                );
+
+                Diagnostic::error("system-child-parent-not-found", message)
+            }
+
+            Self::IdAlreadyTaken(old_child) => {
+                let message = format!(
+                    "The id `{}` is used by more than one child of the system `{}`.",
+                    child.id(),
+                    child.system_id()
+                );
+
+                Diagnostic::error("system-child-id-taken", message)
+                    .with_related(old_child.file_location())
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ReadableParsingError<'a> {
     IdAlreadyTaken(ReadableBuilder<'a>),
     DuplicateReflexive(DeductableBuilder<'a>),
     DuplicateSymmetric(DeductableBuilder<'a>),
     DuplicateTransitive(DeductableBuilder<'a>),
+    DuplicateTransitiveWith(ReadableBuilder<'a>, DeductableBuilder<'a>),
     DuplicateFunction(ReadableBuilder<'a>, DeductableBuilder<'a>),
+    DuplicateCommutative(ReadableBuilder<'a>, DeductableBuilder<'a>),
+    DuplicateAssociative(ReadableBuilder<'a>, DeductableBuilder<'a>),
+    DuplicateDetachment(DeductableBuilder<'a>),
+
+    MissingPrecedence,
+    PrecedenceConflict(ReadableBuilder<'a>),
+}
+
+impl<'a> ReadableParsingError<'a> {
+    fn diagnostic(&self, readable: ReadableBuilder) -> Diagnostic {
+        match self {
+            Self::IdAlreadyTaken(old_readable) => {
+                let message = format!(
+                    "`{}` is read the same way as `{}`.",
+                    readable.id(),
+                    old_readable.id()
+                );
+
+                Diagnostic::error("readable-read-taken", message)
+                    .with_related(old_readable.file_location())
+            }
+
+            Self::DuplicateReflexive(deductable) => {
+                let message = format!(
+                    "`{}` claims reflexivity of `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id()
+                );
+
+                Diagnostic::error("readable-duplicate-reflexive", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateSymmetric(deductable) => {
+                let message = format!(
+                    "`{}` claims symmetry of `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id()
+                );
+
+                Diagnostic::error("readable-duplicate-symmetric", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateTransitive(deductable) => {
+                let message = format!(
+                    "`{}` claims transitivity of `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id()
+                );
+
+                Diagnostic::error("readable-duplicate-transitive", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateTransitiveWith(other, deductable) => {
+                let message = format!(
+                    "`{}` claims what follows from `{}` and then `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id(),
+                    other.id()
+                );
+
+                Diagnostic::error("readable-duplicate-transitive", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateFunction(relation, deductable) => {
+                let message = format!(
+                    "`{}` claims `{}` is a function with respect to `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id(),
+                    relation.id()
+                );
+
+                Diagnostic::error("readable-duplicate-function", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateCommutative(relation, deductable) => {
+                let message = format!(
+                    "`{}` claims `{}` is commutative with respect to `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id(),
+                    relation.id()
+                );
+
+                Diagnostic::error("readable-duplicate-commutative", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateAssociative(relation, deductable) => {
+                let message = format!(
+                    "`{}` claims `{}` is associative with respect to `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id(),
+                    relation.id()
+                );
+
+                Diagnostic::error("readable-duplicate-associative", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::DuplicateDetachment(deductable) => {
+                let message = format!(
+                    "`{}` claims detachment of `{}`, but another claim was already made.",
+                    deductable.id(),
+                    readable.id()
+                );
+
+                Diagnostic::error("readable-duplicate-detachment", message)
+                    .or_location(deductable.file_location())
+                    .with_related(readable.file_location())
+            }
+
+            Self::MissingPrecedence => {
+                let message = format!(
+                    "`{}` is read with an operator which needs a declared precedence.",
+                    readable.id()
+                );
+
+                Diagnostic::error("readable-missing-precedence", message)
+            }
+
+            Self::PrecedenceConflict(old_readable) => {
+                let message = format!(
+                    "`{}` reads an operator with a different precedence or associativity than `{}`.",
+                    readable.id(),
+                    old_readable.id()
+                );
+
+                Diagnostic::error("readable-precedence-conflict", message)
+                    .with_related(old_readable.file_location())
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -207,10 +639,32 @@ pub enum TypeParsingError<'a> {
     DuplicateTagline,
     DuplicateDescription,
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
 }
 
+impl<'a> TypeParsingError<'a> {
+    fn diagnostic(&self, type_ref: &TypeBuilder) -> Diagnostic {
+        let kind = "type";
+        let id = type_ref.id();
+
+        match self {
+            Self::MissingName => missing_entry("type-missing-name", kind, id, "name"),
+            Self::MissingTagline => missing_entry("type-missing-tagline", kind, id, "tagline"),
+            Self::DuplicateName => duplicate_entry("type-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("type-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("type-duplicate-description", kind, id, "description")
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TypeSignatureParsingError<'a> {
     TypeIdNotFound(&'a TypeSignatureBuilderGround<'a>),
@@ -218,6 +672,39 @@ pub enum TypeSignatureParsingError<'a> {
     ForwardReference(&'a TypeSignatureBuilderGround<'a>),
 }
 
+impl<'a> TypeSignatureParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::TypeIdNotFound(ground) => {
+                let message = format!(
+                    "A type signature contains the id `{}`, but this doesn't correspond to any known child of the system.",
+                    ground.id()
+                );
+
+                Diagnostic::error("type-signature-id-not-found", message)
+            }
+
+            Self::SystemChildWrongKind(ground) => {
+                let message = format!(
+                    "A type signature contains the id `{}`, but this isn't a type.",
+                    ground.id()
+                );
+
+                Diagnostic::error("type-signature-wrong-kind", message)
+            }
+
+            Self::ForwardReference(ground) => {
+                let message = format!(
+                    "A type signature contains the type `{}` before it's declared.",
+                    ground.id()
+                );
+
+                Diagnostic::error("type-signature-forward-reference", message)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SymbolParsingError<'a> {
     MissingName,
@@ -229,12 +716,47 @@ pub enum SymbolParsingError<'a> {
     DuplicateTypeSignature,
     DuplicateReads,
     DuplicateDisplays,
+    DisplayArityMismatch(Display, usize),
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
     TypeSignatureError(TypeSignatureParsingError<'a>),
 }
 
+impl<'a> SymbolParsingError<'a> {
+    fn diagnostic(&self, symbol: &SymbolBuilder) -> Diagnostic {
+        let kind = "symbol";
+        let id = symbol.id();
+
+        match self {
+            Self::MissingName => missing_entry("symbol-missing-name", kind, id, "name"),
+            Self::MissingTagline => missing_entry("symbol-missing-tagline", kind, id, "tagline"),
+            Self::MissingTypeSignature => missing_entry("symbol-missing-type", kind, id, "type"),
+            Self::DuplicateName => duplicate_entry("symbol-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("symbol-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("symbol-duplicate-description", kind, id, "description")
+            }
+            Self::DuplicateTypeSignature => {
+                duplicate_entry("symbol-duplicate-type", kind, id, "type")
+            }
+            Self::DuplicateReads => duplicate_entry("symbol-duplicate-read", kind, id, "read"),
+            Self::DuplicateDisplays => {
+                duplicate_entry("symbol-duplicate-display", kind, id, "display")
+            }
+            Self::DisplayArityMismatch(display, arity) => {
+                display_arity_mismatch("symbol-display-arity", kind, id, display, *arity)
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+            Self::TypeSignatureError(e) => e.diagnostic(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum DefinitionParsingError<'a> {
     MissingName,
@@ -247,12 +769,56 @@ pub enum DefinitionParsingError<'a> {
     DuplicateReads,
     DuplicateDisplays,
     DuplicateExpansion,
+    DisplayArityMismatch(Display, usize),
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
 
     VariableError(&'a VariableBuilder<'a>, VariableParsingError<'a>),
-    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError),
+    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError<'a>),
+}
+
+impl<'a> DefinitionParsingError<'a> {
+    fn diagnostic(&self, definition: &DefinitionBuilder) -> Diagnostic {
+        let kind = "definition";
+        let id = definition.id();
+
+        match self {
+            Self::MissingName => missing_entry("definition-missing-name", kind, id, "name"),
+            Self::MissingTagline => {
+                missing_entry("definition-missing-tagline", kind, id, "tagline")
+            }
+            Self::MissingExpansion => {
+                missing_entry("definition-missing-expanded", kind, id, "expanded")
+            }
+            Self::DuplicateName => duplicate_entry("definition-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("definition-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("definition-duplicate-description", kind, id, "description")
+            }
+            Self::DuplicateInputs => {
+                duplicate_entry("definition-duplicate-inputs", kind, id, "inputs")
+            }
+            Self::DuplicateReads => duplicate_entry("definition-duplicate-read", kind, id, "read"),
+            Self::DuplicateDisplays => {
+                duplicate_entry("definition-duplicate-display", kind, id, "display")
+            }
+            Self::DisplayArityMismatch(display, arity) => {
+                display_arity_mismatch("definition-display-arity", kind, id, display, *arity)
+            }
+            Self::DuplicateExpansion => {
+                duplicate_entry("definition-duplicate-expanded", kind, id, "expanded")
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+
+            Self::VariableError(var, e) => e.diagnostic(var),
+            Self::FormulaError(formula, e) => e.diagnostic(formula),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -260,10 +826,189 @@ pub enum VariableParsingError<'a> {
     TypeSignatureError(TypeSignatureParsingError<'a>),
 }
 
+impl<'a> VariableParsingError<'a> {
+    fn diagnostic(&self, var: &VariableBuilder) -> Diagnostic {
+        match self {
+            Self::TypeSignatureError(e) => e.diagnostic().or_location(var.file_location()),
+        }
+    }
+}
+
 #[derive(Debug)]
-pub enum FormulaParsingError {
-    VariableIdNotFound,
-    OperatorNotFound,
+pub enum FormulaParsingError<'a> {
+    SymbolIdNotFound(String),
+    SymbolWrongKind(String),
+    SymbolForwardReference(String),
+    SymbolSystemNotExtended(String),
+    DefinitionPartialApplication(String),
+
+    VariableIdNotFound(String),
+    // The notation, as described by `ReadNotation::describe`.
+    OperatorNotFound(String),
+    OperatorNotAssociative(String),
+    OperatorMissing,
+    OperandMissing,
+    // The delimiter which opens the notation.
+    NotationUnclosed(String),
+    ApplicationTooManyInputs,
+    TypeMismatch(TypeError<'a>),
+
+    BinderIdNotFound(String),
+    BinderWrongKind(String),
+    BinderVariableError(VariableParsingError<'a>),
+}
+
+impl<'a> FormulaParsingError<'a> {
+    fn diagnostic(&self, formula: &FormulaBuilder) -> Diagnostic {
+        let diagnostic = match self {
+            Self::SymbolIdNotFound(id) => {
+                let message = format!(
+                    "A formula contains `{}`, which doesn't correspond to any known child of the system.",
+                    id
+                );
+
+                Diagnostic::error("formula-symbol-not-found", message)
+            }
+            Self::SymbolWrongKind(id) => {
+                let message = format!(
+                    "A formula contains `{}`, which isn't a symbol or definition.",
+                    id
+                );
+
+                Diagnostic::error("formula-symbol-wrong-kind", message)
+            }
+            Self::SymbolForwardReference(id) => {
+                let message = format!(
+                    "A formula contains `{}`, which isn't declared until later.",
+                    id
+                );
+
+                Diagnostic::error("formula-symbol-forward-reference", message)
+            }
+            Self::SymbolSystemNotExtended(system_id) => {
+                let message = format!(
+                    "A formula refers to a child of the system `{}`, which its own system doesn't extend.",
+                    system_id
+                );
+
+                Diagnostic::error("formula-symbol-system-not-extended", message)
+            }
+            Self::DefinitionPartialApplication(id) => {
+                let message = format!(
+                    "A formula applies the definition `{}` to fewer inputs than it takes.",
+                    id
+                );
+
+                Diagnostic::error("formula-definition-partial-application", message)
+            }
+
+            Self::VariableIdNotFound(id) => {
+                let message = format!(
+                    "A formula contains the variable `'{}`, which hasn't been declared.",
+                    id
+                );
+
+                Diagnostic::error("formula-variable-not-found", message)
+            }
+            Self::OperatorNotFound(notation) => {
+                let message = format!(
+                    "A formula contains the {}, which isn't read by any symbol or definition.",
+                    notation
+                );
+
+                Diagnostic::error("formula-operator-not-found", message)
+            }
+            Self::OperatorNotAssociative(operator) => {
+                let message = format!(
+                    "A formula chains the operator `{}`, which doesn't associate, without parentheses.",
+                    operator
+                );
+
+                Diagnostic::error("formula-operator-not-associative", message)
+            }
+            Self::OperatorMissing => Diagnostic::error(
+                "formula-operator-missing",
+                "A formula contains two terms in a row without an operator between them."
+                    .to_owned(),
+            ),
+            Self::OperandMissing => Diagnostic::error(
+                "formula-operand-missing",
+                "A formula contains an operator which is missing an input.".to_owned(),
+            ),
+            Self::NotationUnclosed(opener) => {
+                let message = format!(
+                    "A formula opens a notation with `{}`, but no notation read in the system closes it there.",
+                    opener
+                );
+
+                Diagnostic::error("formula-notation-unclosed", message)
+            }
+            Self::ApplicationTooManyInputs => Diagnostic::error(
+                "formula-too-many-inputs",
+                "A formula applies a function to more inputs than it takes.".to_owned(),
+            ),
+            Self::TypeMismatch(e) => e.diagnostic(),
+
+            Self::BinderIdNotFound(id) => {
+                let message = format!(
+                    "A binder uses `{}`, which doesn't correspond to any known child of the system.",
+                    id
+                );
+
+                Diagnostic::error("formula-binder-not-found", message)
+            }
+            Self::BinderWrongKind(id) => {
+                let message = format!(
+                    "A binder uses `{}`, which isn't a symbol or definition.",
+                    id
+                );
+
+                Diagnostic::error("formula-binder-wrong-kind", message)
+            }
+            Self::BinderVariableError(e) => match formula {
+                FormulaBuilder::Binder(binder) => e.diagnostic(binder.var()),
+                _ => unreachable!(),
+            },
+        };
+
+        match formula.file_location() {
+            Some(file_location) => diagnostic.or_location(file_location),
+            None => diagnostic,
+        }
+    }
+}
+
+// A subformula which doesn't have the type it's used at.
+#[derive(Debug)]
+pub struct TypeError<'a> {
+    expected: TypeSignatureBuilder<'a>,
+    found: TypeSignatureBuilder<'a>,
+    at: &'a FormulaBuilder<'a>,
+}
+
+impl<'a> TypeError<'a> {
+    pub fn new(
+        expected: TypeSignatureBuilder<'a>,
+        found: TypeSignatureBuilder<'a>,
+        at: &'a FormulaBuilder<'a>,
+    ) -> Self {
+        TypeError {
+            expected,
+            found,
+            at,
+        }
+    }
+
+    fn diagnostic(&self) -> Diagnostic {
+        let message = format!(
+            "A formula expects something of type `{}`, but `{}` has type `{}`.",
+            self.expected.render(),
+            self.at.render_plain(),
+            self.found.render(),
+        );
+
+        Diagnostic::error("formula-type-mismatch", message)
+    }
 }
 
 #[derive(Debug)]
@@ -283,10 +1028,8 @@ pub enum FlagListParsingError<'a> {
     TransitivityWrongPremiseLength,
     TransitivityFirstPremiseNotBinary,
     TransitivitySecondPremiseNotBinary,
-    TransitivityPremiseSymbolNotEqual,
     TransitivityPremiseArgumentMismatch,
     TransitivityAssertionNotBinary,
-    TransitivityAssertionSymbolNotEqual,
     TransitivityAssertionLeftMismatch,
     TransitivityAssertionRightMismatch,
 
@@ -294,16 +1037,336 @@ pub enum FlagListParsingError<'a> {
     FunctionPremiseNotBinary(&'a DisplayFormulaBuilder<'a>),
     FunctionPremiseArityMismatch,
     FunctionHypothesisNotBinary(&'a DisplayFormulaBuilder<'a>),
-    FunctionHypothesisRelationMismatch(&'a DisplayFormulaBuilder<'a>),
-    FunctionHypothesisLeftVarMismatch(&'a DisplayFormulaBuilder<'a>),
-    FunctionHypothesisRightVarMismatch(&'a DisplayFormulaBuilder<'a>),
+    FunctionHypothesisVarMismatch(&'a DisplayFormulaBuilder<'a>),
     FunctionAssertionNotBinary,
     FunctionAssertionLeftNotApplication,
     FunctionAssertionRightNotApplication,
     FunctionAssertionSymbolMismatch,
     FunctionAssertionArityMismatch,
     FunctionAssertionInputNotVariable(&'a FormulaBuilder<'a>),
-    FunctionRelationNotPreorder,
+
+    CommutativityPremiseNotEmpty,
+    CommutativityAssertionNotBinary,
+    CommutativityAssertionNotApplication,
+    CommutativityArgumentMismatch,
+
+    AssociativityPremiseNotEmpty,
+    AssociativityAssertionNotBinary,
+    AssociativityAssertionNotApplication,
+    AssociativityArgumentMismatch,
+
+    DetachmentWrongPremiseLength,
+    DetachmentFirstPremiseNotVariable,
+    DetachmentSecondPremiseNotBinary,
+    DetachmentAssertionNotVariable,
+    DetachmentArgumentMismatch,
+}
+
+impl<'a> FlagListParsingError<'a> {
+    // Points at the hypothesis or assertion which doesn't have the shape the flag needs.
+    fn diagnostic(&self, kind: &str, deductable: DeductableBuilder<'a>) -> Diagnostic {
+        // Duplicates are found before the entries are verified, so there are no formulas yet.
+        if let Self::DuplicateFlag(flag) = self {
+            let message = format!(
+                "The {} `{}` has the flag `{}` more than once.",
+                kind,
+                deductable.id(),
+                flag.name()
+            );
+            return Diagnostic::error("flag-duplicate", message);
+        }
+
+        let premise = deductable.premise();
+        let first = premise.first().map(DisplayFormulaBuilder::formula);
+        let second = premise.get(1).map(DisplayFormulaBuilder::formula);
+        let assertion = Some(deductable.assertion().formula());
+
+        let (code, flag, reason, formula) = match self {
+            Self::DuplicateFlag(_) => unreachable!(),
+
+            Self::ReflexivityPremiseNotEmpty => (
+                "flag-reflexive-premise",
+                Flag::Reflexive,
+                "it has hypotheses",
+                first,
+            ),
+            Self::ReflexivityAssertionNotBinary => (
+                "flag-reflexive-assertion",
+                Flag::Reflexive,
+                "its assertion isn't a binary relation between variables",
+                assertion,
+            ),
+            Self::ReflexivityArgumentMismatch => (
+                "flag-reflexive-arguments",
+                Flag::Reflexive,
+                "its assertion doesn't relate a variable to itself",
+                assertion,
+            ),
+
+            Self::SymmetryPremiseWrongLength => (
+                "flag-symmetric-premise",
+                Flag::Symmetric,
+                "it doesn't have exactly one hypothesis",
+                first,
+            ),
+            Self::SymmetryPremiseNotBinary => (
+                "flag-symmetric-premise",
+                Flag::Symmetric,
+                "its hypothesis isn't a binary relation between variables",
+                first,
+            ),
+            Self::SymmetryAssertionNotBinary => (
+                "flag-symmetric-assertion",
+                Flag::Symmetric,
+                "its assertion isn't a binary relation between variables",
+                assertion,
+            ),
+            Self::SymmetrySymbolMismatch => (
+                "flag-symmetric-relation",
+                Flag::Symmetric,
+                "its hypothesis and assertion don't use the same relation",
+                assertion,
+            ),
+            Self::SymmetryArgumentMismatch => (
+                "flag-symmetric-arguments",
+                Flag::Symmetric,
+                "its assertion doesn't swap the variables of its hypothesis",
+                assertion,
+            ),
+
+            Self::TransitivityWrongPremiseLength => (
+                "flag-transitive-premise",
+                Flag::Transitive,
+                "it doesn't have exactly two hypotheses",
+                first,
+            ),
+            Self::TransitivityFirstPremiseNotBinary => (
+                "flag-transitive-premise",
+                Flag::Transitive,
+                "its first hypothesis isn't a binary relation between variables",
+                first,
+            ),
+            Self::TransitivitySecondPremiseNotBinary => (
+                "flag-transitive-premise",
+                Flag::Transitive,
+                "its second hypothesis isn't a binary relation between variables",
+                second,
+            ),
+            Self::TransitivityPremiseArgumentMismatch => (
+                "flag-transitive-arguments",
+                Flag::Transitive,
+                "the right side of its first hypothesis doesn't match the left side of its second",
+                second,
+            ),
+            Self::TransitivityAssertionNotBinary => (
+                "flag-transitive-assertion",
+                Flag::Transitive,
+                "its assertion isn't a binary relation between variables",
+                assertion,
+            ),
+            Self::TransitivityAssertionLeftMismatch => (
+                "flag-transitive-arguments",
+                Flag::Transitive,
+                "the left side of its assertion doesn't match the left side of its first hypothesis",
+                assertion,
+            ),
+            Self::TransitivityAssertionRightMismatch => (
+                "flag-transitive-arguments",
+                Flag::Transitive,
+                "the right side of its assertion doesn't match the right side of its second hypothesis",
+                assertion,
+            ),
+
+            Self::FunctionPremiseEmpty => (
+                "flag-function-premise",
+                Flag::Function,
+                "it has no hypotheses",
+                None,
+            ),
+            Self::FunctionPremiseNotBinary(hypothesis) => (
+                "flag-function-premise",
+                Flag::Function,
+                "this hypothesis isn't a binary relation between variables",
+                Some(hypothesis.formula()),
+            ),
+            Self::FunctionPremiseArityMismatch => (
+                "flag-function-premise",
+                Flag::Function,
+                "it doesn't have one hypothesis for each input of the function",
+                first,
+            ),
+            Self::FunctionHypothesisNotBinary(hypothesis) => (
+                "flag-function-hypothesis",
+                Flag::Function,
+                "this hypothesis isn't a binary relation between variables",
+                Some(hypothesis.formula()),
+            ),
+            Self::FunctionHypothesisVarMismatch(hypothesis) => (
+                "flag-function-hypothesis",
+                Flag::Function,
+                "this hypothesis doesn't relate the corresponding inputs on the two sides of its assertion, in either order",
+                Some(hypothesis.formula()),
+            ),
+            Self::FunctionAssertionNotBinary => (
+                "flag-function-assertion",
+                Flag::Function,
+                "its assertion isn't a binary relation",
+                assertion,
+            ),
+            Self::FunctionAssertionLeftNotApplication => (
+                "flag-function-assertion",
+                Flag::Function,
+                "the left side of its assertion isn't a function applied to variables",
+                assertion,
+            ),
+            Self::FunctionAssertionRightNotApplication => (
+                "flag-function-assertion",
+                Flag::Function,
+                "the right side of its assertion isn't a function applied to variables",
+                assertion,
+            ),
+            Self::FunctionAssertionSymbolMismatch => (
+                "flag-function-assertion",
+                Flag::Function,
+                "the two sides of its assertion don't apply the same function",
+                assertion,
+            ),
+            Self::FunctionAssertionArityMismatch => (
+                "flag-function-assertion",
+                Flag::Function,
+                "the two sides of its assertion apply the function to different numbers of inputs",
+                assertion,
+            ),
+            Self::FunctionAssertionInputNotVariable(input) => (
+                "flag-function-assertion",
+                Flag::Function,
+                "this input in its assertion isn't a variable",
+                Some(*input),
+            ),
+
+            Self::CommutativityPremiseNotEmpty => (
+                "flag-commutative-premise",
+                Flag::Commutative,
+                "it has hypotheses",
+                first,
+            ),
+            Self::CommutativityAssertionNotBinary => (
+                "flag-commutative-assertion",
+                Flag::Commutative,
+                "its assertion isn't a binary relation",
+                assertion,
+            ),
+            Self::CommutativityAssertionNotApplication => (
+                "flag-commutative-assertion",
+                Flag::Commutative,
+                "the two sides of its assertion don't apply the same binary function to variables",
+                assertion,
+            ),
+            Self::CommutativityArgumentMismatch => (
+                "flag-commutative-arguments",
+                Flag::Commutative,
+                "its assertion doesn't swap two different variables",
+                assertion,
+            ),
+
+            Self::AssociativityPremiseNotEmpty => (
+                "flag-associative-premise",
+                Flag::Associative,
+                "it has hypotheses",
+                first,
+            ),
+            Self::AssociativityAssertionNotBinary => (
+                "flag-associative-assertion",
+                Flag::Associative,
+                "its assertion isn't a binary relation",
+                assertion,
+            ),
+            Self::AssociativityAssertionNotApplication => (
+                "flag-associative-assertion",
+                Flag::Associative,
+                "its assertion doesn't relate a binary function applied to an application of itself and a variable, to the function applied to a variable and an application of itself",
+                assertion,
+            ),
+            Self::AssociativityArgumentMismatch => (
+                "flag-associative-arguments",
+                Flag::Associative,
+                "its assertion doesn't regroup three different variables without reordering them",
+                assertion,
+            ),
+
+            Self::DetachmentWrongPremiseLength => (
+                "flag-detachment-premise",
+                Flag::Detachment,
+                "it doesn't have exactly two hypotheses",
+                first,
+            ),
+            Self::DetachmentFirstPremiseNotVariable => (
+                "flag-detachment-premise",
+                Flag::Detachment,
+                "its first hypothesis isn't a variable",
+                first,
+            ),
+            Self::DetachmentSecondPremiseNotBinary => (
+                "flag-detachment-premise",
+                Flag::Detachment,
+                "its second hypothesis isn't a binary relation between variables",
+                second,
+            ),
+            Self::DetachmentAssertionNotVariable => (
+                "flag-detachment-assertion",
+                Flag::Detachment,
+                "its assertion isn't a variable",
+                assertion,
+            ),
+            Self::DetachmentArgumentMismatch => (
+                "flag-detachment-arguments",
+                Flag::Detachment,
+                "its second hypothesis doesn't relate its first hypothesis to its assertion",
+                second,
+            ),
+        };
+
+        let message = format!(
+            "The {} `{}` is flagged `{}`, but {}.",
+            kind,
+            deductable.id(),
+            flag.name(),
+            reason,
+        );
+        let diagnostic = Diagnostic::error(code, message);
+
+        match formula.and_then(FormulaBuilder::file_location) {
+            Some(file_location) => diagnostic.or_location(file_location),
+            None => diagnostic,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DistinctParsingError {
+    VariableIdNotFound(String),
+    DuplicateVariable(String),
+}
+
+impl DistinctParsingError {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::VariableIdNotFound(id) => Diagnostic::error(
+                "distinct-variable-not-found",
+                format!(
+                    "A `distinct` entry contains the variable `'{}`, which hasn't been declared.",
+                    id
+                ),
+            ),
+            Self::DuplicateVariable(id) => Diagnostic::error(
+                "distinct-duplicate-variable",
+                format!(
+                    "A `distinct` entry contains the variable `'{}` more than once.",
+                    id
+                ),
+            ),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -318,12 +1381,52 @@ pub enum AxiomParsingError<'a> {
     DuplicatePremise,
     DuplicateAssertion,
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
     FlagListError(FlagListParsingError<'a>),
 
     VariableError(&'a VariableBuilder<'a>, VariableParsingError<'a>),
-    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError),
+    DistinctError(&'a DistinctBuilder, DistinctParsingError),
+    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError<'a>),
+}
+
+impl<'a> AxiomParsingError<'a> {
+    fn diagnostic(&self, axiom: &'a AxiomBuilder<'a>) -> Diagnostic {
+        let kind = "axiom";
+        let id = axiom.id();
+
+        match self {
+            Self::MissingName => missing_entry("axiom-missing-name", kind, id, "name"),
+            Self::MissingTagline => missing_entry("axiom-missing-tagline", kind, id, "tagline"),
+            Self::MissingAssertion => {
+                missing_entry("axiom-missing-assertion", kind, id, "assertion")
+            }
+            Self::DuplicateName => duplicate_entry("axiom-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("axiom-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("axiom-duplicate-description", kind, id, "description")
+            }
+            Self::DuplicateFlagList => duplicate_entry("axiom-duplicate-flags", kind, id, "flags"),
+            Self::DuplicatePremise => {
+                duplicate_entry("axiom-duplicate-premise", kind, id, "premise")
+            }
+            Self::DuplicateAssertion => {
+                duplicate_entry("axiom-duplicate-assertion", kind, id, "assertion")
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+            Self::FlagListError(e) => e.diagnostic(kind, DeductableBuilder::Axiom(axiom)),
+
+            Self::VariableError(var, e) => e.diagnostic(var),
+            Self::DistinctError(distinct, e) => {
+                e.diagnostic().or_location(distinct.file_location())
+            }
+            Self::FormulaError(formula, e) => e.diagnostic(formula),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -338,12 +1441,54 @@ pub enum TheoremParsingError<'a> {
     DuplicatePremise,
     DuplicateAssertion,
 
-    TaglineParsingError(ParagraphParsingError<'a>),
+    TaglineParsingError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
     DescriptionParsingError(&'a TextBuilder<'a>, TextParsingError<'a>),
     FlagListError(FlagListParsingError<'a>),
 
     VariableError(&'a VariableBuilder<'a>, VariableParsingError<'a>),
-    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError),
+    DistinctError(&'a DistinctBuilder, DistinctParsingError),
+    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError<'a>),
+}
+
+impl<'a> TheoremParsingError<'a> {
+    fn diagnostic(&self, theorem: &'a TheoremBuilder<'a>) -> Diagnostic {
+        let kind = "theorem";
+        let id = theorem.id();
+
+        match self {
+            Self::MissingName => missing_entry("theorem-missing-name", kind, id, "name"),
+            Self::MissingTagline => missing_entry("theorem-missing-tagline", kind, id, "tagline"),
+            Self::MissingAssertion => {
+                missing_entry("theorem-missing-assertion", kind, id, "assertion")
+            }
+            Self::DuplicateName => duplicate_entry("theorem-duplicate-name", kind, id, "name"),
+            Self::DuplicateTagline => {
+                duplicate_entry("theorem-duplicate-tagline", kind, id, "tagline")
+            }
+            Self::DuplicateDescription => {
+                duplicate_entry("theorem-duplicate-description", kind, id, "description")
+            }
+            Self::DuplicateFlagList => {
+                duplicate_entry("theorem-duplicate-flags", kind, id, "flags")
+            }
+            Self::DuplicatePremise => {
+                duplicate_entry("theorem-duplicate-premise", kind, id, "premise")
+            }
+            Self::DuplicateAssertion => {
+                duplicate_entry("theorem-duplicate-assertion", kind, id, "assertion")
+            }
+
+            Self::TaglineParsingError(tagline, e) => e.diagnostic(tagline),
+            Self::DescriptionParsingError(text, e) => e.diagnostic(text),
+            Self::FlagListError(e) => e.diagnostic(kind, DeductableBuilder::Theorem(theorem)),
+
+            Self::VariableError(var, e) => e.diagnostic(var),
+            Self::DistinctError(distinct, e) => {
+                e.diagnostic().or_location(distinct.file_location())
+            }
+            Self::FormulaError(formula, e) => e.diagnostic(formula),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -355,6 +1500,7 @@ pub enum ProofStepParsingError<'a> {
     DuplicateJustification,
 
     SystemChildJustificationNotFound,
+    SystemChildJustificationNotExtended,
     SystemChildJustificationWrongKind,
 
     TheoremJustificationUnproven,
@@ -364,32 +1510,479 @@ pub enum ProofStepParsingError<'a> {
     HypothesisZeroIndex,
     HypothesisIndexOutOfRange,
 
-    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError),
+    PremiseTagNotFound(String),
+    PremiseNotBefore(&'a ProofBuilderStep<'a>),
+    // The number of hypotheses of the justification, then the number of premises given.
+    PremiseCountMismatch(usize, usize),
+
+    SubstitutionWithoutDeductable,
+    SubstitutionVariableNotFound(&'a ProofBuilderSubstitution<'a>),
+    SubstitutionDuplicateVariable(&'a ProofBuilderSubstitution<'a>),
+    SubstitutionTypeMismatch(&'a ProofBuilderSubstitution<'a>),
+
+    MacroFormulaNotBinary,
+    MacroMissingFlag(Flag, ReadableBuilder<'a>),
+    MacroFunctionApplication(FunctionApplicationError<'a>),
+    MacroSubstitutionNoStep,
+    // The function, then the relation it would need to carry through its inputs.
+    MacroMissingFunction(ReadableBuilder<'a>, ReadableBuilder<'a>),
+    MacroRearrangementMismatch,
+    MacroRewriteClaimNotBinary(DeductableBuilder<'a>),
+    MacroRewriteNoStep,
+    MacroRewriteNoInstance(DeductableBuilder<'a>),
+    // The relation of the rewritten formula so far, then the relation which follows it.
+    MacroRewriteNotTransitive(ReadableBuilder<'a>, ReadableBuilder<'a>),
+    // The relation the rewritten sides were joined by, then the relation of the step.
+    MacroRewriteRelationMismatch(ReadableBuilder<'a>, ReadableBuilder<'a>),
+    // The function applied by the step, if it is an application.
+    MacroRewriteNotDetachable(Option<ReadableBuilder<'a>>),
+
+    // The relation of the calculation so far, then the relation of the next link.
+    CalculationNotTransitive(ReadableBuilder<'a>, ReadableBuilder<'a>),
+
+    FormulaError(&'a FormulaBuilder<'a>, FormulaParsingError<'a>),
 }
 
 impl<'a> ProofStepParsingError<'a> {
-    fn eprint_system_child_justification_not_found(proof: &ProofBuilder, step: &ProofBuilderStep) {
-        let justification = step.justification().unwrap().system_child().unwrap();
+    fn diagnostic_system_child_justification_not_found(
+        proof: &ProofBuilder,
+        step: &ProofBuilderStep,
+    ) -> Diagnostic {
+        let justification = step.justification().unwrap().cited_system_child().unwrap();
 
         let message = format!(
             "A step of a proof for `{}` references `{}`, but this id doesn't correspond to any known child of the system `{}`.",
             proof.theorem_name(),
             justification.id(),
+            justification.system_id().unwrap_or(proof.system_id()),
+        );
+
+        Diagnostic::error("step-justification-not-found", message)
+    }
+
+    fn diagnostic_system_child_justification_not_extended(
+        proof: &ProofBuilder,
+        step: &ProofBuilderStep,
+    ) -> Diagnostic {
+        let justification = step.justification().unwrap().cited_system_child().unwrap();
+
+        let message = format!(
+            "A step of a proof for `{}` references `{}.{}`, but the system `{}` doesn't extend `{}`.",
+            proof.theorem_name(),
+            justification.system_id().unwrap(),
+            justification.id(),
             proof.system_id(),
+            justification.system_id().unwrap(),
         );
 
-        eprint(&message, step.file_location());
+        Diagnostic::error("step-justification-system-not-extended", message)
     }
 
-    fn eprint(&self, proof: &ProofBuilder, step: &ProofBuilderStep) {
-        match self {
+    fn diagnostic_system_child_justification_wrong_kind(
+        proof: &ProofBuilder,
+        step: &ProofBuilderStep,
+    ) -> Diagnostic {
+        let justification = step.justification().unwrap().cited_system_child().unwrap();
+
+        let message = format!(
+            "A step of a proof for `{}` references `{}`, but this is neither an axiom nor a theorem.",
+            proof.theorem_name(),
+            justification.id(),
+        );
+
+        Diagnostic::error("step-justification-wrong-kind", message)
+    }
+
+    fn diagnostic_theorem_justification(
+        code: &'static str,
+        reason: &str,
+        proof: &ProofBuilder,
+        step: &ProofBuilderStep,
+    ) -> Diagnostic {
+        let justification = step.justification().unwrap().cited_system_child().unwrap();
+
+        let message = format!(
+            "A step of a proof for `{}` references the theorem `{}`, but {}.",
+            proof.theorem_name(),
+            justification.id(),
+            reason,
+        );
+
+        Diagnostic::error(code, message)
+    }
+
+    fn diagnostic_macro(
+        code: &'static str,
+        reason: &str,
+        proof: &ProofBuilder,
+        step: &ProofBuilderStep,
+    ) -> Diagnostic {
+        let justification = step.justification().unwrap().macro_justification().unwrap();
+
+        let message = format!(
+            "A step of a proof for `{}` is justified by `{}`, but {}.",
+            proof.theorem_name(),
+            justification.name(),
+            reason,
+        );
+
+        Diagnostic::error(code, message)
+    }
+
+    fn diagnostic(&self, proof: &ProofBuilder, step: &ProofBuilderStep) -> Diagnostic {
+        let diagnostic = match self {
+            Self::TagAlreadyTaken(old_step) => {
+                let message = format!(
+                    "A step of a proof for `{}` has a tag which is already used by another step.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-tag-taken", message).with_related(old_step.file_location())
+            }
+
+            Self::MissingJustification => {
+                let message = format!(
+                    "A step of a proof for `{}` is missing a justification.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-missing-justification", message)
+            }
+            Self::DuplicateTags => {
+                let message = format!(
+                    "A step of a proof for `{}` has more than one tag.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-duplicate-tags", message)
+            }
+            Self::DuplicateJustification => {
+                let message = format!(
+                    "A step of a proof for `{}` has more than one justification.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-duplicate-justification", message)
+            }
+
             Self::SystemChildJustificationNotFound => {
-                Self::eprint_system_child_justification_not_found(proof, step)
+                Self::diagnostic_system_child_justification_not_found(proof, step)
             }
+            Self::SystemChildJustificationNotExtended => {
+                Self::diagnostic_system_child_justification_not_extended(proof, step)
+            }
+            Self::SystemChildJustificationWrongKind => {
+                Self::diagnostic_system_child_justification_wrong_kind(proof, step)
+            }
+
+            Self::TheoremJustificationUnproven => Self::diagnostic_theorem_justification(
+                "step-theorem-unproven",
+                "that theorem hasn't been proven",
+                proof,
+                step,
+            ),
+            Self::TheoremJustificationUsedBeforeProof => Self::diagnostic_theorem_justification(
+                "step-theorem-used-before-proof",
+                "that theorem isn't proven until later",
+                proof,
+                step,
+            ),
+            Self::TheoremJustificationCircularProof => Self::diagnostic_theorem_justification(
+                "step-theorem-circular",
+                "that's the theorem being proven",
+                proof,
+                step,
+            ),
+
+            Self::HypothesisZeroIndex => {
+                let message = format!(
+                    "A step of a proof for `{}` references hypothesis 0, but hypotheses are numbered starting from 1.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-hypothesis-zero", message)
+            }
+            Self::HypothesisIndexOutOfRange => {
+                let message = format!(
+                    "A step of a proof for `{}` references a hypothesis which `{}` doesn't have.",
+                    proof.theorem_name(),
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-hypothesis-out-of-range", message)
+            }
+
+            Self::PremiseTagNotFound(tag) => {
+                let message = format!(
+                    "A step of a proof for `{}` cites `#{}` as a premise, but no step has this tag.",
+                    proof.theorem_name(),
+                    tag,
+                );
 
-            _ => todo!("{:#?}", self),
+                Diagnostic::error("step-premise-not-found", message)
+            }
+            Self::PremiseNotBefore(premise_step) => {
+                let message = format!(
+                    "A step of a proof for `{}` cites a step as a premise which doesn't come before it.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-premise-not-before", message)
+                    .with_related(premise_step.file_location())
+            }
+            Self::PremiseCountMismatch(expected, found) => {
+                let justification = step.justification().unwrap().system_child().unwrap();
+
+                let message = format!(
+                    "A step of a proof for `{}` cites {}, but `{}` has {}.",
+                    proof.theorem_name(),
+                    count(*found, "premise", "premises"),
+                    justification.id(),
+                    count(*expected, "hypothesis", "hypotheses"),
+                );
+
+                Diagnostic::error("step-premise-count-mismatch", message)
+            }
+
+            Self::SubstitutionWithoutDeductable => {
+                let message = format!(
+                    "A step of a proof for `{}` gives a substitution, but isn't justified by an axiom or theorem.",
+                    proof.theorem_name()
+                );
+
+                Diagnostic::error("step-substitution-without-deductable", message)
+            }
+            Self::SubstitutionVariableNotFound(substitution) => {
+                let justification = step.justification().unwrap().system_child().unwrap();
+
+                let message = format!(
+                    "A step of a proof for `{}` substitutes for `'{}`, but `{}` has no such variable.",
+                    proof.theorem_name(),
+                    substitution.var(),
+                    justification.id(),
+                );
+
+                Diagnostic::error("step-substitution-variable-not-found", message)
+                    .or_location(substitution.file_location())
+            }
+            Self::SubstitutionDuplicateVariable(substitution) => {
+                let message = format!(
+                    "A step of a proof for `{}` substitutes for `'{}` more than once.",
+                    proof.theorem_name(),
+                    substitution.var(),
+                );
+
+                Diagnostic::error("step-substitution-duplicate", message)
+                    .or_location(substitution.file_location())
+            }
+            Self::SubstitutionTypeMismatch(substitution) => {
+                let message = format!(
+                    "A step of a proof for `{}` substitutes a formula for `'{}` whose type doesn't match the type of that variable.",
+                    proof.theorem_name(),
+                    substitution.var(),
+                );
+
+                Diagnostic::error("step-substitution-type-mismatch", message)
+                    .or_location(substitution.file_location())
+            }
+
+            Self::MacroFormulaNotBinary => Self::diagnostic_macro(
+                "step-macro-not-binary",
+                "its formula isn't a relation between two formulas",
+                proof,
+                step,
+            ),
+            Self::MacroMissingFlag(flag, relation) => {
+                let reason = format!(
+                    "this needs `{}` to be flagged `{}`, and no axiom or theorem claims this",
+                    relation.id(),
+                    flag.name(),
+                );
+
+                Self::diagnostic_macro("step-macro-missing-flag", &reason, proof, step)
+                    .with_related(relation.file_location())
+            }
+            Self::MacroFunctionApplication(e) => {
+                let diagnostic = Self::diagnostic_macro(
+                    "step-macro-function-application",
+                    &e.reason(),
+                    proof,
+                    step,
+                );
+
+                match e.failure {
+                    FunctionApplicationFailure::NotFunction(function) => {
+                        diagnostic.with_related(function.file_location())
+                    }
+
+                    _ => diagnostic,
+                }
+            }
+            Self::MacroSubstitutionNoStep => Self::diagnostic_macro(
+                "step-macro-substitution",
+                "no previous step uses the same relation",
+                proof,
+                step,
+            ),
+            Self::MacroMissingFunction(function, relation) => {
+                let reason = format!(
+                    "this needs `{}` to carry `{}` through its inputs, and no axiom or theorem flagged `function` claims this",
+                    function.id(),
+                    relation.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-missing-function", &reason, proof, step)
+                    .with_related(function.file_location())
+            }
+            Self::MacroRearrangementMismatch => Self::diagnostic_macro(
+                "step-macro-rearrangement",
+                "its two sides can't be rearranged into each other",
+                proof,
+                step,
+            ),
+            Self::MacroRewriteClaimNotBinary(deductable) => {
+                let reason = format!(
+                    "the assertion of `{}` isn't a relation between two formulas",
+                    deductable.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-rewrite-not-binary", &reason, proof, step)
+                    .with_related(deductable.file_location())
+            }
+            Self::MacroRewriteNoStep => Self::diagnostic_macro(
+                "step-macro-rewrite-no-step",
+                "there's no step before it",
+                proof,
+                step,
+            ),
+            Self::MacroRewriteNoInstance(deductable) => {
+                let reason = format!(
+                    "rewriting no instance of the left side of `{}` in the step before it gives this step",
+                    deductable.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-rewrite-no-instance", &reason, proof, step)
+                    .with_related(deductable.file_location())
+            }
+            Self::MacroRewriteNotTransitive(relation, next_relation) => {
+                let reason = format!(
+                    "this needs what follows from `{}` and then `{}`, and no axiom or theorem flagged `transitive` claims this",
+                    relation.id(),
+                    next_relation.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-rewrite-not-transitive", &reason, proof, step)
+                    .with_related(relation.file_location())
+            }
+            Self::MacroRewriteRelationMismatch(joined, relation) => {
+                let reason = format!(
+                    "rewriting the sides of the step before it relates them by `{}` rather than `{}`",
+                    joined.id(),
+                    relation.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-rewrite-relation", &reason, proof, step)
+            }
+            Self::MacroRewriteNotDetachable(None) => Self::diagnostic_macro(
+                "step-macro-rewrite-not-detachable",
+                "it and the step before it don't relate two formulas by the same relation, and it doesn't apply a function to anything which could be rewritten",
+                proof,
+                step,
+            ),
+            Self::MacroRewriteNotDetachable(Some(function)) => {
+                let reason = format!(
+                    "it and the step before it don't relate two formulas by the same relation, and no axiom or theorem flagged `function` claims `{}` carries a relation flagged `detachment`",
+                    function.id(),
+                );
+
+                Self::diagnostic_macro("step-macro-rewrite-not-detachable", &reason, proof, step)
+                    .with_related(function.file_location())
+            }
+
+            Self::CalculationNotTransitive(relation, link_relation) => {
+                let message = format!(
+                    "A calculation in a proof for `{}` follows `{}` with `{}`, but no axiom or theorem flagged `transitive` claims what follows from them.",
+                    proof.theorem_name(),
+                    relation.id(),
+                    link_relation.id(),
+                );
+
+                Diagnostic::error("step-calculation-not-transitive", message)
+                    .with_related(link_relation.file_location())
+            }
+
+            Self::FormulaError(formula, e) => e.diagnostic(formula),
+        };
+
+        diagnostic.or_location(step.file_location())
+    }
+}
+
+#[derive(Debug)]
+pub enum FunctionApplicationFailure<'a> {
+    NotApplications,
+    FunctionMismatch(ReadableBuilder<'a>, ReadableBuilder<'a>),
+    // The function, then the number of inputs it's applied to on each side.
+    ArityMismatch(ReadableBuilder<'a>, usize, usize),
+    NotFunction(ReadableBuilder<'a>),
+}
+
+// A pair of subformulas which a macro needed to relate, and why it couldn't.
+#[derive(Debug)]
+pub struct FunctionApplicationError<'a> {
+    relation: ReadableBuilder<'a>,
+    left: &'a FormulaBuilder<'a>,
+    right: &'a FormulaBuilder<'a>,
+    failure: FunctionApplicationFailure<'a>,
+}
+
+impl<'a> FunctionApplicationError<'a> {
+    pub fn new(
+        relation: ReadableBuilder<'a>,
+        left: &'a FormulaBuilder<'a>,
+        right: &'a FormulaBuilder<'a>,
+        failure: FunctionApplicationFailure<'a>,
+    ) -> Self {
+        FunctionApplicationError {
+            relation,
+            left,
+            right,
+            failure,
         }
     }
+
+    fn reason(&self) -> String {
+        let failure = match &self.failure {
+            FunctionApplicationFailure::NotApplications => {
+                "they aren't both function applications and no previous step relates them"
+                    .to_owned()
+            }
+            FunctionApplicationFailure::FunctionMismatch(left, right) => format!(
+                "they apply different functions, `{}` and `{}`",
+                left.id(),
+                right.id()
+            ),
+            FunctionApplicationFailure::ArityMismatch(function, left, right) => format!(
+                "they apply `{}` to {} and {} inputs",
+                function.id(),
+                left,
+                right
+            ),
+            FunctionApplicationFailure::NotFunction(function) => format!(
+                "no axiom or theorem flags `{}` as a `function` for `{}`",
+                function.id(),
+                self.relation.id()
+            ),
+        };
+
+        format!(
+            "this needs `{}` and `{}` to be related by `{}`: {}",
+            self.left.render_plain(),
+            self.right.render_plain(),
+            self.relation.id(),
+            failure,
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -397,16 +1990,66 @@ pub enum ProofParsingError<'a> {
     ParentNotFound,
     ParentNotTheorem,
 
+    VariableError(&'a VariableBuilder<'a>, VariableParsingError<'a>),
+    VariableIdTaken(&'a VariableBuilder<'a>),
+    LetIdTaken(&'a ProofBuilderLet<'a>),
+    LetError(
+        &'a ProofBuilderLet<'a>,
+        &'a FormulaBuilder<'a>,
+        FormulaParsingError<'a>,
+    ),
+
     TextError(&'a TextBuilder<'a>, TextParsingError<'a>),
     StepError(&'a ProofBuilderStep<'a>, ProofStepParsingError<'a>),
 }
 
 impl<'a> ProofParsingError<'a> {
-    fn eprint(&self, proof: &ProofBuilder) {
+    fn diagnostic(&self, proof: &ProofBuilder) -> Diagnostic {
         match self {
-            Self::StepError(step, error) => error.eprint(proof, step),
+            Self::ParentNotFound => {
+                let message = format!(
+                    "A proof is given for `{}`, but this doesn't correspond to any known child of the system `{}`.",
+                    proof.theorem_id(),
+                    proof.system_id()
+                );
+
+                Diagnostic::error("proof-parent-not-found", message)
+            }
+            Self::ParentNotTheorem => {
+                let message = format!(
+                    "A proof is given for `{}`, but this isn't a theorem.",
+                    proof.theorem_id()
+                );
+
+                Diagnostic::error("proof-parent-not-theorem", message)
+            }
+
+            Self::VariableError(var, e) => e.diagnostic(var),
+            Self::VariableIdTaken(var) => {
+                let message = format!(
+                    "A proof for `{}` declares the variable `'{}`, but this id is already taken by another variable.",
+                    proof.theorem_name(),
+                    var.id(),
+                );
 
-            _ => todo!("{:#?}", self),
+                Diagnostic::error("proof-variable-taken", message).or_location(var.file_location())
+            }
+            Self::LetIdTaken(abbreviation) => {
+                let message = format!(
+                    "A proof for `{}` abbreviates a formula as `'{}`, but this id is already taken by another variable.",
+                    proof.theorem_name(),
+                    abbreviation.var(),
+                );
+
+                Diagnostic::error("proof-let-taken", message)
+                    .or_location(abbreviation.file_location())
+            }
+            Self::LetError(abbreviation, formula, e) => e
+                .diagnostic(formula)
+                .or_location(abbreviation.file_location()),
+
+            Self::TextError(text, e) => e.diagnostic(text),
+            Self::StepError(step, e) => e.diagnostic(proof, step),
         }
     }
 }
@@ -414,7 +2057,16 @@ impl<'a> ProofParsingError<'a> {
 #[derive(Debug)]
 pub enum TableParsingError<'a> {
     CellError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
-    CaptionError(ParagraphParsingError<'a>),
+    CaptionError(&'a ParagraphBuilder<'a>, ParagraphParsingError<'a>),
+}
+
+impl<'a> TableParsingError<'a> {
+    fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::CellError(cell, e) => e.diagnostic(cell),
+            Self::CaptionError(caption, e) => e.diagnostic(caption),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -422,17 +2074,45 @@ pub enum QuoteValueParsingError {
     BibKeyNotFound,
 }
 
+impl QuoteValueParsingError {
+    fn diagnostic(&self, bib_key: &str) -> Diagnostic {
+        match self {
+            Self::BibKeyNotFound => {
+                let message = format!(
+                    "A quote contains the key `{}`, but this doesn't correspond to any entry of the bibliography.",
+                    bib_key
+                );
+
+                Diagnostic::error("quote-key-not-found", message)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum QuoteParsingError {
     OriginalError(QuoteValueParsingError),
     ValueError(QuoteValueParsingError),
 }
 
+impl QuoteParsingError {
+    fn diagnostic(&self, quote: &QuoteBuilder) -> Diagnostic {
+        match self {
+            Self::OriginalError(e) => e
+                .diagnostic(quote.original_bib_key().unwrap())
+                .or_location(quote.original_file_location().unwrap()),
+            Self::ValueError(e) => e
+                .diagnostic(quote.value_bib_key())
+                .or_location(quote.value_file_location()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ParsingError<'a> {
-    IoError(IoError),
-    PestError(PestError<Rule>),
-    UrlError(UrlError),
+    IoError(PathBuf, FileLocation, IoError),
+    PestError(FileLocation, Box<PestError<Rule>>),
+    UrlError(FileLocation, UrlError),
 
     BookError(&'a BookBuilder<'a>, BookParsingError<'a>),
     ChapterError(&'a ChapterBuilder<'a>, ChapterParsingError<'a>),
@@ -460,32 +2140,78 @@ pub enum ParsingError<'a> {
 }
 
 impl<'a> ParsingError<'a> {
-    fn eprint(&self) {
+    fn diagnostic_pest_error(file_location: &FileLocation, e: &PestError<Rule>) -> Diagnostic {
+        let message = match &e.variant {
+            ErrorVariant::ParsingError {
+                positives,
+                negatives,
+            } => match (positives.is_empty(), negatives.is_empty()) {
+                (true, true) => "Failed to parse the file.".to_owned(),
+                (false, true) => format!("Expected one of {:?}.", positives),
+                (true, false) => format!("Unexpected {:?}.", negatives),
+                (false, false) => {
+                    format!(
+                        "Unexpected {:?}, expected one of {:?}.",
+                        negatives, positives
+                    )
+                }
+            },
+            ErrorVariant::CustomError { message } => message.clone(),
+        };
+
+        Diagnostic::error("syntax", message).or_location(file_location)
+    }
+
+    fn diagnostic(&self) -> Diagnostic {
         match self {
-            Self::TextError(text, error) => error.eprint(text),
+            Self::IoError(path, file_location, e) => {
+                let message = format!("Failed to read `{}`: {}.", path.to_str().unwrap(), e);
 
-            Self::ProofError(proof, error) => error.eprint(proof),
+                Diagnostic::error("io", message).or_location(file_location)
+            }
+            Self::PestError(file_location, e) => Self::diagnostic_pest_error(file_location, e),
+            Self::UrlError(file_location, e) => {
+                let message = format!("A hyperlink contains an invalid url: {}.", e);
 
-            _ => todo!("{:#?}", self),
-        }
-    }
-}
+                Diagnostic::error("url", message).or_location(file_location)
+            }
 
-impl<'a> From<IoError> for ParsingError<'a> {
-    fn from(e: IoError) -> Self {
-        ParsingError::IoError(e)
-    }
-}
+            Self::BookError(book, e) => e.diagnostic().or_location(book.file_location()),
+            Self::ChapterError(chapter, e) => e.diagnostic().or_location(chapter.file_location()),
+            Self::BibliographyError(entry, e) => {
+                e.diagnostic(entry).or_location(entry.file_location())
+            }
 
-impl<'a> From<PestError<Rule>> for ParsingError<'a> {
-    fn from(e: PestError<Rule>) -> Self {
-        ParsingError::PestError(e)
-    }
-}
+            Self::SystemError(system, e) => {
+                e.diagnostic(system).or_location(system.file_location())
+            }
+            Self::SystemChildError(child, e) => {
+                e.diagnostic(*child).or_location(child.file_location())
+            }
+            Self::ReadableError(readable, e) => e
+                .diagnostic(*readable)
+                .or_location(readable.file_location()),
 
-impl<'a> From<UrlError> for ParsingError<'a> {
-    fn from(e: UrlError) -> Self {
-        ParsingError::UrlError(e)
+            Self::TypeError(type_ref, e) => {
+                e.diagnostic(type_ref).or_location(type_ref.file_location())
+            }
+            Self::SymbolError(symbol, e) => {
+                e.diagnostic(symbol).or_location(symbol.file_location())
+            }
+            Self::DefinitionError(definition, e) => e
+                .diagnostic(definition)
+                .or_location(definition.file_location()),
+            Self::AxiomError(axiom, e) => e.diagnostic(axiom).or_location(axiom.file_location()),
+            Self::TheoremError(theorem, e) => {
+                e.diagnostic(theorem).or_location(theorem.file_location())
+            }
+            Self::ProofError(proof, e) => e.diagnostic(proof).or_location(proof.file_location()),
+
+            Self::ListItemError(_, item, e) => e.diagnostic(item),
+            Self::TableError(_, e) => e.diagnostic(),
+            Self::QuoteError(quote, e) => e.diagnostic(quote),
+            Self::TextError(text, e) => e.diagnostic(text),
+        }
     }
 }
 
@@ -507,11 +2233,13 @@ impl<'a> ParsingErrorContext<'a> {
         !self.errors.is_empty()
     }
 
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors.iter().map(ParsingError::diagnostic).collect()
+    }
+
     pub fn eprint(&self) {
-        for error in &self.errors {
-            error.eprint()
+        for diagnostic in self.diagnostics() {
+            diagnostic.eprint();
         }
-
-        eprintln!("Checker exited with errors.");
     }
 }
@@ -18,10 +18,12 @@ use std::collections::HashMap;
 
 use super::bibliography::BibliographyBuilderEntry;
 use super::errors::{
-    BibliographyParsingError, ParsingError, ParsingErrorContext, ReadableParsingError,
-    SystemChildParsingError, SystemParsingError,
+    BibliographyParsingError, FormulaParsingError, ParsingError, ParsingErrorContext,
+    ReadableParsingError, SystemChildParsingError, SystemParsingError, VariableParsingError,
+};
+use super::language::{
+    FormulaBuilder, ReadFixity, ReadNotation, ReadSignature, ReadableBuilder, VariableBuilder,
 };
-use super::language::{ReadSignature, ReadableBuilder, VariableBuilder};
 use super::system::{SystemBuilder, SystemBuilderChild};
 
 struct SystemBuilderIndex<'a> {
@@ -29,6 +31,10 @@ struct SystemBuilderIndex<'a> {
 
     children: HashMap<&'a str, SystemBuilderChild<'a>>,
     operators: HashMap<ReadSignature<'a>, ReadableBuilder<'a>>,
+    fixities: HashMap<ReadNotation, (ReadFixity, ReadableBuilder<'a>)>,
+
+    // The delimiters of every mixfix notation, keyed by the one which opens it.
+    notations: HashMap<String, Vec<Vec<String>>>,
 }
 
 impl<'a> SystemBuilderIndex<'a> {
@@ -38,6 +44,8 @@ impl<'a> SystemBuilderIndex<'a> {
 
             children: HashMap::new(),
             operators: HashMap::new(),
+            fixities: HashMap::new(),
+            notations: HashMap::new(),
         }
     }
 
@@ -70,6 +78,21 @@ impl<'a> SystemBuilderIndex<'a> {
         readable: ReadableBuilder<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
+        match read_signature.notation() {
+            ReadNotation::Mixfix(delimiters) => {
+                let notations = self.notations.entry(delimiters[0].clone()).or_default();
+                if !notations.contains(delimiters) {
+                    notations.push(delimiters.clone());
+                }
+            }
+
+            ReadNotation::Prefix(_) | ReadNotation::Infix(_) => {
+                if !self.add_fixity(&read_signature, readable, errors) {
+                    return;
+                }
+            }
+        }
+
         match self.operators.entry(read_signature) {
             Entry::Occupied(old_readable) => {
                 errors.err(ParsingError::ReadableError(
@@ -84,11 +107,76 @@ impl<'a> SystemBuilderIndex<'a> {
         }
     }
 
+    fn add_fixity(
+        &mut self,
+        read_signature: &ReadSignature<'a>,
+        readable: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        let fixity = match readable.read_fixity() {
+            Some(fixity) => fixity,
+
+            None => {
+                errors.err(ParsingError::ReadableError(
+                    readable,
+                    ReadableParsingError::MissingPrecedence,
+                ));
+                return false;
+            }
+        };
+
+        // Every overload of an operator has to agree on how it's parsed.
+        match self.fixities.entry(read_signature.notation().clone()) {
+            Entry::Occupied(old_entry) => {
+                let (old_fixity, old_readable) = *old_entry.get();
+                if old_fixity != fixity {
+                    errors.err(ParsingError::ReadableError(
+                        readable,
+                        ReadableParsingError::PrecedenceConflict(old_readable),
+                    ));
+                    return false;
+                }
+            }
+
+            Entry::Vacant(slot) => {
+                slot.insert((fixity, readable));
+            }
+        }
+
+        true
+    }
+
     pub fn search_operator(
         &self,
         read_signature: &ReadSignature<'a>,
     ) -> Option<ReadableBuilder<'a>> {
-        self.operators.get(read_signature).copied()
+        if let Some(readable) = self.operators.get(read_signature) {
+            return Some(*readable);
+        }
+
+        // Otherwise the operator might be polymorphic in the types of its inputs.
+        self.operators
+            .iter()
+            .find(|(signature, _)| signature.matches(read_signature))
+            .map(|(_, readable)| *readable)
+    }
+
+    fn search_overloads<'c>(
+        &'c self,
+        notation: &'c ReadNotation,
+    ) -> impl Iterator<Item = ReadableBuilder<'a>> + 'c {
+        self.operators
+            .iter()
+            .filter(move |(signature, _)| signature.notation() == notation)
+            .map(|(_, readable)| *readable)
+    }
+
+    fn search_fixity(&self, notation: &ReadNotation) -> Option<ReadFixity> {
+        self.fixities.get(notation).map(|(fixity, _)| *fixity)
+    }
+
+    fn search_notations(&self, opener: &str) -> &[Vec<String>] {
+        self.notations.get(opener).map_or(&[], Vec::as_slice)
     }
 }
 
@@ -147,14 +235,48 @@ impl<'a> BuilderIndex<'a> {
         }
     }
 
+    // The system followed by every system it extends, directly or not, so that the children of a
+    // system shadow those of its ancestors.
+    fn lineage(&self, system_id: &str) -> Vec<&SystemBuilderIndex<'a>> {
+        let mut lineage: Vec<&SystemBuilderIndex> = Vec::new();
+        let mut stack = vec![system_id];
+
+        while let Some(system_id) = stack.pop() {
+            if let Some(system_index) = self.systems.get(system_id) {
+                if lineage.iter().any(|seen| seen.system_ref.id() == system_id) {
+                    continue;
+                }
+
+                lineage.push(system_index);
+                stack.extend(
+                    system_index
+                        .system_ref
+                        .extends()
+                        .iter()
+                        .rev()
+                        .map(String::as_str),
+                );
+            }
+        }
+
+        lineage
+    }
+
+    // Whether the children of `ancestor_id` are visible from the system `system_id`.
+    pub fn extends(&self, system_id: &str, ancestor_id: &str) -> bool {
+        self.lineage(system_id)
+            .iter()
+            .any(|system_index| system_index.system_ref.id() == ancestor_id)
+    }
+
     pub fn search_system_child(
         &self,
         system_id: &str,
         child_id: &str,
     ) -> Option<SystemBuilderChild<'a>> {
-        self.systems
-            .get(system_id)
-            .and_then(|system_index| system_index.search_child(child_id))
+        self.lineage(system_id)
+            .into_iter()
+            .find_map(|system_index| system_index.search_child(child_id))
     }
 
     pub fn add_bib_ref(
@@ -193,34 +315,176 @@ impl<'a> BuilderIndex<'a> {
     pub fn get_local<'b>(
         &'b self,
         system_id: &str,
+        max_serial: usize,
         vars: &'a [VariableBuilder<'a>],
     ) -> LocalBuilderIndex<'a, 'b> {
-        let parent_system = self.systems.get(system_id).unwrap();
+        let lineage = self.lineage(system_id);
         let vars = vars.iter().map(|var| (var.id(), var)).collect();
 
         LocalBuilderIndex {
-            parent_system,
+            index: self,
+            lineage,
+            max_serial,
 
             vars,
+            abbreviations: HashMap::new(),
+            bound: Vec::new(),
         }
     }
 }
 
+#[derive(Clone)]
 pub struct LocalBuilderIndex<'a, 'b> {
-    parent_system: &'b SystemBuilderIndex<'a>,
+    index: &'b BuilderIndex<'a>,
+    lineage: Vec<&'b SystemBuilderIndex<'a>>,
+    max_serial: usize,
 
     vars: HashMap<&'a str, &'a VariableBuilder<'a>>,
+    abbreviations: HashMap<&'a str, &'a FormulaBuilder<'a>>,
+    bound: Vec<&'a VariableBuilder<'a>>,
 }
 
 impl<'a, 'b> LocalBuilderIndex<'a, 'b> {
+    // Returns whether the id was free, so that variables declared by a proof can't shadow those of
+    // the theorem or each other.
+    pub fn add_variable(&mut self, var: &'a VariableBuilder<'a>) -> bool {
+        if self.search_local_id(var.id()) {
+            return false;
+        }
+
+        self.vars.insert(var.id(), var);
+        true
+    }
+
+    pub fn add_abbreviation(&mut self, id: &'a str, formula: &'a FormulaBuilder<'a>) -> bool {
+        if self.search_local_id(id) {
+            return false;
+        }
+
+        self.abbreviations.insert(id, formula);
+        true
+    }
+
+    fn search_local_id(&self, id: &str) -> bool {
+        self.vars.contains_key(id) || self.abbreviations.contains_key(id)
+    }
+
+    // Returns a copy of this index with an extra variable bound on top of the others.
+    pub fn bind(&self, var: &'a VariableBuilder<'a>) -> Self {
+        let mut local_index = self.clone();
+        local_index.bound.push(var);
+
+        local_index
+    }
+
+    pub fn verify_variable<F>(
+        &self,
+        var: &'a VariableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(VariableParsingError<'a>) -> ParsingError<'a>,
+    {
+        var.verify_structure(
+            self.lineage[0].system_ref.id(),
+            self.max_serial,
+            self.index,
+            errors,
+            generate_error,
+        )
+    }
+
     pub fn search_variable(&'b self, id: &str) -> Option<&'a VariableBuilder<'a>> {
         self.vars.get(id).copied()
     }
 
+    pub fn search_abbreviation(&self, id: &str) -> Option<&'a FormulaBuilder<'a>> {
+        self.abbreviations.get(id).copied()
+    }
+
+    // Returns the de Bruijn index of the innermost bound variable with this id.
+    pub fn search_bound(&self, id: &str) -> Option<(usize, &'a VariableBuilder<'a>)> {
+        self.bound
+            .iter()
+            .rev()
+            .enumerate()
+            .find(|(_, var)| var.id() == id)
+            .map(|(i, var)| (i, *var))
+    }
+
+    pub fn search_child(&self, child_id: &str) -> Option<SystemBuilderChild<'a>> {
+        self.lineage
+            .iter()
+            .find_map(|system_index| system_index.search_child(child_id))
+    }
+
+    pub fn extends(&self, system_id: &str) -> bool {
+        self.lineage
+            .iter()
+            .any(|system_index| system_index.system_ref.id() == system_id)
+    }
+
+    pub fn search_qualified_child(
+        &self,
+        system_id: &str,
+        child_id: &str,
+    ) -> Option<SystemBuilderChild<'a>> {
+        self.index.search_system_child(system_id, child_id)
+    }
+
+    // Whether a block with this serial is declared before the one this index was made for.
+    pub fn precedes(&self, serial: usize) -> bool {
+        serial < self.max_serial
+    }
+
     pub fn search_operator(
         &self,
         read_signature: &ReadSignature<'a>,
     ) -> Option<ReadableBuilder<'a>> {
-        self.parent_system.search_operator(read_signature)
+        self.lineage
+            .iter()
+            .find_map(|system_index| system_index.search_operator(read_signature))
+    }
+
+    // Explains why no operator matches `read_signature`. If the operator is only read by a single
+    // symbol or definition, the inputs must have the wrong types for it.
+    pub fn operator_error<I>(
+        &self,
+        read_signature: &ReadSignature<'a>,
+        inputs: I,
+    ) -> FormulaParsingError<'a>
+    where
+        I: IntoIterator<Item = &'a FormulaBuilder<'a>>,
+        I::IntoIter: Clone,
+    {
+        let notation = read_signature.notation();
+        let mut overloads = self
+            .lineage
+            .iter()
+            .flat_map(|system_index| system_index.search_overloads(notation));
+
+        match (overloads.next(), overloads.next()) {
+            (Some(readable), None) => readable
+                .type_signature()
+                .apply_formulas(inputs)
+                .err()
+                .unwrap_or_else(|| FormulaParsingError::OperatorNotFound(notation.describe())),
+
+            _ => FormulaParsingError::OperatorNotFound(notation.describe()),
+        }
+    }
+
+    pub fn search_fixity(&self, notation: &ReadNotation) -> Option<ReadFixity> {
+        self.lineage
+            .iter()
+            .find_map(|system_index| system_index.search_fixity(notation))
+    }
+
+    pub fn search_notations(&self, opener: &str) -> Vec<&Vec<String>> {
+        self.lineage
+            .iter()
+            .flat_map(|system_index| system_index.search_notations(opener))
+            .collect()
     }
 }
@@ -18,19 +18,26 @@ use std::lazy::OnceCell;
 use pest::iterators::Pair;
 
 use crate::document::structure::{AxiomBlockRef, DeductableBlockRef, TheoremBlockRef};
-use crate::document::system::ProofBlockJustification;
+use crate::document::system::{ProofBlockJustification, ProofBlockPremise};
 
-use super::errors::{ParsingError, ParsingErrorContext, ProofParsingError, ProofStepParsingError};
+use super::errors::{
+    FunctionApplicationError, FunctionApplicationFailure, ParsingError, ParsingErrorContext,
+    ProofParsingError, ProofStepParsingError,
+};
 use super::index::BuilderIndex;
-use super::language::{FormulaBuilder, FormulaReadableApplicationBuilder, ReadableBuilder};
+use super::language::{
+    DisplayFormulaBuilder, FormulaBuilder, FormulaReadableApplicationBuilder, ReadableBuilder,
+};
 use super::system::{
-    DeductableBuilder, ProofBuilder, ProofBuilderElement, ProofBuilderSmallJustification,
+    DeductableBuilder, Flag, ProofBuilder, ProofBuilderElement, ProofBuilderSmallJustification,
     ProofBuilderSmallStep, ProofBuilderStep, SystemBuilderChild, TheoremBuilder,
 };
 use super::Rule;
 
 #[derive(Debug)]
 pub struct SystemChildJustificationBuilder<'a> {
+    // `None` for a child of the proof's own system or one of its ancestors.
+    system_id: Option<String>,
     id: String,
 
     // TODO: Make this a DeductableBuilder instead of a SystemBuilderChild.
@@ -39,11 +46,21 @@ pub struct SystemChildJustificationBuilder<'a> {
 
 impl<'a> SystemChildJustificationBuilder<'a> {
     fn from_pest(pair: Pair<Rule>) -> Self {
-        assert_eq!(pair.as_rule(), Rule::ident);
+        let (system_id, id) = match pair.as_rule() {
+            Rule::fqid => {
+                let mut inner = pair.into_inner();
+                let system_id = inner.next().unwrap().as_str().to_owned();
+                let id = inner.next().unwrap().as_str().to_owned();
+
+                (Some(system_id), id)
+            }
+            Rule::ident => (None, pair.as_str().to_owned()),
 
-        let id = pair.as_str().to_owned();
+            _ => unreachable!(),
+        };
 
         SystemChildJustificationBuilder {
+            system_id,
             id,
 
             child: OnceCell::new(),
@@ -57,7 +74,23 @@ impl<'a> SystemChildJustificationBuilder<'a> {
         index: &BuilderIndex<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) -> bool {
-        let child = match index.search_system_child(&proof_ref.system_id(), &self.id) {
+        let system_id = match &self.system_id {
+            Some(system_id) if !index.extends(proof_ref.system_id(), system_id) => {
+                errors.err(ParsingError::ProofError(
+                    proof_ref,
+                    ProofParsingError::StepError(
+                        step_ref,
+                        ProofStepParsingError::SystemChildJustificationNotExtended,
+                    ),
+                ));
+                return false;
+            }
+
+            Some(system_id) => system_id,
+            None => proof_ref.system_id(),
+        };
+
+        let child = match index.search_system_child(system_id, &self.id) {
             Some(child) => child,
 
             None => {
@@ -136,22 +169,13 @@ impl<'a> SystemChildJustificationBuilder<'a> {
     fn build_small_steps(
         &self,
         formula: &FormulaBuilder<'a>,
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
-        let justification = match *self.child.get().unwrap() {
-            SystemBuilderChild::Axiom(axiom_ref) => {
-                ProofBuilderSmallJustification::Deductable(DeductableBuilder::Axiom(axiom_ref))
-            }
-            SystemBuilderChild::Theorem(theorem_ref) => {
-                ProofBuilderSmallJustification::Deductable(DeductableBuilder::Theorem(theorem_ref))
-            }
+        premises: Option<Vec<ProofBlockPremise>>,
+        substitution: Vec<(usize, FormulaBuilder<'a>)>,
+    ) -> Vec<ProofBuilderSmallStep<'a>> {
+        let justification =
+            ProofBuilderSmallJustification::Deductable(self.deductable(), premises, substitution);
 
-            _ => unreachable!(),
-        };
-
-        Some(vec![ProofBuilderSmallStep::new(
-            justification,
-            formula.clone(),
-        )])
+        vec![ProofBuilderSmallStep::new(justification, formula.clone())]
     }
 
     fn finish<'b>(&self) -> ProofBlockJustification<'b> {
@@ -173,76 +197,122 @@ impl<'a> SystemChildJustificationBuilder<'a> {
         }
     }
 
+    pub fn deductable(&self) -> DeductableBuilder<'a> {
+        match *self.child.get().unwrap() {
+            SystemBuilderChild::Axiom(axiom_ref) => DeductableBuilder::Axiom(axiom_ref),
+            SystemBuilderChild::Theorem(theorem_ref) => DeductableBuilder::Theorem(theorem_ref),
+
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn system_id(&self) -> Option<&str> {
+        self.system_id.as_deref()
+    }
+
     pub fn id(&self) -> &str {
         &self.id
     }
 }
 
-enum FunctionApplicationStackItem<'a, 'iter> {
-    Pair(&'iter FormulaBuilder<'a>, &'iter FormulaBuilder<'a>),
-    Prepared(ProofBuilderSmallStep<'a>),
+enum FunctionApplicationStackItem<'a> {
+    Pair(
+        ReadableBuilder<'a>,
+        &'a FormulaBuilder<'a>,
+        &'a FormulaBuilder<'a>,
+    ),
+    Prepared(Box<ProofBuilderSmallStep<'a>>),
 }
 
-struct FunctionApplicationIter<'a, 'iter> {
-    stack: Vec<FunctionApplicationStackItem<'a, 'iter>>,
-    relation: ReadableBuilder<'a>,
-    prev_steps: &'iter [ProofBuilderElement<'a>],
+struct FunctionApplicationIter<'a> {
+    stack: Vec<FunctionApplicationStackItem<'a>>,
+    prev_steps: &'a [ProofBuilderElement<'a>],
+    // Formulas derived by earlier small steps of the same step.
+    derived: Vec<FormulaBuilder<'a>>,
 }
 
-impl<'a, 'iter> FunctionApplicationIter<'a, 'iter> {
+impl<'a> FunctionApplicationIter<'a> {
     fn new(
-        left: &'iter FormulaBuilder<'a>,
-        right: &'iter FormulaBuilder<'a>,
+        left: &'a FormulaBuilder<'a>,
+        right: &'a FormulaBuilder<'a>,
         relation: ReadableBuilder<'a>,
-        prev_steps: &'iter [ProofBuilderElement<'a>],
+        prev_steps: &'a [ProofBuilderElement<'a>],
     ) -> Self {
         FunctionApplicationIter {
-            stack: vec![FunctionApplicationStackItem::Pair(left, right)],
-            relation,
+            stack: vec![FunctionApplicationStackItem::Pair(relation, left, right)],
             prev_steps,
+            derived: Vec::new(),
         }
     }
 
+    fn with_derived(mut self, derived: &[FormulaBuilder<'a>]) -> Self {
+        self.derived.extend_from_slice(derived);
+        self
+    }
+
     fn formula_already_derived(&self, formula: &FormulaBuilder<'a>) -> bool {
         self.prev_steps.iter().any(|step| step.eq_formula(formula))
+            || self.derived.iter().any(|derived| derived == formula)
     }
 
-    fn by_reflexivity(&self, formula: FormulaBuilder<'a>) -> ProofBuilderSmallStep<'a> {
-        let reflexive_deductable = self.relation.get_reflexive().unwrap();
+    fn by_reflexivity(
+        relation: ReadableBuilder<'a>,
+        formula: FormulaBuilder<'a>,
+    ) -> Result<ProofBuilderSmallStep<'a>, ProofStepParsingError<'a>> {
+        let reflexive_deductable =
+            relation
+                .get_reflexive()
+                .ok_or(ProofStepParsingError::MacroMissingFlag(
+                    Flag::Reflexive,
+                    relation,
+                ))?;
 
-        ProofBuilderSmallStep::new(
-            ProofBuilderSmallJustification::Deductable(reflexive_deductable),
+        Ok(ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(reflexive_deductable, None, Vec::new()),
             formula,
-        )
+        ))
+    }
+
+    fn failure(
+        relation: ReadableBuilder<'a>,
+        left: &'a FormulaBuilder<'a>,
+        right: &'a FormulaBuilder<'a>,
+        failure: FunctionApplicationFailure<'a>,
+    ) -> ProofStepParsingError<'a> {
+        let e = FunctionApplicationError::new(relation, left, right, failure);
+
+        ProofStepParsingError::MacroFunctionApplication(e)
     }
 
-    fn by_symmetry(&self, formula: FormulaBuilder<'a>) -> ProofBuilderSmallStep<'a> {
-        let symmetry_deductable = self.relation.get_symmetric().unwrap();
+    fn by_symmetry(
+        relation: ReadableBuilder<'a>,
+        formula: FormulaBuilder<'a>,
+    ) -> ProofBuilderSmallStep<'a> {
+        let symmetry_deductable = relation.get_symmetric().unwrap();
 
         ProofBuilderSmallStep::new(
-            ProofBuilderSmallJustification::Deductable(symmetry_deductable),
+            ProofBuilderSmallJustification::Deductable(symmetry_deductable, None, Vec::new()),
             formula,
         )
     }
 }
 
-impl<'a, 'iter> Iterator for FunctionApplicationIter<'a, 'iter> {
-    // TODO: The error of this should contain information about the error.
-    type Item = Result<ProofBuilderSmallStep<'a>, ()>;
+impl<'a> Iterator for FunctionApplicationIter<'a> {
+    type Item = Result<ProofBuilderSmallStep<'a>, ProofStepParsingError<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(item) = self.stack.pop() {
-            let (left, right) = match item {
-                FunctionApplicationStackItem::Pair(left, right) => (left, right),
-                FunctionApplicationStackItem::Prepared(ret) => return Some(Ok(ret)),
+            let (relation, left, right) = match item {
+                FunctionApplicationStackItem::Pair(relation, left, right) => {
+                    (relation, left, right)
+                }
+                FunctionApplicationStackItem::Prepared(ret) => return Some(Ok(*ret)),
             };
 
             // We need to create a small step to justify this formula.
-            let target_formula =
-                FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
-                    self.relation,
-                    vec![left.clone(), right.clone()],
-                ));
+            let target_formula = FormulaBuilder::ReadableApplication(
+                FormulaReadableApplicationBuilder::new(relation, vec![left.clone(), right.clone()]),
+            );
 
             // If this was derived in a previous step, then there is no work to do. Move on to the
             // next item in the stack.
@@ -252,20 +322,20 @@ impl<'a, 'iter> Iterator for FunctionApplicationIter<'a, 'iter> {
 
             // If this formula can be derived by reflexivity.
             if left == right {
-                return Some(Ok(self.by_reflexivity(target_formula)));
+                return Some(Self::by_reflexivity(relation, target_formula));
             }
 
             // If this was derived in a previous step, but backwards, and the relation is
             // symmetric, then we can get what we need by applying that symmetry.
-            if self.relation.is_symmetric() {
+            if relation.is_symmetric() {
                 let reversed_formula =
                     FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
-                        self.relation,
+                        relation,
                         vec![right.clone(), left.clone()],
                     ));
 
                 if self.formula_already_derived(&reversed_formula) {
-                    return Some(Ok(self.by_symmetry(target_formula)));
+                    return Some(Ok(Self::by_symmetry(relation, target_formula)));
                 }
             }
 
@@ -274,162 +344,1108 @@ impl<'a, 'iter> Iterator for FunctionApplicationIter<'a, 'iter> {
                 (left.application(), right.application())
             {
                 if left_function != right_function {
-                    return Some(Err(()));
+                    return Some(Err(Self::failure(
+                        relation,
+                        left,
+                        right,
+                        FunctionApplicationFailure::FunctionMismatch(left_function, right_function),
+                    )));
                 }
 
                 if left_inputs.len() != right_inputs.len() {
-                    return Some(Err(()));
+                    return Some(Err(Self::failure(
+                        relation,
+                        left,
+                        right,
+                        FunctionApplicationFailure::ArityMismatch(
+                            left_function,
+                            left_inputs.len(),
+                            right_inputs.len(),
+                        ),
+                    )));
                 }
 
-                let function_deductable = match left_function.get_function(self.relation) {
-                    Some(deductable) => deductable,
-                    None => return Some(Err(())),
+                let function = match left_function.get_function(relation) {
+                    Some(function) => function,
+                    None => {
+                        return Some(Err(Self::failure(
+                            relation,
+                            left,
+                            right,
+                            FunctionApplicationFailure::NotFunction(left_function),
+                        )))
+                    }
                 };
 
                 // We're good to go. Push the work to do on the stack, and move on to the next.
                 let target_step = ProofBuilderSmallStep::new(
-                    ProofBuilderSmallJustification::Deductable(function_deductable),
+                    ProofBuilderSmallJustification::Deductable(
+                        function.deductable(),
+                        None,
+                        Vec::new(),
+                    ),
                     target_formula,
                 );
                 self.stack
-                    .push(FunctionApplicationStackItem::Prepared(target_step));
-
-                let input_steps = left_inputs
-                    .zip(right_inputs)
-                    .map(|(left, right)| FunctionApplicationStackItem::Pair(left, right));
+                    .push(FunctionApplicationStackItem::Prepared(Box::new(
+                        target_step,
+                    )));
+
+                // Each pair of inputs is related by the relation the function claim gives for it,
+                // backwards if the function reverses that relation.
+                let input_steps = function
+                    .inputs()
+                    .iter()
+                    .zip(left_inputs.zip(right_inputs))
+                    .map(|(input, (left, right))| {
+                        if input.reversed() {
+                            FunctionApplicationStackItem::Pair(input.relation(), right, left)
+                        } else {
+                            FunctionApplicationStackItem::Pair(input.relation(), left, right)
+                        }
+                    });
                 self.stack.extend(input_steps.rev());
 
                 continue;
             }
 
             // If we've reached here, then every possible method has failed.
-            return Some(Err(()));
+            return Some(Err(Self::failure(
+                relation,
+                left,
+                right,
+                FunctionApplicationFailure::NotApplications,
+            )));
         }
 
         None
     }
 }
 
+// A formula rewritten from another, along with small steps which relate the original to it by
+// `relation`. There are no small steps if nothing was rewritten.
+struct Rewrite<'a> {
+    relation: ReadableBuilder<'a>,
+    formula: FormulaBuilder<'a>,
+    small_steps: Vec<ProofBuilderSmallStep<'a>>,
+}
+
+type RewriteResult<'a> = Result<Rewrite<'a>, ProofStepParsingError<'a>>;
+
+impl<'a> Rewrite<'a> {
+    fn unchanged(relation: ReadableBuilder<'a>, formula: FormulaBuilder<'a>) -> Self {
+        Rewrite {
+            relation,
+            formula,
+            small_steps: Vec::new(),
+        }
+    }
+
+    fn is_unchanged(&self) -> bool {
+        self.small_steps.is_empty()
+    }
+
+    fn relate(
+        relation: ReadableBuilder<'a>,
+        left: &FormulaBuilder<'a>,
+        right: &FormulaBuilder<'a>,
+    ) -> FormulaBuilder<'a> {
+        FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
+            relation,
+            vec![left.clone(), right.clone()],
+        ))
+    }
+
+    fn apply(function: ReadableBuilder<'a>, inputs: Vec<FormulaBuilder<'a>>) -> FormulaBuilder<'a> {
+        FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
+            function, inputs,
+        ))
+    }
+
+    // A single instance of a claim with no hypotheses, such as `'a + 'b = 'b + 'a`.
+    fn by_claim(
+        deductable: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        original: &FormulaBuilder<'a>,
+        formula: FormulaBuilder<'a>,
+    ) -> Self {
+        let small_step = ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(deductable, None, Vec::new()),
+            Self::relate(relation, original, &formula),
+        );
+
+        Rewrite {
+            relation,
+            formula,
+            small_steps: vec![small_step],
+        }
+    }
+
+    // Follows this rewrite of `original` with a rewrite of its result.
+    fn then(self, original: &FormulaBuilder<'a>, next: Self) -> RewriteResult<'a> {
+        if self.is_unchanged() {
+            return Ok(next);
+        }
+        if next.is_unchanged() {
+            return Ok(self);
+        }
+
+        let relation = self.relation;
+        let transitive_deductable =
+            relation
+                .get_transitive()
+                .ok_or(ProofStepParsingError::MacroMissingFlag(
+                    Flag::Transitive,
+                    relation,
+                ))?;
+
+        let mut small_steps = self.small_steps;
+        small_steps.extend(next.small_steps);
+        small_steps.push(MacroJustificationBuilder::by_transitivity(
+            transitive_deductable,
+            relation,
+            original,
+            &next.formula,
+        ));
+
+        Ok(Rewrite {
+            relation,
+            formula: next.formula,
+            small_steps,
+        })
+    }
+
+    // Rewrites `original`, an application of `function`, into `formula` by rewriting its inputs.
+    // Each input which changes must be rewritten by the relation the function claim gives for it,
+    // and in the same direction.
+    fn congruence(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        original: &FormulaBuilder<'a>,
+        formula: FormulaBuilder<'a>,
+        inputs: Vec<Self>,
+    ) -> RewriteResult<'a> {
+        if inputs.iter().all(Self::is_unchanged) {
+            return Ok(Self::unchanged(relation, formula));
+        }
+
+        let missing_function = || ProofStepParsingError::MacroMissingFunction(function, relation);
+        let function_claim = function
+            .get_function(relation)
+            .ok_or_else(missing_function)?;
+        let (_, original_inputs) = original.application().unwrap();
+
+        let mut small_steps = Vec::new();
+        let iter = function_claim
+            .inputs()
+            .iter()
+            .zip(original_inputs)
+            .zip(inputs);
+        for ((input, original_input), rewrite) in iter {
+            if rewrite.is_unchanged() {
+                let target_formula = Self::relate(input.relation(), original_input, original_input);
+                small_steps.push(FunctionApplicationIter::by_reflexivity(
+                    input.relation(),
+                    target_formula,
+                )?);
+            } else if input.reversed() || input.relation() != rewrite.relation {
+                return Err(missing_function());
+            } else {
+                small_steps.extend(rewrite.small_steps);
+            }
+        }
+
+        small_steps.push(ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(
+                function_claim.deductable(),
+                None,
+                Vec::new(),
+            ),
+            Self::relate(relation, original, &formula),
+        ));
+
+        Ok(Rewrite {
+            relation,
+            formula,
+            small_steps,
+        })
+    }
+
+    // Rewrites `left` into `right` by swapping the operands of commutative functions.
+    fn commute(
+        relation: ReadableBuilder<'a>,
+        left: &FormulaBuilder<'a>,
+        right: &FormulaBuilder<'a>,
+    ) -> RewriteResult<'a> {
+        if left == right {
+            return Ok(Self::unchanged(relation, right.clone()));
+        }
+
+        let ((left_function, left_inputs), (right_function, right_inputs)) = left
+            .application()
+            .zip(right.application())
+            .ok_or(ProofStepParsingError::MacroRearrangementMismatch)?;
+        let left_inputs: Vec<_> = left_inputs.collect();
+        let right_inputs: Vec<_> = right_inputs.collect();
+
+        if left_function != right_function || left_inputs.len() != right_inputs.len() {
+            return Err(ProofStepParsingError::MacroRearrangementMismatch);
+        }
+
+        // Inputs are only swapped if they can't be related where they are.
+        let in_place = Self::commute_inputs(
+            relation,
+            left_function,
+            left,
+            right,
+            &left_inputs,
+            &right_inputs,
+        );
+        if in_place.is_ok() || left_inputs.len() != 2 {
+            return in_place;
+        }
+
+        if let Some(commutative_deductable) = left_function.get_commutative(relation) {
+            let swapped_inputs = [left_inputs[1], left_inputs[0]];
+            let swapped = Self::apply(
+                left_function,
+                vec![swapped_inputs[0].clone(), swapped_inputs[1].clone()],
+            );
+
+            let rest = Self::commute_inputs(
+                relation,
+                left_function,
+                &swapped,
+                right,
+                &swapped_inputs,
+                &right_inputs,
+            );
+            if let Ok(rest) = rest {
+                return Self::by_claim(commutative_deductable, relation, left, swapped)
+                    .then(left, rest);
+            }
+        }
+
+        in_place
+    }
+
+    fn commute_inputs(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        left: &FormulaBuilder<'a>,
+        right: &FormulaBuilder<'a>,
+        left_inputs: &[&FormulaBuilder<'a>],
+        right_inputs: &[&FormulaBuilder<'a>],
+    ) -> RewriteResult<'a> {
+        let function_claim =
+            function
+                .get_function(relation)
+                .ok_or(ProofStepParsingError::MacroMissingFunction(
+                    function, relation,
+                ))?;
+
+        let rewrites = function_claim
+            .inputs()
+            .iter()
+            .zip(left_inputs.iter().zip(right_inputs))
+            .map(|(input, (left_input, right_input))| {
+                Self::commute(input.relation(), left_input, right_input)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Self::congruence(relation, function, left, right.clone(), rewrites)
+    }
+
+    // Rewrites `formula` into a normal form, in which the operands of every commutative function
+    // are sorted, and those of every function which is also associative are grouped to the right.
+    fn normalize(relation: ReadableBuilder<'a>, formula: &FormulaBuilder<'a>) -> RewriteResult<'a> {
+        let (function, inputs) = match formula.application() {
+            Some((function, inputs)) => (function, inputs.collect::<Vec<_>>()),
+            None => return Ok(Self::unchanged(relation, formula.clone())),
+        };
+
+        // Inputs which the function reverses are left as they are, since their normal forms are
+        // related to them the wrong way around.
+        let normalized_inputs = match function.get_function(relation) {
+            Some(function_claim) => {
+                let rewrites = function_claim
+                    .inputs()
+                    .iter()
+                    .zip(&inputs)
+                    .map(|(input, formula)| {
+                        if input.reversed() {
+                            Ok(Self::unchanged(input.relation(), (*formula).clone()))
+                        } else {
+                            Self::normalize(input.relation(), formula)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let target = Self::apply(
+                    function,
+                    rewrites
+                        .iter()
+                        .map(|rewrite| rewrite.formula.clone())
+                        .collect(),
+                );
+                Self::congruence(relation, function, formula, target, rewrites)?
+            }
+
+            None => Self::unchanged(relation, formula.clone()),
+        };
+
+        if inputs.len() != 2 {
+            return Ok(normalized_inputs);
+        }
+
+        let sorted = match (
+            function.get_commutative(relation),
+            function.get_associative(relation),
+        ) {
+            (Some(_), Some(_)) => {
+                Self::sort_operands(relation, function, &normalized_inputs.formula)?
+            }
+
+            // The operands of a function which is only commutative are sorted by swapping them.
+            (Some(commutative_deductable), None) => {
+                let (_, left, right) = normalized_inputs.formula.binary().unwrap();
+
+                if right.render_plain() < left.render_plain() {
+                    let swapped = Self::apply(function, vec![right.clone(), left.clone()]);
+                    Self::by_claim(
+                        commutative_deductable,
+                        relation,
+                        &normalized_inputs.formula,
+                        swapped,
+                    )
+                } else {
+                    Self::unchanged(relation, normalized_inputs.formula.clone())
+                }
+            }
+
+            _ => return Ok(normalized_inputs),
+        };
+
+        normalized_inputs.then(formula, sorted)
+    }
+
+    fn push_operands(
+        function: ReadableBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+        operands: &mut Vec<FormulaBuilder<'a>>,
+    ) {
+        match formula.binary() {
+            Some((formula_function, left, right)) if formula_function == function => {
+                Self::push_operands(function, left, operands);
+                Self::push_operands(function, right, operands);
+            }
+
+            _ => operands.push(formula.clone()),
+        }
+    }
+
+    fn sort_operands(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+    ) -> RewriteResult<'a> {
+        let mut operands = Vec::new();
+        Self::push_operands(function, formula, &mut operands);
+        operands.sort_by_cached_key(FormulaBuilder::render_plain);
+
+        let grouped = Self::group_right(relation, function, formula)?;
+        let sorted = Self::permute(relation, function, &grouped.formula, &operands)?;
+
+        grouped.then(formula, sorted)
+    }
+
+    // Rewrites every `f(f('a, 'b), 'c)` into `f('a, f('b, 'c))`, until the operands of `function`
+    // form a list.
+    fn group_right(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+    ) -> RewriteResult<'a> {
+        let (left, right) = match formula.binary() {
+            Some((formula_function, left, right)) if formula_function == function => (left, right),
+            _ => return Ok(Self::unchanged(relation, formula.clone())),
+        };
+
+        match left.binary() {
+            Some((left_function, a, b)) if left_function == function => {
+                let associative_deductable = function.get_associative(relation).unwrap();
+                let regrouped = Self::apply(
+                    function,
+                    vec![
+                        a.clone(),
+                        Self::apply(function, vec![b.clone(), right.clone()]),
+                    ],
+                );
+
+                let regroup = Self::by_claim(associative_deductable, relation, formula, regrouped);
+                let rest = Self::group_right(relation, function, &regroup.formula)?;
+
+                regroup.then(formula, rest)
+            }
+
+            _ => {
+                let tail = Self::group_right(relation, function, right)?;
+                let target = Self::apply(function, vec![left.clone(), tail.formula.clone()]);
+                let head = Self::unchanged(relation, left.clone());
+
+                Self::congruence(relation, function, formula, target, vec![head, tail])
+            }
+        }
+    }
+
+    // Reorders a list of the operands of `function` to match `operands`.
+    fn permute(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+        operands: &[FormulaBuilder<'a>],
+    ) -> RewriteResult<'a> {
+        if operands.len() < 2 {
+            return Ok(Self::unchanged(relation, formula.clone()));
+        }
+
+        let (_, head, tail) = formula.binary().unwrap();
+        if *head != operands[0] {
+            let front = Self::bring_to_front(relation, function, formula, &operands[0])?;
+            let rest = Self::permute(relation, function, &front.formula, operands)?;
+
+            return front.then(formula, rest);
+        }
+
+        let tail = Self::permute(relation, function, tail, &operands[1..])?;
+        let target = Self::apply(function, vec![head.clone(), tail.formula.clone()]);
+        let head = Self::unchanged(relation, head.clone());
+
+        Self::congruence(relation, function, formula, target, vec![head, tail])
+    }
+
+    // Moves `operand` to the front of a list of the operands of `function`, which it's in but
+    // doesn't start.
+    fn bring_to_front(
+        relation: ReadableBuilder<'a>,
+        function: ReadableBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+        operand: &FormulaBuilder<'a>,
+    ) -> RewriteResult<'a> {
+        let commutative_deductable = function.get_commutative(relation).unwrap();
+        let associative_deductable = function.get_associative(relation).unwrap();
+
+        let (_, head, tail) = formula.binary().unwrap();
+        let (tail_head, tail_tail) = match tail.binary() {
+            Some((tail_function, tail_head, tail_tail)) if tail_function == function => {
+                (tail_head, tail_tail)
+            }
+
+            // The list has two operands, so swapping them is enough.
+            _ => {
+                let swapped = Self::apply(function, vec![tail.clone(), head.clone()]);
+                return Ok(Self::by_claim(
+                    commutative_deductable,
+                    relation,
+                    formula,
+                    swapped,
+                ));
+            }
+        };
+
+        // First move the operand to the front of the tail, so that the list is
+        // `f(head, f(operand, rest))`.
+        let (front, rest) = if tail_head == operand {
+            (
+                Self::unchanged(relation, formula.clone()),
+                tail_tail.clone(),
+            )
+        } else {
+            let tail_front = Self::bring_to_front(relation, function, tail, operand)?;
+            let (_, _, rest) = tail_front.formula.binary().unwrap();
+            let rest = rest.clone();
+
+            let target = Self::apply(function, vec![head.clone(), tail_front.formula.clone()]);
+            let head = Self::unchanged(relation, head.clone());
+            let front =
+                Self::congruence(relation, function, formula, target, vec![head, tail_front])?;
+
+            (front, rest)
+        };
+
+        // Then `f(head, f(operand, rest))` becomes `f(f(operand, rest), head)`, then
+        // `f(operand, f(rest, head))`, then `f(operand, f(head, rest))`.
+        let operand_rest = Self::apply(function, vec![operand.clone(), rest.clone()]);
+        let rest_head = Self::apply(function, vec![rest.clone(), head.clone()]);
+        let head_rest = Self::apply(function, vec![head.clone(), rest]);
+
+        let commuted = Self::apply(function, vec![operand_rest, head.clone()]);
+        let commute = Self::by_claim(
+            commutative_deductable,
+            relation,
+            &front.formula,
+            commuted.clone(),
+        );
+
+        let regrouped = Self::apply(function, vec![operand.clone(), rest_head.clone()]);
+        let regroup = Self::by_claim(
+            associative_deductable,
+            relation,
+            &commuted,
+            regrouped.clone(),
+        );
+
+        let target = Self::apply(function, vec![operand.clone(), head_rest.clone()]);
+        let swap_tail = Self::by_claim(commutative_deductable, relation, &rest_head, head_rest);
+        let swap = Self::congruence(
+            relation,
+            function,
+            &regrouped,
+            target,
+            vec![Self::unchanged(relation, operand.clone()), swap_tail],
+        )?;
+
+        front
+            .then(formula, commute)?
+            .then(formula, regroup)?
+            .then(formula, swap)
+    }
+}
+
+// A claim with a binary assertion, such as `'a + 0 = 'a`, used to rewrite instances of its left
+// side into the same instances of its right side.
+struct RewriteRule<'a> {
+    relation: ReadableBuilder<'a>,
+    left: &'a FormulaBuilder<'a>,
+    right: &'a FormulaBuilder<'a>,
+    vars: usize,
+}
+
+impl<'a> RewriteRule<'a> {
+    fn new(deductable: DeductableBuilder<'a>) -> Option<Self> {
+        let (relation, left, right) = deductable.assertion().binary()?;
+
+        Some(RewriteRule {
+            relation,
+            left,
+            right,
+            vars: deductable.vars().len(),
+        })
+    }
+
+    // Binds the variables of the claim so that `pattern` becomes `formula`, if it can.
+    fn match_instance(
+        pattern: &'a FormulaBuilder<'a>,
+        formula: &FormulaBuilder<'a>,
+        bindings: &mut [Option<FormulaBuilder<'a>>],
+    ) -> bool {
+        if let Some(var) = pattern.variable() {
+            return match &bindings[var.index()] {
+                Some(bound) => bound == formula,
+                None => {
+                    bindings[var.index()] = Some(formula.clone());
+                    true
+                }
+            };
+        }
+
+        match (pattern.application(), formula.application()) {
+            (Some((pattern_function, pattern_inputs)), Some((function, inputs))) => {
+                pattern_function == function
+                    && pattern_inputs.len() == inputs.len()
+                    && pattern_inputs
+                        .zip(inputs)
+                        .all(|(pattern, input)| Self::match_instance(pattern, input, bindings))
+            }
+
+            _ => pattern == formula,
+        }
+    }
+
+    // Returns `None` if `pattern` has a variable which isn't bound.
+    fn substitute(
+        pattern: &'a FormulaBuilder<'a>,
+        bindings: &[Option<FormulaBuilder<'a>>],
+    ) -> Option<FormulaBuilder<'a>> {
+        if let Some(var) = pattern.variable() {
+            return bindings[var.index()].clone();
+        }
+
+        match pattern.application() {
+            Some((function, inputs)) => {
+                let inputs = inputs
+                    .map(|input| Self::substitute(input, bindings))
+                    .collect::<Option<_>>()?;
+
+                Some(Rewrite::apply(function, inputs))
+            }
+
+            None => Some(pattern.clone()),
+        }
+    }
+
+    // Walks `original` and `rewritten` together, and collects the instances of the claim which
+    // turn each subformula where they differ into the other. Returns `false` if some difference
+    // isn't an instance of the claim.
+    fn collect_instances(
+        &self,
+        original: &FormulaBuilder<'a>,
+        rewritten: &FormulaBuilder<'a>,
+        claims: &mut Vec<FormulaBuilder<'a>>,
+    ) -> bool {
+        if original == rewritten {
+            return true;
+        }
+
+        let mut bindings = vec![None; self.vars];
+        if Self::match_instance(self.left, original, &mut bindings)
+            && Self::substitute(self.right, &bindings).as_ref() == Some(rewritten)
+        {
+            let claim = Rewrite::relate(self.relation, original, rewritten);
+            if !claims.contains(&claim) {
+                claims.push(claim);
+            }
+
+            return true;
+        }
+
+        match (original.application(), rewritten.application()) {
+            (Some((original_function, original_inputs)), Some((function, inputs))) => {
+                original_function == function
+                    && original_inputs.len() == inputs.len()
+                    && original_inputs
+                        .zip(inputs)
+                        .all(|(original, input)| self.collect_instances(original, input, claims))
+            }
+
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug)]
-pub enum MacroJustificationBuilder {
+pub enum MacroJustificationBuilder<'a> {
     Definition,
     FunctionApplication,
     Substitution,
+    Commutativity,
+    AssociativityCommutativity,
+    Rewrite(SystemChildJustificationBuilder<'a>),
 }
 
-impl MacroJustificationBuilder {
+impl<'a> MacroJustificationBuilder<'a> {
     fn from_pest(pair: Pair<Rule>) -> Self {
         assert_eq!(pair.as_rule(), Rule::macro_justification);
 
-        match pair.into_inner().next().unwrap().as_rule() {
+        let pair = pair.into_inner().next().unwrap();
+        match pair.as_rule() {
             Rule::macro_justification_by_definition => Self::Definition,
             Rule::macro_justification_by_function_application => Self::FunctionApplication,
             Rule::macro_justification_by_substitution => Self::Substitution,
+            Rule::macro_justification_by_commutativity => Self::Commutativity,
+            Rule::macro_justification_by_ac => Self::AssociativityCommutativity,
+            Rule::macro_justification_by_rewrite => Self::Rewrite(
+                SystemChildJustificationBuilder::from_pest(pair.into_inner().next().unwrap()),
+            ),
 
             _ => unreachable!(),
         }
     }
 
-    // TODO: This should return a Result.
-    fn build_function_application<'a>(
-        formula: &FormulaBuilder<'a>,
-        prev_steps: &[ProofBuilderElement<'a>],
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
-        if let Some((relation, left, right)) = formula.binary() {
-            if !relation.is_preorder() {
-                todo!()
-            }
+    fn build_function_application(
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let (relation, left, right) = formula
+            .binary()
+            .ok_or(ProofStepParsingError::MacroFormulaNotBinary)?;
 
-            let result: Result<_, _> =
-                FunctionApplicationIter::new(left, right, relation, prev_steps).collect();
-            result.ok()
-        } else {
-            todo!()
-        }
+        FunctionApplicationIter::new(left, right, relation, prev_steps).collect()
     }
 
-    fn try_build_substitution<'a>(
-        step: &ProofBuilderStep<'a>,
+    fn by_transitivity(
+        transitive_deductable: DeductableBuilder<'a>,
         relation: ReadableBuilder<'a>,
         left: &FormulaBuilder<'a>,
         right: &FormulaBuilder<'a>,
-        prev_steps: &[ProofBuilderElement<'a>],
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
-        let (step_relation, step_left, step_right) = step.formula().binary()?;
+    ) -> ProofBuilderSmallStep<'a> {
+        let formula = FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
+            relation,
+            vec![left.clone(), right.clone()],
+        ));
+
+        ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(transitive_deductable, None, Vec::new()),
+            formula,
+        )
+    }
+
+    fn try_build_substitution(
+        step: &'a ProofBuilderStep<'a>,
+        relation: ReadableBuilder<'a>,
+        left: &'a FormulaBuilder<'a>,
+        right: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Option<Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>>> {
+        let (step_relation, step_left, step_right) = step.formula()?.binary()?;
 
         if step_relation != relation {
             return None;
         }
 
-        let transitive_deductable = relation.get_transitive().unwrap();
+        let transitive_deductable = match relation.get_transitive() {
+            Some(deductable) => deductable,
+            None => {
+                return Some(Err(ProofStepParsingError::MacroMissingFlag(
+                    Flag::Transitive,
+                    relation,
+                )))
+            }
+        };
+
+        // Sides which already agree with the previous step are left alone, so that relations
+        // which aren't reflexive, such as strict orders, can still be substituted into. If
+        // neither side changes, then the step is rederived by relating its right side to itself.
+        let change_left = left != step_left;
+        let change_right = right != step_right || !change_left;
+
+        let mut small_steps = Vec::new();
+
+        if change_left {
+            let left_steps = FunctionApplicationIter::new(left, step_left, relation, prev_steps);
+
+            for small_step in left_steps {
+                match small_step {
+                    Ok(small_step) => small_steps.push(small_step),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        }
+
+        if change_right {
+            let right_steps = FunctionApplicationIter::new(step_right, right, relation, prev_steps);
+
+            for small_step in right_steps {
+                match small_step {
+                    Ok(small_step) => small_steps.push(small_step),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        }
+
+        if change_left {
+            small_steps.push(Self::by_transitivity(
+                transitive_deductable,
+                relation,
+                left,
+                step_right,
+            ));
+        }
+
+        if change_right {
+            small_steps.push(Self::by_transitivity(
+                transitive_deductable,
+                relation,
+                left,
+                right,
+            ));
+        }
+
+        Some(Ok(small_steps))
+    }
+
+    fn build_substitution(
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let (relation, left, right) = formula
+            .binary()
+            .ok_or(ProofStepParsingError::MacroFormulaNotBinary)?;
+
+        // Report why the last step which could have been substituted into failed, if every one
+        // does.
+        let mut last_error = ProofStepParsingError::MacroSubstitutionNoStep;
+        for step in prev_steps.iter().filter_map(ProofBuilderElement::step) {
+            match Self::try_build_substitution(step, relation, left, right, prev_steps) {
+                Some(Ok(small_steps)) => return Ok(small_steps),
+                Some(Err(e)) => last_error = e,
+                None => {}
+            }
+        }
+
+        Err(last_error)
+    }
 
-        let left_steps = FunctionApplicationIter::new(left, step_left, relation, prev_steps);
-        let right_steps = FunctionApplicationIter::new(step_right, right, relation, prev_steps);
+    // The step relates its sides by reflexivity if they're already the same.
+    fn build_rewrite(
+        relation: ReadableBuilder<'a>,
+        left: &FormulaBuilder<'a>,
+        right: &FormulaBuilder<'a>,
+        rewrite: Rewrite<'a>,
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        if rewrite.is_unchanged() {
+            let formula = Rewrite::relate(relation, left, right);
+            return Ok(vec![FunctionApplicationIter::by_reflexivity(
+                relation, formula,
+            )?]);
+        }
 
-        let join_left = std::iter::once_with(|| {
-            let formula =
-                FormulaBuilder::ReadableApplication(FormulaReadableApplicationBuilder::new(
+        Ok(rewrite.small_steps)
+    }
+
+    fn build_commutativity(
+        formula: &'a FormulaBuilder<'a>,
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let (relation, left, right) = formula
+            .binary()
+            .ok_or(ProofStepParsingError::MacroFormulaNotBinary)?;
+
+        let rewrite = Rewrite::commute(relation, left, right)?;
+        Self::build_rewrite(relation, left, right, rewrite)
+    }
+
+    // Both sides are rewritten into their normal forms, and the right side is then related back
+    // from its normal form by symmetry.
+    fn build_associativity_commutativity(
+        formula: &'a FormulaBuilder<'a>,
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let (relation, left, right) = formula
+            .binary()
+            .ok_or(ProofStepParsingError::MacroFormulaNotBinary)?;
+
+        let left_normalized = Rewrite::normalize(relation, left)?;
+        let right_normalized = Rewrite::normalize(relation, right)?;
+        if left_normalized.formula != right_normalized.formula {
+            return Err(ProofStepParsingError::MacroRearrangementMismatch);
+        }
+
+        if right_normalized.is_unchanged() {
+            return Self::build_rewrite(relation, left, right, left_normalized);
+        }
+
+        let symmetric_deductable =
+            relation
+                .get_symmetric()
+                .ok_or(ProofStepParsingError::MacroMissingFlag(
+                    Flag::Symmetric,
                     relation,
-                    vec![left.clone(), step_right.clone()],
-                ));
+                ))?;
+
+        let mut small_steps = right_normalized.small_steps;
+        small_steps.push(ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(symmetric_deductable, None, Vec::new()),
+            Rewrite::relate(relation, &right_normalized.formula, right),
+        ));
+        let unnormalize = Rewrite {
+            relation,
+            formula: right.clone(),
+            small_steps,
+        };
 
-            Ok(ProofBuilderSmallStep::new(
-                ProofBuilderSmallJustification::Deductable(transitive_deductable),
-                formula,
-            ))
-        });
-        let join_right = std::iter::once_with(|| {
-            let formula = FormulaBuilder::ReadableApplication(
-                FormulaReadableApplicationBuilder::new(relation, vec![left.clone(), right.clone()]),
-            );
+        let rewrite = left_normalized.then(left, unnormalize)?;
+        Self::build_rewrite(relation, left, right, rewrite)
+    }
 
-            Ok(ProofBuilderSmallStep::new(
-                ProofBuilderSmallJustification::Deductable(transitive_deductable),
-                formula,
-            ))
-        });
+    // Relates each side of a rewritten relation to the same side of the original one, by the
+    // relation of the claim, and joins the three with mixed transitivity: the new left side to the
+    // old, the old relation, then the old right side to the new.
+    fn build_rewrite_sides(
+        claim_relation: ReadableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        (step_left, step_right): (&'a FormulaBuilder<'a>, &'a FormulaBuilder<'a>),
+        (left, right): (&'a FormulaBuilder<'a>, &'a FormulaBuilder<'a>),
+        prev_steps: &'a [ProofBuilderElement<'a>],
+        claims: &[FormulaBuilder<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let mut small_steps = Vec::new();
+        let mut composite = relation;
+
+        if left != step_left {
+            let left_steps =
+                FunctionApplicationIter::new(left, step_left, claim_relation, prev_steps)
+                    .with_derived(claims);
+            for small_step in left_steps {
+                small_steps.push(small_step?);
+            }
 
-        let result: Result<_, _> = left_steps
-            .chain(right_steps)
-            .chain(join_left)
-            .chain(join_right)
-            .collect();
-        result.ok()
+            let (joined, transitive_deductable) =
+                claim_relation.get_transitive_with(composite).ok_or(
+                    ProofStepParsingError::MacroRewriteNotTransitive(claim_relation, composite),
+                )?;
+            small_steps.push(Self::by_transitivity(
+                transitive_deductable,
+                joined,
+                left,
+                step_right,
+            ));
+            composite = joined;
+        }
+
+        if right != step_right {
+            let right_steps =
+                FunctionApplicationIter::new(step_right, right, claim_relation, prev_steps)
+                    .with_derived(claims);
+            for small_step in right_steps {
+                small_steps.push(small_step?);
+            }
+
+            let (joined, transitive_deductable) =
+                composite.get_transitive_with(claim_relation).ok_or(
+                    ProofStepParsingError::MacroRewriteNotTransitive(composite, claim_relation),
+                )?;
+            small_steps.push(Self::by_transitivity(
+                transitive_deductable,
+                joined,
+                left,
+                right,
+            ));
+            composite = joined;
+        }
+
+        if composite != relation {
+            return Err(ProofStepParsingError::MacroRewriteRelationMismatch(
+                composite, relation,
+            ));
+        }
+
+        Ok(small_steps)
     }
 
-    fn build_substitution<'a>(
-        formula: &FormulaBuilder<'a>,
-        prev_steps: &[ProofBuilderElement<'a>],
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
-        if let Some((relation, left, right)) = formula.binary() {
-            if !relation.is_preorder() {
-                todo!()
+    // Relates the step before to the rewritten step by a relation which can be detached, then
+    // detaches it. This is the relation of the claim if it can be detached itself, and otherwise
+    // one which the function applied by both steps carries.
+    fn build_rewrite_detached(
+        claim_relation: ReadableBuilder<'a>,
+        step_formula: &'a FormulaBuilder<'a>,
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+        claims: &[FormulaBuilder<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let relation = match claim_relation.get_detachment() {
+            Some(_) => claim_relation,
+
+            None => {
+                let (function, _) = formula
+                    .application()
+                    .ok_or(ProofStepParsingError::MacroRewriteNotDetachable(None))?;
+                let (relation, _) = function.get_detachable_function().ok_or(
+                    ProofStepParsingError::MacroRewriteNotDetachable(Some(function)),
+                )?;
+
+                relation
             }
+        };
+        let detachment_deductable = relation.get_detachment().unwrap();
 
-            prev_steps
-                .iter()
-                .filter_map(ProofBuilderElement::step)
-                .find_map(|step| {
-                    Self::try_build_substitution(step, relation, left, right, prev_steps)
-                })
-        } else {
-            todo!()
+        let mut small_steps = Vec::new();
+        let steps = FunctionApplicationIter::new(step_formula, formula, relation, prev_steps)
+            .with_derived(claims);
+        for small_step in steps {
+            small_steps.push(small_step?);
         }
+
+        small_steps.push(ProofBuilderSmallStep::new(
+            ProofBuilderSmallJustification::Deductable(detachment_deductable, None, Vec::new()),
+            formula.clone(),
+        ));
+
+        Ok(small_steps)
     }
 
-    fn build_small_steps<'a>(
+    // The step is the one before it with instances of the claim's left side rewritten into its
+    // right side, wherever the two differ. Those instances of the claim are derived first. If both
+    // steps relate two formulas by the same relation, each side is then rewritten separately and
+    // the sides are joined by transitivity, so the relation of the claim needn't be the relation
+    // of the steps. Otherwise the step before is related to this one through a function claim, and
+    // this one is detached from it.
+    fn build_rewrite_by_claim(
+        deductable: DeductableBuilder<'a>,
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
+        let rule = RewriteRule::new(deductable).ok_or(
+            ProofStepParsingError::MacroRewriteClaimNotBinary(deductable),
+        )?;
+
+        let step_formula = prev_steps
+            .iter()
+            .rev()
+            .find_map(ProofBuilderElement::step)
+            .and_then(ProofBuilderStep::formula)
+            .map(DisplayFormulaBuilder::formula)
+            .ok_or(ProofStepParsingError::MacroRewriteNoStep)?;
+
+        let mut claims = Vec::new();
+        if !rule.collect_instances(step_formula, formula, &mut claims) || claims.is_empty() {
+            return Err(ProofStepParsingError::MacroRewriteNoInstance(deductable));
+        }
+
+        let mut small_steps: Vec<_> = claims
+            .iter()
+            .map(|claim| {
+                ProofBuilderSmallStep::new(
+                    ProofBuilderSmallJustification::Deductable(deductable, None, Vec::new()),
+                    claim.clone(),
+                )
+            })
+            .collect();
+
+        let rest = match (step_formula.binary(), formula.binary()) {
+            (Some((step_relation, step_left, step_right)), Some((relation, left, right)))
+                if step_relation == relation =>
+            {
+                Self::build_rewrite_sides(
+                    rule.relation,
+                    relation,
+                    (step_left, step_right),
+                    (left, right),
+                    prev_steps,
+                    &claims,
+                )?
+            }
+
+            _ => Self::build_rewrite_detached(
+                rule.relation,
+                step_formula,
+                formula,
+                prev_steps,
+                &claims,
+            )?,
+        };
+        small_steps.extend(rest);
+
+        Ok(small_steps)
+    }
+
+    fn build_small_steps(
         &self,
-        formula: &FormulaBuilder<'a>,
-        prev_steps: &[ProofBuilderElement<'a>],
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
         match self {
-            Self::Definition => Some(vec![ProofBuilderSmallStep::new(
+            Self::Definition => Ok(vec![ProofBuilderSmallStep::new(
                 ProofBuilderSmallJustification::Definition,
                 formula.clone(),
             )]),
 
             Self::FunctionApplication => Self::build_function_application(formula, prev_steps),
             Self::Substitution => Self::build_substitution(formula, prev_steps),
+            Self::Commutativity => Self::build_commutativity(formula),
+            Self::AssociativityCommutativity => Self::build_associativity_commutativity(formula),
+            Self::Rewrite(builder) => {
+                Self::build_rewrite_by_claim(builder.deductable(), formula, prev_steps)
+            }
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Definition => "!def",
+            Self::FunctionApplication => "!fun",
+            Self::Substitution => "!sub",
+            Self::Commutativity => "!comm",
+            Self::AssociativityCommutativity => "!ac",
+            Self::Rewrite(_) => "!rw",
         }
     }
 
@@ -438,14 +1454,89 @@ impl MacroJustificationBuilder {
             Self::FunctionApplication => ProofBlockJustification::FunctionApplication,
             Self::Definition => ProofBlockJustification::Definition,
             Self::Substitution => ProofBlockJustification::Substitution,
+            Self::Commutativity => ProofBlockJustification::Commutativity,
+            Self::AssociativityCommutativity => ProofBlockJustification::AssociativityCommutativity,
+            Self::Rewrite(builder) => {
+                ProofBlockJustification::Rewrite(builder.deductable().finish())
+            }
+        }
+    }
+
+    pub fn system_child(&self) -> Option<&SystemChildJustificationBuilder<'a>> {
+        match self {
+            Self::Rewrite(builder) => Some(builder),
+
+            _ => None,
         }
     }
 }
 
+// Justifies the conclusion of a calculation by joining the steps of its links, which come just
+// before it, with claims flagged `transitive`.
+#[derive(Debug)]
+pub struct CalculationJustificationBuilder {
+    links: usize,
+}
+
+impl CalculationJustificationBuilder {
+    pub fn new(links: usize) -> Self {
+        CalculationJustificationBuilder { links }
+    }
+
+    // Returns `None` if one of the links failed to build.
+    pub fn build_small_steps<'a>(
+        &self,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+    ) -> Option<Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>>> {
+        let links: Vec<_> = prev_steps[prev_steps.len() - self.links..]
+            .iter()
+            .map(|element| element.step().unwrap())
+            .collect();
+
+        if !links.iter().all(|link| link.is_built()) {
+            return None;
+        }
+
+        // Every link is an infix relation, so its formula is binary.
+        let (mut relation, left, _) = links[0].formula().unwrap().binary().unwrap();
+
+        let mut small_steps = Vec::with_capacity(self.links - 1);
+        for link in &links[1..] {
+            let (link_relation, _, right) = link.formula().unwrap().binary().unwrap();
+
+            let (composite, transitive_deductable) =
+                match relation.get_transitive_with(link_relation) {
+                    Some(transitive) => transitive,
+                    None => {
+                        return Some(Err(ProofStepParsingError::CalculationNotTransitive(
+                            relation,
+                            link_relation,
+                        )))
+                    }
+                };
+
+            small_steps.push(MacroJustificationBuilder::by_transitivity(
+                transitive_deductable,
+                composite,
+                left,
+                right,
+            ));
+            relation = composite;
+        }
+
+        Some(Ok(small_steps))
+    }
+
+    fn finish<'b>(&self) -> ProofBlockJustification<'b> {
+        ProofBlockJustification::Calculation
+    }
+}
+
 #[derive(Debug)]
 pub enum ProofJustificationBuilder<'a> {
     SystemChild(SystemChildJustificationBuilder<'a>),
-    Macro(MacroJustificationBuilder),
+    Macro(MacroJustificationBuilder<'a>),
+    Calculation(CalculationJustificationBuilder),
     // TODO: Create a HypothesisJustificationBuilder which references the hypothesis itself instead
     // of its index.
     Hypothesis(usize),
@@ -454,7 +1545,9 @@ pub enum ProofJustificationBuilder<'a> {
 impl<'a> ProofJustificationBuilder<'a> {
     pub fn from_pest(pair: Pair<Rule>) -> Self {
         match pair.as_rule() {
-            Rule::ident => Self::SystemChild(SystemChildJustificationBuilder::from_pest(pair)),
+            Rule::fqid | Rule::ident => {
+                Self::SystemChild(SystemChildJustificationBuilder::from_pest(pair))
+            }
             Rule::macro_justification => Self::Macro(MacroJustificationBuilder::from_pest(pair)),
 
             _ => unreachable!(),
@@ -480,7 +1573,11 @@ impl<'a> ProofJustificationBuilder<'a> {
                 builder.verify_structure(proof_ref, step_ref, index, errors)
             }
 
-            Self::Macro(_) => true,
+            Self::Macro(MacroJustificationBuilder::Rewrite(builder)) => {
+                builder.verify_structure(proof_ref, step_ref, index, errors)
+            }
+
+            Self::Macro(_) | Self::Calculation(_) => true,
 
             Self::Hypothesis(id) => {
                 let premise_len = theorem_ref.premise().len();
@@ -514,14 +1611,19 @@ impl<'a> ProofJustificationBuilder<'a> {
 
     pub fn build_small_steps(
         &self,
-        formula: &FormulaBuilder<'a>,
-        prev_steps: &[ProofBuilderElement<'a>],
-        errors: &mut ParsingErrorContext,
-    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
+        formula: &'a FormulaBuilder<'a>,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+        premises: Option<Vec<ProofBlockPremise>>,
+        substitution: Vec<(usize, FormulaBuilder<'a>)>,
+    ) -> Result<Vec<ProofBuilderSmallStep<'a>>, ProofStepParsingError<'a>> {
         match self {
-            Self::SystemChild(justification) => justification.build_small_steps(formula),
+            Self::SystemChild(justification) => {
+                Ok(justification.build_small_steps(formula, premises, substitution))
+            }
             Self::Macro(justification) => justification.build_small_steps(formula, prev_steps),
-            Self::Hypothesis(id) => Some(vec![ProofBuilderSmallStep::new(
+            // The conclusion of a calculation has no formula until it's built.
+            Self::Calculation(_) => unreachable!(),
+            Self::Hypothesis(id) => Ok(vec![ProofBuilderSmallStep::new(
                 ProofBuilderSmallJustification::Hypothesis(*id),
                 formula.clone(),
             )]),
@@ -532,6 +1634,7 @@ impl<'a> ProofJustificationBuilder<'a> {
         match self {
             Self::SystemChild(builder) => builder.finish(),
             Self::Macro(builder) => builder.finish(),
+            Self::Calculation(builder) => builder.finish(),
             Self::Hypothesis(id) => ProofBlockJustification::Hypothesis(*id),
         }
     }
@@ -543,4 +1646,466 @@ impl<'a> ProofJustificationBuilder<'a> {
             _ => None,
         }
     }
+
+    // The axiom or theorem cited by the justification, either directly or by a macro.
+    pub fn cited_system_child(&self) -> Option<&SystemChildJustificationBuilder<'a>> {
+        match self {
+            Self::SystemChild(builder) => Some(builder),
+            Self::Macro(builder) => builder.system_child(),
+
+            _ => None,
+        }
+    }
+
+    pub fn macro_justification(&self) -> Option<&MacroJustificationBuilder<'a>> {
+        match self {
+            Self::Macro(builder) => Some(builder),
+
+            _ => None,
+        }
+    }
+
+    pub fn calculation(&self) -> Option<&CalculationJustificationBuilder> {
+        match self {
+            Self::Calculation(builder) => Some(builder),
+
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{check_page, page_diagnostics};
+
+    const ORDER: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol zero : sys {
+  name = "Zero" tagline = {Zero.}
+  type = Nat
+}
+
+\Symbol eq : sys {
+  name = "Eq" tagline = {Eq.}
+  type = Nat -> Nat -> Prop
+  read = Infix = 30 none
+}
+
+\Symbol le : sys {
+  name = "Le" tagline = {Le.}
+  type = Nat -> Nat -> Prop
+  read = Infix <= 30 none
+}
+
+\Symbol lt : sys {
+  name = "Lt" tagline = {Lt.}
+  type = Nat -> Nat -> Prop
+  read = Infix < 30 none
+}
+
+\Symbol plus : sys {
+  name = "Plus" tagline = {Plus.}
+  type = Nat -> Nat -> Nat
+  read = Infix + 50 left
+}
+
+\Symbol times : sys {
+  name = "Times" tagline = {Times.}
+  type = Nat -> Nat -> Nat
+  read = Infix * 60 left
+}
+
+\Axiom eq_refl : sys {
+  name = "ER" tagline = {ER.}
+  flags = [reflexive]
+  var a : Nat
+  assertion = 'a = 'a;
+}
+
+\Axiom eq_symm : sys {
+  name = "ES" tagline = {ES.}
+  flags = [symmetric]
+  var a : Nat
+  var b : Nat
+  premise = [ 'a = 'b; ]
+  assertion = 'b = 'a;
+}
+
+\Axiom eq_trans : sys {
+  name = "ET" tagline = {ET.}
+  flags = [transitive]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ 'a = 'b; 'b = 'c; ]
+  assertion = 'a = 'c;
+}
+
+\Axiom eq_le : sys {
+  name = "EL" tagline = {EL.}
+  flags = [transitive]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ 'a = 'b; 'b <= 'c; ]
+  assertion = 'a <= 'c;
+}
+
+\Axiom le_eq : sys {
+  name = "LE" tagline = {LE.}
+  flags = [transitive]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ 'a <= 'b; 'b = 'c; ]
+  assertion = 'a <= 'c;
+}
+
+\Axiom plus_fun : sys {
+  name = "PF" tagline = {PF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ 'a = 'b; 'c = 'd; ]
+  assertion = 'a + 'c = 'b + 'd;
+}
+
+\Axiom times_fun : sys {
+  name = "TF" tagline = {TF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ 'a = 'b; 'c = 'd; ]
+  assertion = 'a * 'c = 'b * 'd;
+}
+
+\Axiom plus_zero : sys {
+  name = "PZ" tagline = {PZ.}
+  var a : Nat
+  assertion = 'a + zero = 'a;
+}
+"#;
+
+    // A page proving a theorem about `'x`, `'y` and `'z`, with `extra` declared after the rest
+    // of the system.
+    fn goal(extra: &str, premise: &[&str], assertion: &str, proof: &str) -> String {
+        format!(
+            r#"{}
+{}
+\Theorem goal : sys {{
+  name = "G" tagline = {{G.}}
+  var x : Nat
+  var y : Nat
+  var z : Nat
+  premise = [ {} ]
+  assertion = {};
+}}
+
+\Proof goal : sys {{
+{}
+}}
+"#,
+            ORDER,
+            extra,
+            premise
+                .iter()
+                .map(|p| format!("{};", p))
+                .collect::<String>(),
+            assertion,
+            proof,
+        )
+    }
+
+    // Checks the proof on the page made by `goal`.
+    fn prove(extra: &str, premise: &[&str], assertion: &str, proof: &str) -> Vec<&'static str> {
+        check_page(&goal(extra, premise, assertion, proof))
+    }
+
+    #[test]
+    fn calculation() {
+        let codes = prove(
+            "",
+            &[],
+            "('x + zero) + zero = 'x",
+            "calc ('x + zero) + zero
+               _ = 'x + zero | plus_zero;
+               _ = 'x | plus_zero;",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        // Links with different relations are joined by whatever the transitive flags claim.
+        let codes = prove(
+            "",
+            &["'x <= 'y", "'y = 'z"],
+            "'x + zero <= 'z",
+            "calc 'x + zero
+               _ = 'x | plus_zero;
+               _ <= 'y | 1;
+               _ = 'z | 2;",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        let codes = prove(
+            "",
+            &["'x <= 'y", "'y <= 'z"],
+            "'x <= 'z",
+            "calc 'x
+               _ <= 'y | 1;
+               _ <= 'z | 2;",
+        );
+        assert_eq!(codes, ["step-calculation-not-transitive"]);
+    }
+
+    const COMMUTATIVE: &str = r#"
+\Axiom plus_comm : sys {
+  name = "PC" tagline = {PC.}
+  flags = [commutative]
+  var a : Nat
+  var b : Nat
+  assertion = 'a + 'b = 'b + 'a;
+}
+
+\Axiom plus_assoc : sys {
+  name = "PA" tagline = {PA.}
+  flags = [associative]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  assertion = ('a + 'b) + 'c = 'a + ('b + 'c);
+}
+
+\Axiom times_comm : sys {
+  name = "TC" tagline = {TC.}
+  flags = [commutative]
+  var a : Nat
+  var b : Nat
+  assertion = 'a * 'b = 'b * 'a;
+}
+"#;
+
+    #[test]
+    fn commutativity() {
+        let codes = prove(
+            COMMUTATIVE,
+            &[],
+            "('x * 'y) + 'z = 'z + ('y * 'x)",
+            "| !comm | ('x * 'y) + 'z = 'z + ('y * 'x);",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        // Regrouping takes `!ac`.
+        let codes = prove(
+            COMMUTATIVE,
+            &[],
+            "('x + 'y) + 'z = 'x + ('z + 'y)",
+            "| !comm | ('x + 'y) + 'z = 'x + ('z + 'y);",
+        );
+        assert_eq!(codes, ["step-macro-rearrangement"]);
+
+        // Only functions which are claimed to commute can be rearranged.
+        let codes = prove("", &[], "'x + 'y = 'y + 'x", "| !comm | 'x + 'y = 'y + 'x;");
+        assert_eq!(codes, ["step-macro-rearrangement"]);
+    }
+
+    #[test]
+    fn associativity_and_commutativity() {
+        let codes = prove(
+            COMMUTATIVE,
+            &[],
+            "('x + 'y) + ('z + 'x) = 'x + ('x + ('z + 'y))",
+            "| !ac | ('x + 'y) + ('z + 'x) = 'x + ('x + ('z + 'y));",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        let codes = prove(
+            COMMUTATIVE,
+            &[],
+            "'x + 'y = 'x + 'z",
+            "| !ac | 'x + 'y = 'x + 'z;",
+        );
+        assert_eq!(codes, ["step-macro-rearrangement"]);
+
+        // Nothing claims `*` is associative, so it can't be regrouped.
+        let codes = prove(
+            COMMUTATIVE,
+            &[],
+            "('x * 'y) * 'z = 'x * ('y * 'z)",
+            "| !ac | ('x * 'y) * 'z = 'x * ('y * 'z);",
+        );
+        assert_eq!(codes, ["step-macro-rearrangement"]);
+    }
+
+    #[test]
+    fn expanded_step_errors() {
+        // The failing step was generated by the macro, so its formula is given in the message.
+        let extra = r#"
+\Axiom plus_comm : sys {
+  name = "PC" tagline = {PC.}
+  flags = [commutative]
+  var a : Nat
+  var b : Nat
+  assertion = 'a + 'b = 'b + 'a;
+}
+
+\Axiom times_comm : sys {
+  name = "TC" tagline = {TC.}
+  flags = [commutative]
+  var a : Nat
+  var b : Nat
+  distinct = [ 'a, 'b ]
+  assertion = 'a * 'b = 'b * 'a;
+}
+"#;
+        let diagnostics = page_diagnostics(&goal(
+            extra,
+            &[],
+            "('x * 'y) + 'z = 'z + ('y * 'x)",
+            "| !comm | ('x * 'y) + 'z = 'z + ('y * 'x);",
+        ));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "step-not-distinct");
+        assert!(diagnostics[0]
+            .message
+            .contains("step 3 of these, which derives `(x ⋅ y) = (y ⋅ x)` from the axiom `TC`"));
+    }
+
+    const PREDICATES: &str = r#"
+\Symbol implies : sys {
+  name = "Implies" tagline = {Implies.}
+  type = Prop -> Prop -> Prop
+  read = Infix -> 10 right
+}
+
+\Symbol even : sys {
+  name = "Even" tagline = {Even.}
+  type = Nat -> Prop
+}
+
+\Symbol odd : sys {
+  name = "Odd" tagline = {Odd.}
+  type = Nat -> Prop
+}
+
+\Symbol between : sys {
+  name = "Between" tagline = {Between.}
+  type = Nat -> Nat -> Nat -> Prop
+}
+
+\Axiom mp : sys {
+  name = "MP" tagline = {MP.}
+  flags = [detachment]
+  var p : Prop
+  var q : Prop
+  premise = [ 'p; 'p -> 'q; ]
+  assertion = 'q;
+}
+
+\Axiom even_fun : sys {
+  name = "EF" tagline = {EF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  premise = [ 'a = 'b; ]
+  assertion = even('a) -> even('b);
+}
+
+\Axiom between_fun : sys {
+  name = "BF" tagline = {BF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  var e : Nat
+  var f : Nat
+  premise = [ 'a = 'b; 'c = 'd; 'e = 'f; ]
+  assertion = between('a, 'c, 'e) -> between('b, 'd, 'f);
+}
+"#;
+
+    fn rewrite(premise: &str, assertion: &str) -> Vec<&'static str> {
+        prove(
+            PREDICATES,
+            &[premise],
+            assertion,
+            &format!("| 1 | {};\n| !rw plus_zero | {};", premise, assertion),
+        )
+    }
+
+    #[test]
+    fn rewrite_relations() {
+        // Every instance is rewritten at once.
+        let codes = rewrite("('x + zero) + ('y + zero) = 'z", "'x + 'y = 'z");
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        // The step relates its sides by `<=`, which is joined to the `=` of the claim.
+        let codes = rewrite("'x + zero <= 'y + zero", "'x <= 'y");
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        // Nothing joins `=` and `<`.
+        let codes = rewrite("'x + zero < 'y", "'x < 'y");
+        assert_eq!(codes, ["step-macro-rewrite-not-transitive"]);
+    }
+
+    #[test]
+    fn rewrite_detached() {
+        let codes = rewrite("even('x + zero)", "even('x)");
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        let codes = rewrite("between('x + zero, 'y, 'z + zero)", "between('x, 'y, 'z)");
+        assert!(codes.is_empty(), "{:?}", codes);
+
+        // Nothing claims equal inputs to `odd` give implied formulas.
+        let codes = rewrite("odd('x + zero)", "odd('x)");
+        assert_eq!(codes, ["step-macro-rewrite-not-detachable"]);
+    }
+
+    #[test]
+    fn rewrite_errors() {
+        // The claim is only used from left to right.
+        let codes = rewrite("'x = 'y", "'x + zero = 'y");
+        assert_eq!(codes, ["step-macro-rewrite-no-instance"]);
+
+        let codes = prove(PREDICATES, &[], "'x = 'x", "| !rw plus_zero | 'x = 'x;");
+        assert_eq!(codes, ["step-macro-rewrite-no-step"]);
+    }
+
+    // A detachment claim about `->`, with the given premise.
+    fn detachment(premise: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+{}
+\Axiom claim : sys {{
+  name = "C" tagline = {{C.}}
+  flags = [detachment]
+  var p : Prop
+  var q : Prop
+  premise = [ {} ]
+  assertion = 'q;
+}}
+"#,
+            ORDER, PREDICATES, premise
+        ))
+    }
+
+    #[test]
+    fn detachment_claims() {
+        // `mp` already claims detachment of `->`.
+        assert_eq!(
+            detachment("'p; 'p -> 'q;"),
+            ["readable-duplicate-detachment"]
+        );
+
+        assert_eq!(detachment("'p -> 'q; 'p;"), ["flag-detachment-premise"]);
+        assert_eq!(detachment("'p;"), ["flag-detachment-premise"]);
+        assert_eq!(detachment("'p; 'q -> 'p;"), ["flag-detachment-arguments"]);
+    }
 }
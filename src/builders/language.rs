@@ -22,6 +22,9 @@ use std::path::Path;
 
 use pest::iterators::{Pair, Pairs};
 
+use crate::map_ident;
+use crate::FileLocation;
+
 use crate::document::language::{
     DefinitionBlock, Display, DisplayFormulaBlock, DisplayStyle, FormulaBlock, SymbolBlock,
     TypeBlock, TypeSignatureBlock, VariableBlock, VariableBlockRef,
@@ -29,16 +32,17 @@ use crate::document::language::{
 use crate::document::structure::{
     BlockLocation, DefinitionBlockRef, SymbolBlockRef, SystemBlockRef, TypeBlockRef,
 };
+use crate::document::text::{MathBlock, MathElement};
 
 use super::bibliography::BibliographyBuilderEntry;
 use super::errors::{
     DefinitionParsingError, FormulaParsingError, ParsingError, ParsingErrorContext,
-    ReadableParsingError, SymbolParsingError, TypeParsingError, TypeSignatureParsingError,
-    VariableParsingError,
+    ReadableParsingError, SymbolParsingError, TypeError, TypeParsingError,
+    TypeSignatureParsingError, VariableParsingError,
 };
 use super::index::{BuilderIndex, LocalBuilderIndex};
 use super::system::{DeductableBuilder, SystemBuilder};
-use super::text::{MathBuilder, ParagraphBuilder, TextBuilder};
+use super::text::{ParagraphBuilder, TextBuilder};
 use super::Rule;
 
 struct TypeBuilderEntries<'a> {
@@ -132,7 +136,10 @@ impl<'a> TypeBuilderEntries<'a> {
 
             1 => {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
-                    ParsingError::TypeError(type_ref, TypeParsingError::TaglineParsingError(e))
+                    ParsingError::TypeError(
+                        type_ref,
+                        TypeParsingError::TaglineParsingError(&self.taglines[0], e),
+                    )
                 });
 
                 if !success {
@@ -216,6 +223,7 @@ pub struct TypeBuilder<'a> {
     id: String,
     system_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     system_ref: OnceCell<&'a SystemBuilder<'a>>,
     entries: TypeBuilderEntries<'a>,
@@ -226,7 +234,9 @@ impl<'a> TypeBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::type_block);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
         let entries = TypeBuilderEntries::from_pest(path, inner);
 
@@ -234,6 +244,7 @@ impl<'a> TypeBuilder<'a> {
             id,
             system_id,
             location,
+            file_location,
 
             system_ref: OnceCell::new(),
             entries,
@@ -293,6 +304,10 @@ impl<'a> TypeBuilder<'a> {
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> std::fmt::Debug for TypeBuilder<'a> {
@@ -367,6 +382,10 @@ impl<'a> TypeSignatureBuilderGround<'a> {
 
         TypeSignatureBlock::Ground(type_ref)
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 impl<'a> PartialEq for TypeSignatureBuilderGround<'a> {
@@ -390,6 +409,11 @@ impl<'a> Hash for TypeSignatureBuilderGround<'a> {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TypeSignatureBuilder<'a> {
     Ground(TypeSignatureBuilderGround<'a>),
+
+    // A type parameter, written `'A`. Symbols and definitions can be used at any type for it, but
+    // within an axiom or theorem it's a single unknown type.
+    Variable(String),
+
     Compound(Box<TypeSignatureBuilder<'a>>, Box<TypeSignatureBuilder<'a>>),
 }
 
@@ -401,6 +425,11 @@ impl<'a> TypeSignatureBuilder<'a> {
                 Rule::ident => {
                     TypeSignatureBuilder::Ground(TypeSignatureBuilderGround::from_pest(pair))
                 }
+                Rule::var => {
+                    let id = pair.into_inner().next().unwrap().as_str().to_owned();
+
+                    TypeSignatureBuilder::Variable(id)
+                }
                 Rule::type_signature => TypeSignatureBuilder::from_pest(pair),
 
                 _ => unreachable!(),
@@ -438,6 +467,7 @@ impl<'a> TypeSignatureBuilder<'a> {
             Self::Ground(ground) => {
                 ground.verify_structure(parent_system, max_serial, index, errors, generate_error)
             }
+            Self::Variable(_) => true,
 
             Self::Compound(input, output) => {
                 let input_success = input.verify_structure(
@@ -463,6 +493,7 @@ impl<'a> TypeSignatureBuilder<'a> {
     fn finish<'b>(&self) -> TypeSignatureBlock<'b> {
         match self {
             Self::Ground(ground) => ground.finish(),
+            Self::Variable(id) => TypeSignatureBlock::Variable(id.clone()),
 
             Self::Compound(input, output) => {
                 TypeSignatureBlock::Compound(Box::new(input.finish()), Box::new(output.finish()))
@@ -476,12 +507,123 @@ impl<'a> TypeSignatureBuilder<'a> {
 
     fn applied(&'a self) -> &TypeSignatureBuilder {
         match self {
-            Self::Ground(_) => panic!("Tried to apply an input to a ground type"),
+            Self::Ground(_) | Self::Variable(_) => {
+                panic!("Tried to apply an input to a ground type")
+            }
             Self::Compound(_, right) => right,
         }
     }
+
+    // Matches a signature which may mention type variables against the type of something it's
+    // used as, recording which type each variable stands for.
+    fn instantiate(
+        &self,
+        found: &TypeSignatureBuilder<'a>,
+        types: &mut HashMap<String, TypeSignatureBuilder<'a>>,
+    ) -> bool {
+        match (self, found) {
+            (Self::Variable(id), _) => match types.entry(id.clone()) {
+                Entry::Occupied(expected) => expected.get() == found,
+
+                Entry::Vacant(slot) => {
+                    slot.insert(found.clone());
+                    true
+                }
+            },
+
+            (Self::Compound(input, output), Self::Compound(found_input, found_output)) => {
+                input.instantiate(found_input, types) && output.instantiate(found_output, types)
+            }
+
+            _ => self == found,
+        }
+    }
+
+    // Whether something of type `found` can be used where something of this type is expected.
+    pub fn accepts(&self, found: &TypeSignatureBuilder<'a>) -> bool {
+        self.instantiate(found, &mut HashMap::new())
+    }
+
+    fn substitute(&self, types: &HashMap<String, TypeSignatureBuilder<'a>>) -> Self {
+        match self {
+            Self::Variable(id) => match types.get(id) {
+                Some(ty) => ty.clone(),
+                None => self.clone(),
+            },
+            Self::Ground(_) => self.clone(),
+
+            Self::Compound(input, output) => Self::Compound(
+                Box::new(input.substitute(types)),
+                Box::new(output.substitute(types)),
+            ),
+        }
+    }
+
+    // The type of the result of applying something of this type to inputs of the given types.
+    // Otherwise the position of the first input which doesn't fit, along with the type it should
+    // have had, unless there was no room left for it.
+    fn apply_inputs<'b, I>(&self, inputs: I) -> Result<Self, (usize, Option<Self>)>
+    where
+        I: IntoIterator<Item = &'b TypeSignatureBuilder<'a>>,
+        'a: 'b,
+    {
+        let mut types = HashMap::new();
+        let mut curr = self;
+
+        for (i, input) in inputs.into_iter().enumerate() {
+            match curr {
+                Self::Compound(expected, output) => {
+                    if !expected.instantiate(input, &mut types) {
+                        return Err((i, Some(expected.substitute(&types))));
+                    }
+
+                    curr = output;
+                }
+
+                _ => return Err((i, None)),
+            }
+        }
+
+        Ok(curr.substitute(&types))
+    }
+
+    // Applies something of this type to the given formulas, which have already been built.
+    pub fn apply_formulas<I>(&self, inputs: I) -> Result<Self, FormulaParsingError<'a>>
+    where
+        I: IntoIterator<Item = &'a FormulaBuilder<'a>>,
+        I::IntoIter: Clone,
+    {
+        let inputs = inputs.into_iter();
+
+        match self.apply_inputs(inputs.clone().map(FormulaBuilder::type_signature)) {
+            Ok(type_signature) => Ok(type_signature),
+
+            Err((i, Some(expected))) => {
+                let at = inputs.clone().nth(i).unwrap();
+                let found = at.type_signature().clone();
+
+                Err(FormulaParsingError::TypeMismatch(TypeError::new(
+                    expected, found, at,
+                )))
+            }
+            Err((_, None)) => Err(FormulaParsingError::ApplicationTooManyInputs),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        match self {
+            Self::Ground(ground) => ground.id().to_owned(),
+            Self::Variable(id) => format!("'{}", id),
+
+            Self::Compound(input, output) => match input.as_ref() {
+                Self::Compound(_, _) => format!("({}) -> {}", input.render(), output.render()),
+                _ => format!("{} -> {}", input.render(), output.render()),
+            },
+        }
+    }
 }
 
+#[derive(Clone)]
 struct TypeSignatureBuilderInputs<'a> {
     curr: &'a TypeSignatureBuilder<'a>,
 }
@@ -491,7 +633,7 @@ impl<'a> Iterator for TypeSignatureBuilderInputs<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.curr {
-            TypeSignatureBuilder::Ground(_) => None,
+            TypeSignatureBuilder::Ground(_) | TypeSignatureBuilder::Variable(_) => None,
             TypeSignatureBuilder::Compound(input, output) => {
                 self.curr = output;
 
@@ -501,86 +643,103 @@ impl<'a> Iterator for TypeSignatureBuilderInputs<'a> {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-enum ReadStyle {
-    Prefix,
-    Infix,
-}
-
-impl ReadStyle {
-    fn from_pest(pair: Pair<Rule>) -> ReadStyle {
-        match pair.as_rule() {
-            Rule::style_prefix => Self::Prefix,
-            Rule::style_infix => Self::Infix,
-
-            _ => unreachable!(),
-        }
-    }
-
-    fn to_display(&self) -> DisplayStyle {
-        match self {
-            ReadStyle::Prefix => DisplayStyle::Prefix,
-            ReadStyle::Infix => DisplayStyle::Infix,
-        }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-enum ReadOperator {
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ReadOperator {
     Negation,
     Implies,
     Equivalent,
     And,
     Or,
 
+    LessThan,
+    Equal,
+    GreaterThan,
+    Twiddle,
+
     Asterisk,
     Slash,
     Plus,
     Minus,
+
+    Custom(String),
 }
 
 impl ReadOperator {
     fn from_pest(pair: Pair<Rule>) -> ReadOperator {
         assert_eq!(pair.as_rule(), Rule::read_operator);
 
-        // TODO: operator_lt, operator_eq, and operator_gt
-        match pair.into_inner().next().unwrap().as_rule() {
-            Rule::operator_negation => Self::Negation,
-            Rule::operator_implies => Self::Implies,
-            Rule::operator_equiv => Self::Equivalent,
-            Rule::operator_and => Self::And,
-            Rule::operator_or => Self::Or,
+        Self::new(pair.as_str())
+    }
 
-            Rule::operator_asterisk => Self::Asterisk,
-            Rule::operator_slash => Self::Slash,
-            Rule::operator_plus => Self::Plus,
-            Rule::operator_minus => Self::Minus,
+    fn new(operator: &str) -> ReadOperator {
+        match operator {
+            "!" => Self::Negation,
+            "->" => Self::Implies,
+            "<->" => Self::Equivalent,
+            "&" => Self::And,
+            "|" => Self::Or,
 
-            _ => unreachable!(),
+            "<" => Self::LessThan,
+            "=" => Self::Equal,
+            ">" => Self::GreaterThan,
+            "~" => Self::Twiddle,
+
+            "*" => Self::Asterisk,
+            "/" => Self::Slash,
+            "+" => Self::Plus,
+            "-" => Self::Minus,
+
+            operator => Self::Custom(operator.to_owned()),
         }
     }
 
-    fn precedence(self) -> usize {
-        match self {
-            Self::Negation => todo!(),
+    // The fixity of an operator which is read without declaring one. Negation sits between the
+    // connectives and the relations, so `!a = b & c` reads as `(!(a = b)) & c`.
+    fn default_fixity(&self) -> Option<ReadFixity> {
+        let (precedence, associativity) = match self {
+            Self::Equivalent => (0, Associativity::Right),
+            Self::Implies => (1, Associativity::Right),
+            Self::Or => (2, Associativity::Right),
+            Self::And => (3, Associativity::Right),
 
-            Self::Equivalent => 0,
-            Self::Implies => 1,
-            Self::And => 2,
-            Self::Or => 3,
+            Self::Negation => (4, Associativity::Right),
 
-            Self::Asterisk | Self::Slash => 4,
-            Self::Plus | Self::Minus => 5,
-        }
+            Self::LessThan | Self::Equal | Self::GreaterThan | Self::Twiddle => {
+                (5, Associativity::NonAssociative)
+            }
+
+            Self::Plus | Self::Minus => (6, Associativity::Left),
+            Self::Asterisk | Self::Slash => (7, Associativity::Left),
+
+            Self::Custom(_) => return None,
+        };
+
+        Some(ReadFixity {
+            precedence,
+            associativity,
+        })
     }
 
-    fn is_left_associative(self) -> bool {
+    // The operator as it's written in formulas.
+    fn as_str(&self) -> &str {
         match self {
-            Self::Negation => todo!(),
-
-            Self::Asterisk | Self::Slash | Self::Plus | Self::Minus => true,
+            Self::Negation => "!",
+            Self::Implies => "->",
+            Self::Equivalent => "<->",
+            Self::And => "&",
+            Self::Or => "|",
+
+            Self::LessThan => "<",
+            Self::Equal => "=",
+            Self::GreaterThan => ">",
+            Self::Twiddle => "~",
+
+            Self::Asterisk => "*",
+            Self::Slash => "/",
+            Self::Plus => "+",
+            Self::Minus => "-",
 
-            Self::Equivalent | Self::Implies | Self::And | Self::Or => false,
+            Self::Custom(operator) => operator,
         }
     }
 
@@ -592,18 +751,117 @@ impl ReadOperator {
             Self::And => "\u{2227}",
             Self::Or => "\u{2228}",
 
+            Self::LessThan => "<",
+            Self::Equal => "=",
+            Self::GreaterThan => ">",
+            Self::Twiddle => "~",
+
             Self::Asterisk => "\u{22C5}",
             Self::Slash => "/",
             Self::Plus => "+",
             Self::Minus => "-",
+
+            Self::Custom(operator) => operator,
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssociative,
+}
+
+impl Associativity {
+    fn from_pest(pair: Pair<Rule>) -> Associativity {
+        match pair.as_rule() {
+            Rule::associativity_left => Self::Left,
+            Rule::associativity_right => Self::Right,
+            Rule::associativity_none => Self::NonAssociative,
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+// Operators with a higher precedence bind more tightly.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReadFixity {
+    precedence: usize,
+    associativity: Associativity,
+}
+
+impl ReadFixity {
+    fn from_pest(pair: Pair<Rule>) -> ReadFixity {
+        assert_eq!(pair.as_rule(), Rule::read_fixity);
+
+        let mut inner = pair.into_inner();
+        let precedence = inner.next().unwrap().as_str().parse().unwrap();
+        let associativity = inner
+            .next()
+            .map(Associativity::from_pest)
+            .unwrap_or(Associativity::NonAssociative);
+
+        ReadFixity {
+            precedence,
+            associativity,
+        }
+    }
+}
+
+// The part of a read which operators are looked up by.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ReadNotation {
+    Prefix(ReadOperator),
+    Infix(ReadOperator),
+
+    // The delimiters around and between the inputs, like `⟨`, `,` and `⟩` for a pair.
+    Mixfix(Vec<String>),
+}
+
+impl ReadNotation {
+    fn from_pest(pair: Pair<Rule>) -> ReadNotation {
+        assert_eq!(pair.as_rule(), Rule::read_mixfix);
+
+        let delimiters = pair
+            .into_inner()
+            .map(|string| string.into_inner().next().unwrap().as_str().to_owned())
+            .collect();
+
+        ReadNotation::Mixfix(delimiters)
+    }
+
+    // Names the notation as it's written in formulas, for diagnostics.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Prefix(operator) => format!("prefix operator `{}`", operator.as_str()),
+            Self::Infix(operator) => format!("infix operator `{}`", operator.as_str()),
+            Self::Mixfix(delimiters) => format!("notation `{}`", delimiters.join(" ")),
+        }
+    }
+
+    fn to_display(&self) -> Display {
+        match self {
+            Self::Prefix(operator) => {
+                Display::new(DisplayStyle::Prefix, operator.to_display().to_owned())
+            }
+            Self::Infix(operator) => {
+                Display::new(DisplayStyle::Infix, operator.to_display().to_owned())
+            }
+
+            Self::Mixfix(delimiters) => Display::new(
+                DisplayStyle::Mixfix(delimiters.clone()),
+                delimiters.concat(),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 struct ReadBuilder {
-    style: ReadStyle,
-    operator: ReadOperator,
+    notation: ReadNotation,
+    fixity: Option<ReadFixity>,
 }
 
 impl ReadBuilder {
@@ -611,31 +869,132 @@ impl ReadBuilder {
         assert_eq!(pair.as_rule(), Rule::read);
 
         let mut inner = pair.into_inner();
-        let style = ReadStyle::from_pest(inner.next().unwrap());
-        let operator = ReadOperator::from_pest(inner.next().unwrap());
+        let style = inner.next().unwrap();
+        let notation = match style.as_rule() {
+            Rule::read_mixfix => ReadNotation::from_pest(style),
+
+            Rule::style_prefix => {
+                ReadNotation::Prefix(ReadOperator::from_pest(inner.next().unwrap()))
+            }
+            Rule::style_infix => {
+                ReadNotation::Infix(ReadOperator::from_pest(inner.next().unwrap()))
+            }
+
+            _ => unreachable!(),
+        };
+        let fixity = inner.next().map(ReadFixity::from_pest);
 
-        ReadBuilder { style, operator }
+        ReadBuilder { notation, fixity }
     }
 
-    fn to_display(&self) -> Display {
-        let style = self.style.to_display();
-        let operator = self.operator.to_display().to_owned();
+    // Notations are closed on both sides, so they don't need a fixity.
+    fn fixity(&self) -> Option<ReadFixity> {
+        match &self.notation {
+            ReadNotation::Prefix(operator) | ReadNotation::Infix(operator) => {
+                self.fixity.or_else(|| operator.default_fixity())
+            }
+
+            ReadNotation::Mixfix(_) => None,
+        }
+    }
 
-        Display::new(style, operator)
+    fn to_display(&self) -> Display {
+        self.notation.to_display()
     }
 }
 
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct ReadSignature<'a> {
-    read: ReadBuilder,
+    notation: ReadNotation,
     inputs: Vec<&'a TypeSignatureBuilder<'a>>,
 }
 
+impl<'a> ReadSignature<'a> {
+    pub fn notation(&self) -> &ReadNotation {
+        &self.notation
+    }
+
+    // Whether an operator read with this signature can be used on inputs of the types in `found`.
+    pub fn matches(&self, found: &ReadSignature<'a>) -> bool {
+        let mut types = HashMap::new();
+
+        self.notation == found.notation
+            && self.inputs.len() == found.inputs.len()
+            && self
+                .inputs
+                .iter()
+                .zip(&found.inputs)
+                .all(|(expected, found)| expected.instantiate(found, &mut types))
+    }
+}
+
 impl Display {
     fn from_pest(pair: Pair<Rule>) -> Display {
         assert_eq!(pair.as_rule(), Rule::display);
 
-        todo!()
+        let mut inner = pair.into_inner();
+        let style = match inner.next().unwrap().as_rule() {
+            Rule::style_prefix => DisplayStyle::Prefix,
+            Rule::style_infix => DisplayStyle::Infix,
+            Rule::style_suffix => DisplayStyle::Suffix,
+            Rule::style_standard => DisplayStyle::Standard,
+
+            _ => unreachable!(),
+        };
+
+        let symbol = inner.next().unwrap();
+        let id = match symbol.as_rule() {
+            Rule::read_operator => ReadOperator::from_pest(symbol).to_display().to_owned(),
+            Rule::operator_bang => "!".to_owned(),
+            Rule::ident => symbol.as_str().to_owned(),
+
+            _ => unreachable!(),
+        };
+
+        Display::new(style, id)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FunctionInputBuilder<'a> {
+    relation: ReadableBuilder<'a>,
+
+    // Whether the hypothesis for this input relates the right side of the assertion to the left.
+    reversed: bool,
+}
+
+impl<'a> FunctionInputBuilder<'a> {
+    pub fn new(relation: ReadableBuilder<'a>, reversed: bool) -> Self {
+        FunctionInputBuilder { relation, reversed }
+    }
+
+    pub fn relation(&self) -> ReadableBuilder<'a> {
+        self.relation
+    }
+
+    pub fn reversed(&self) -> bool {
+        self.reversed
+    }
+}
+
+// A claim that a function respects a relation, given that its inputs are related as described.
+#[derive(Clone, Debug)]
+pub struct FunctionBuilder<'a> {
+    deductable: DeductableBuilder<'a>,
+    inputs: Vec<FunctionInputBuilder<'a>>,
+}
+
+impl<'a> FunctionBuilder<'a> {
+    pub fn new(deductable: DeductableBuilder<'a>, inputs: Vec<FunctionInputBuilder<'a>>) -> Self {
+        FunctionBuilder { deductable, inputs }
+    }
+
+    pub fn deductable(&self) -> DeductableBuilder<'a> {
+        self.deductable
+    }
+
+    pub fn inputs(&self) -> &[FunctionInputBuilder<'a>] {
+        &self.inputs
     }
 }
 
@@ -645,7 +1004,17 @@ struct PropertyList<'a> {
     symmetric: OnceCell<DeductableBuilder<'a>>,
     transitive: OnceCell<DeductableBuilder<'a>>,
 
-    function: RefCell<HashMap<ReadableBuilder<'a>, DeductableBuilder<'a>>>,
+    // Claims that this relation followed by another gives a third, keyed by the other relation.
+    transitive_with:
+        RefCell<HashMap<ReadableBuilder<'a>, (ReadableBuilder<'a>, DeductableBuilder<'a>)>>,
+
+    function: RefCell<HashMap<ReadableBuilder<'a>, FunctionBuilder<'a>>>,
+    commutative: RefCell<HashMap<ReadableBuilder<'a>, DeductableBuilder<'a>>>,
+    associative: RefCell<HashMap<ReadableBuilder<'a>, DeductableBuilder<'a>>>,
+
+    // A claim that something proved, and related by this relation to something else, proves that
+    // too.
+    detachment: OnceCell<DeductableBuilder<'a>>,
 }
 
 impl<'a> PropertyList<'a> {
@@ -707,11 +1076,40 @@ impl<'a> PropertyList<'a> {
         self.transitive.get().copied()
     }
 
-    // TODO: Allow inputs to use different preorders.
-    fn set_function(
+    fn set_transitive_with(
         &self,
         readable_ref: ReadableBuilder<'a>,
         deductable_ref: DeductableBuilder<'a>,
+        other: ReadableBuilder<'a>,
+        result: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        let mut transitive_with = self.transitive_with.borrow_mut();
+        match transitive_with.entry(other) {
+            Entry::Occupied(_) => {
+                errors.err(ParsingError::ReadableError(
+                    readable_ref,
+                    ReadableParsingError::DuplicateTransitiveWith(other, deductable_ref),
+                ));
+            }
+
+            Entry::Vacant(slot) => {
+                slot.insert((result, deductable_ref));
+            }
+        }
+    }
+
+    fn get_transitive_with(
+        &self,
+        other: ReadableBuilder<'a>,
+    ) -> Option<(ReadableBuilder<'a>, DeductableBuilder<'a>)> {
+        self.transitive_with.borrow().get(&other).copied()
+    }
+
+    fn set_function(
+        &self,
+        readable_ref: ReadableBuilder<'a>,
+        function_ref: FunctionBuilder<'a>,
         relation: ReadableBuilder<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
@@ -720,47 +1118,129 @@ impl<'a> PropertyList<'a> {
             Entry::Occupied(_) => {
                 errors.err(ParsingError::ReadableError(
                     readable_ref,
-                    ReadableParsingError::DuplicateFunction(relation, deductable_ref),
+                    ReadableParsingError::DuplicateFunction(relation, function_ref.deductable()),
                 ));
             }
 
             Entry::Vacant(slot) => {
-                slot.insert(deductable_ref);
+                slot.insert(function_ref);
             }
         }
     }
 
-    fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
-        self.function.borrow().get(&relation).copied()
+    fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<FunctionBuilder<'a>> {
+        self.function.borrow().get(&relation).cloned()
     }
 
-    fn is_reflexive(&self) -> bool {
-        self.reflexive.get().is_some()
+    // A function claim for a relation which can be detached, so that an application of the
+    // function is proved from one with related inputs. The first relation by id is chosen if
+    // there are several.
+    fn get_detachable_function(&self) -> Option<(ReadableBuilder<'a>, FunctionBuilder<'a>)> {
+        self.function
+            .borrow()
+            .iter()
+            .filter(|(relation, _)| relation.get_detachment().is_some())
+            .min_by_key(|(relation, _)| relation.id().to_owned())
+            .map(|(relation, function)| (*relation, function.clone()))
     }
 
-    fn is_symmetric(&self) -> bool {
-        self.symmetric.get().is_some()
-    }
+    fn set_commutative(
+        &self,
+        readable_ref: ReadableBuilder<'a>,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        let mut commutative = self.commutative.borrow_mut();
+        match commutative.entry(relation) {
+            Entry::Occupied(_) => {
+                errors.err(ParsingError::ReadableError(
+                    readable_ref,
+                    ReadableParsingError::DuplicateCommutative(relation, deductable_ref),
+                ));
+            }
 
-    fn is_transitive(&self) -> bool {
-        self.transitive.get().is_some()
+            Entry::Vacant(slot) => {
+                slot.insert(deductable_ref);
+            }
+        }
     }
 
-    fn is_preorder(&self) -> bool {
-        self.is_reflexive() && self.is_transitive()
+    fn get_commutative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.commutative.borrow().get(&relation).copied()
     }
-}
 
-struct SymbolBuilderEntries<'a> {
-    names: Vec<String>,
-    taglines: Vec<ParagraphBuilder<'a>>,
-    descriptions: Vec<Vec<TextBuilder<'a>>>,
-    type_signatures: Vec<TypeSignatureBuilder<'a>>,
-    reads: Vec<ReadBuilder>,
-    displays: Vec<Display>,
+    fn set_associative(
+        &self,
+        readable_ref: ReadableBuilder<'a>,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        let mut associative = self.associative.borrow_mut();
+        match associative.entry(relation) {
+            Entry::Occupied(_) => {
+                errors.err(ParsingError::ReadableError(
+                    readable_ref,
+                    ReadableParsingError::DuplicateAssociative(relation, deductable_ref),
+                ));
+            }
 
-    verified: Cell<bool>,
-}
+            Entry::Vacant(slot) => {
+                slot.insert(deductable_ref);
+            }
+        }
+    }
+
+    fn get_associative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.associative.borrow().get(&relation).copied()
+    }
+
+    fn set_detachment(
+        &self,
+        readable_ref: ReadableBuilder<'a>,
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        if let Err(deductable_ref) = self.detachment.set(deductable_ref) {
+            errors.err(ParsingError::ReadableError(
+                readable_ref,
+                ReadableParsingError::DuplicateDetachment(deductable_ref),
+            ));
+        }
+    }
+
+    fn get_detachment(&self) -> Option<DeductableBuilder<'a>> {
+        self.detachment.get().copied()
+    }
+
+    fn is_reflexive(&self) -> bool {
+        self.reflexive.get().is_some()
+    }
+
+    fn is_symmetric(&self) -> bool {
+        self.symmetric.get().is_some()
+    }
+
+    fn is_transitive(&self) -> bool {
+        self.transitive.get().is_some()
+    }
+
+    fn is_preorder(&self) -> bool {
+        self.is_reflexive() && self.is_transitive()
+    }
+}
+
+struct SymbolBuilderEntries<'a> {
+    names: Vec<String>,
+    taglines: Vec<ParagraphBuilder<'a>>,
+    descriptions: Vec<Vec<TextBuilder<'a>>>,
+    type_signatures: Vec<TypeSignatureBuilder<'a>>,
+    reads: Vec<ReadBuilder>,
+    displays: Vec<Display>,
+
+    verified: Cell<bool>,
+}
 
 impl<'a> SymbolBuilderEntries<'a> {
     fn from_pest(path: &Path, pairs: Pairs<Rule>) -> Self {
@@ -874,7 +1354,7 @@ impl<'a> SymbolBuilderEntries<'a> {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
                     ParsingError::SymbolError(
                         symbol_ref,
-                        SymbolParsingError::TaglineParsingError(e),
+                        SymbolParsingError::TaglineParsingError(&self.taglines[0], e),
                     )
                 });
 
@@ -971,6 +1451,23 @@ impl<'a> SymbolBuilderEntries<'a> {
             ));
         }
 
+        // Operators and notations lay out a fixed number of inputs, which the type has to take.
+        if !found_error {
+            let display = self.displays.first().cloned();
+            let display = display.or_else(|| self.reads.first().map(ReadBuilder::to_display));
+            let arity = self.type_signatures[0].inputs().count();
+
+            if let Some(display) =
+                display.filter(|display| display.arity().is_some_and(|n| n != arity))
+            {
+                found_error = true;
+                errors.err(ParsingError::SymbolError(
+                    symbol_ref,
+                    SymbolParsingError::DisplayArityMismatch(display, arity),
+                ));
+            }
+        }
+
         self.verified.set(!found_error);
     }
 
@@ -1012,22 +1509,18 @@ impl<'a> SymbolBuilderEntries<'a> {
         &self.type_signatures[0]
     }
 
-    fn read(&self) -> Option<ReadBuilder> {
+    fn read(&self) -> Option<&ReadBuilder> {
         assert!(self.verified.get());
-        self.reads.get(0).copied()
+        self.reads.first()
     }
 
-    fn display(&self) -> Display {
+    fn display(&self) -> Option<Display> {
         assert!(self.verified.get());
 
         if let Some(display) = self.displays.get(0) {
-            display.clone()
+            Some(display.clone())
         } else {
-            if let Some(read) = self.reads.get(0) {
-                read.to_display()
-            } else {
-                todo!()
-            }
+            self.reads.first().map(ReadBuilder::to_display)
         }
     }
 }
@@ -1036,6 +1529,7 @@ pub struct SymbolBuilder<'a> {
     id: String,
     system_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     system_ref: OnceCell<&'a SystemBuilder<'a>>,
     entries: SymbolBuilderEntries<'a>,
@@ -1051,7 +1545,9 @@ impl<'a> SymbolBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::symbol_block);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
 
         let entries = SymbolBuilderEntries::from_pest(path, inner);
@@ -1060,6 +1556,7 @@ impl<'a> SymbolBuilder<'a> {
             id,
             system_id,
             location,
+            file_location,
 
             system_ref: OnceCell::new(),
             entries,
@@ -1129,24 +1626,100 @@ impl<'a> SymbolBuilder<'a> {
         self.properties.get_transitive()
     }
 
-    pub fn set_function(
+    pub fn set_transitive_with(
         &'a self,
         deductable_ref: DeductableBuilder<'a>,
+        other: ReadableBuilder<'a>,
+        result: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_transitive_with(
+            ReadableBuilder::Symbol(self),
+            deductable_ref,
+            other,
+            result,
+            errors,
+        );
+    }
+
+    pub fn get_transitive_with(
+        &self,
+        other: ReadableBuilder<'a>,
+    ) -> Option<(ReadableBuilder<'a>, DeductableBuilder<'a>)> {
+        self.properties.get_transitive_with(other)
+    }
+
+    pub fn set_function(
+        &'a self,
+        function_ref: FunctionBuilder<'a>,
         relation: ReadableBuilder<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
         self.properties.set_function(
             ReadableBuilder::Symbol(self),
-            deductable_ref,
+            function_ref,
             relation,
             errors,
         );
     }
 
-    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<FunctionBuilder<'a>> {
         self.properties.get_function(relation)
     }
 
+    pub fn set_commutative(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_commutative(
+            ReadableBuilder::Symbol(self),
+            deductable_ref,
+            relation,
+            errors,
+        );
+    }
+
+    pub fn get_commutative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_commutative(relation)
+    }
+
+    pub fn set_associative(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_associative(
+            ReadableBuilder::Symbol(self),
+            deductable_ref,
+            relation,
+            errors,
+        );
+    }
+
+    pub fn get_associative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_associative(relation)
+    }
+
+    pub fn get_detachable_function(&self) -> Option<(ReadableBuilder<'a>, FunctionBuilder<'a>)> {
+        self.properties.get_detachable_function()
+    }
+
+    pub fn set_detachment(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties
+            .set_detachment(ReadableBuilder::Symbol(self), deductable_ref, errors);
+    }
+
+    pub fn get_detachment(&self) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_detachment()
+    }
+
     pub fn is_reflexive(&self) -> bool {
         self.properties.is_reflexive()
     }
@@ -1188,7 +1761,7 @@ impl<'a> SymbolBuilder<'a> {
             .collect();
 
         let type_signature = self.entries.type_signature().finish();
-        let display = self.entries.display();
+        let display = self.display();
 
         let href = self.href.get().unwrap().clone();
 
@@ -1218,11 +1791,22 @@ impl<'a> SymbolBuilder<'a> {
 
     pub fn read_signature(&'a self) -> Option<ReadSignature> {
         self.entries.read().map(|read| ReadSignature {
-            read,
+            notation: read.notation.clone(),
             inputs: self.entries.type_signature().inputs().collect(),
         })
     }
 
+    pub fn read_fixity(&self) -> Option<ReadFixity> {
+        self.entries.read().and_then(ReadBuilder::fixity)
+    }
+
+    // Symbols which are neither read nor displayed are written out by name.
+    fn display(&self) -> Display {
+        self.entries
+            .display()
+            .unwrap_or_else(|| Display::new(DisplayStyle::Standard, self.id.clone()))
+    }
+
     pub fn serial(&self) -> usize {
         self.location.serial()
     }
@@ -1230,6 +1814,10 @@ impl<'a> SymbolBuilder<'a> {
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> PartialEq for SymbolBuilder<'a> {
@@ -1304,7 +1892,7 @@ impl<'a> DefinitionBuilderEntries<'a> {
                     let input = pair
                         .into_inner()
                         .enumerate()
-                        .map(|(index, pair)| VariableBuilder::from_pest(pair, index))
+                        .map(|(index, pair)| VariableBuilder::from_pest(path, pair, index))
                         .collect();
 
                     inputs.push(input);
@@ -1324,7 +1912,7 @@ impl<'a> DefinitionBuilderEntries<'a> {
 
                 Rule::expanded => {
                     let expanded =
-                        DisplayFormulaBuilder::from_pest(pair.into_inner().next().unwrap());
+                        DisplayFormulaBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     expansions.push(expanded);
                 }
@@ -1388,7 +1976,7 @@ impl<'a> DefinitionBuilderEntries<'a> {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
                     ParsingError::DefinitionError(
                         definition_ref,
-                        DefinitionParsingError::TaglineParsingError(e),
+                        DefinitionParsingError::TaglineParsingError(&self.taglines[0], e),
                     )
                 });
 
@@ -1485,9 +2073,7 @@ impl<'a> DefinitionBuilderEntries<'a> {
                 ));
             }
 
-            1 => {
-                self.expansions[0].verify_structure(errors);
-            }
+            1 => {}
 
             _ => {
                 found_error = true;
@@ -1498,6 +2084,24 @@ impl<'a> DefinitionBuilderEntries<'a> {
             }
         }
 
+        // Operators and notations lay out a fixed number of inputs, which have to be the inputs
+        // of the definition.
+        if !found_error {
+            let display = self.displays.first().cloned();
+            let display = display.or_else(|| self.reads.first().map(ReadBuilder::to_display));
+            let arity = self.inputs.first().map_or(0, Vec::len);
+
+            if let Some(display) =
+                display.filter(|display| display.arity().is_some_and(|n| n != arity))
+            {
+                found_error = true;
+                errors.err(ParsingError::DefinitionError(
+                    definition_ref,
+                    DefinitionParsingError::DisplayArityMismatch(display, arity),
+                ));
+            }
+        }
+
         self.verified.set(!found_error);
     }
 
@@ -1524,7 +2128,11 @@ impl<'a> DefinitionBuilderEntries<'a> {
     ) {
         assert!(self.verified.get());
 
-        let local_index = index.get_local(definition_ref.system_id(), self.inputs());
+        let local_index = index.get_local(
+            definition_ref.system_id(),
+            definition_ref.serial(),
+            self.inputs(),
+        );
         let success = self.expanded().build(&local_index, errors, |formula, e| {
             ParsingError::DefinitionError(
                 definition_ref,
@@ -1572,22 +2180,18 @@ impl<'a> DefinitionBuilderEntries<'a> {
         &self.inputs[0]
     }
 
-    fn read(&self) -> Option<ReadBuilder> {
+    fn read(&self) -> Option<&ReadBuilder> {
         assert!(self.verified.get());
-        self.reads.get(0).copied()
+        self.reads.first()
     }
 
-    fn display(&self) -> Display {
+    fn display(&self) -> Option<Display> {
         assert!(self.verified.get());
 
         if let Some(display) = self.displays.get(0) {
-            display.clone()
+            Some(display.clone())
         } else {
-            if let Some(read) = self.reads.get(0) {
-                read.to_display()
-            } else {
-                todo!()
-            }
+            self.reads.first().map(ReadBuilder::to_display)
         }
     }
 
@@ -1601,6 +2205,7 @@ pub struct DefinitionBuilder<'a> {
     id: String,
     system_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     system_ref: OnceCell<&'a SystemBuilder<'a>>,
     type_signature: OnceCell<TypeSignatureBuilder<'a>>,
@@ -1618,7 +2223,9 @@ impl<'a> DefinitionBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::definition_block);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
 
         let entries = DefinitionBuilderEntries::from_pest(path, inner);
@@ -1627,6 +2234,7 @@ impl<'a> DefinitionBuilder<'a> {
             id,
             system_id,
             location,
+            file_location,
 
             system_ref: OnceCell::new(),
             type_signature: OnceCell::new(),
@@ -1706,24 +2314,100 @@ impl<'a> DefinitionBuilder<'a> {
         self.properties.get_transitive()
     }
 
-    pub fn set_function(
+    pub fn set_transitive_with(
         &'a self,
         deductable_ref: DeductableBuilder<'a>,
+        other: ReadableBuilder<'a>,
+        result: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_transitive_with(
+            ReadableBuilder::Definition(self),
+            deductable_ref,
+            other,
+            result,
+            errors,
+        );
+    }
+
+    pub fn get_transitive_with(
+        &self,
+        other: ReadableBuilder<'a>,
+    ) -> Option<(ReadableBuilder<'a>, DeductableBuilder<'a>)> {
+        self.properties.get_transitive_with(other)
+    }
+
+    pub fn set_function(
+        &'a self,
+        function_ref: FunctionBuilder<'a>,
         relation: ReadableBuilder<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
         self.properties.set_function(
             ReadableBuilder::Definition(self),
-            deductable_ref,
+            function_ref,
             relation,
             errors,
         );
     }
 
-    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<FunctionBuilder<'a>> {
         self.properties.get_function(relation)
     }
 
+    pub fn set_commutative(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_commutative(
+            ReadableBuilder::Definition(self),
+            deductable_ref,
+            relation,
+            errors,
+        );
+    }
+
+    pub fn get_commutative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_commutative(relation)
+    }
+
+    pub fn set_associative(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties.set_associative(
+            ReadableBuilder::Definition(self),
+            deductable_ref,
+            relation,
+            errors,
+        );
+    }
+
+    pub fn get_associative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_associative(relation)
+    }
+
+    pub fn get_detachable_function(&self) -> Option<(ReadableBuilder<'a>, FunctionBuilder<'a>)> {
+        self.properties.get_detachable_function()
+    }
+
+    pub fn set_detachment(
+        &'a self,
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        self.properties
+            .set_detachment(ReadableBuilder::Definition(self), deductable_ref, errors);
+    }
+
+    pub fn get_detachment(&self) -> Option<DeductableBuilder<'a>> {
+        self.properties.get_detachment()
+    }
+
     pub fn is_reflexive(&self) -> bool {
         self.properties.is_reflexive()
     }
@@ -1765,7 +2449,7 @@ impl<'a> DefinitionBuilder<'a> {
             .collect();
 
         let type_signature = self.type_signature.get().unwrap().finish();
-        let display = self.entries.display();
+        let display = self.display();
         let inputs = self
             .entries
             .inputs()
@@ -1804,7 +2488,7 @@ impl<'a> DefinitionBuilder<'a> {
 
     pub fn read_signature(&'a self) -> Option<ReadSignature> {
         self.entries.read().map(|read| ReadSignature {
-            read,
+            notation: read.notation.clone(),
             inputs: self
                 .entries
                 .inputs()
@@ -1814,6 +2498,21 @@ impl<'a> DefinitionBuilder<'a> {
         })
     }
 
+    pub fn read_fixity(&self) -> Option<ReadFixity> {
+        self.entries.read().and_then(ReadBuilder::fixity)
+    }
+
+    // Symbols which are neither read nor displayed are written out by name.
+    fn display(&self) -> Display {
+        self.entries
+            .display()
+            .unwrap_or_else(|| Display::new(DisplayStyle::Standard, self.id.clone()))
+    }
+
+    pub fn inputs(&self) -> &[VariableBuilder<'a>] {
+        self.entries.inputs()
+    }
+
     pub fn serial(&self) -> usize {
         self.location.serial()
     }
@@ -1821,6 +2520,10 @@ impl<'a> DefinitionBuilder<'a> {
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> PartialEq for DefinitionBuilder<'a> {
@@ -1864,13 +2567,41 @@ impl<'a> ReadableBuilder<'a> {
         }
     }
 
-    fn type_signature(&'a self) -> &TypeSignatureBuilder {
+    pub fn file_location(&self) -> &FileLocation {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.file_location(),
+            Self::Definition(definition_ref) => definition_ref.file_location(),
+        }
+    }
+
+    pub fn type_signature(&self) -> &'a TypeSignatureBuilder<'a> {
         match self {
             Self::Symbol(symbol_ref) => symbol_ref.type_signature(),
             Self::Definition(definition_ref) => definition_ref.type_signature(),
         }
     }
 
+    fn serial(&self) -> usize {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.serial(),
+            Self::Definition(definition_ref) => definition_ref.serial(),
+        }
+    }
+
+    pub fn read_fixity(&self) -> Option<ReadFixity> {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.read_fixity(),
+            Self::Definition(definition_ref) => definition_ref.read_fixity(),
+        }
+    }
+
+    fn display(&self) -> Display {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.display(),
+            Self::Definition(definition_ref) => definition_ref.display(),
+        }
+    }
+
     pub fn set_reflexive(
         &self,
         deductable_ref: DeductableBuilder<'a>,
@@ -1931,66 +2662,191 @@ impl<'a> ReadableBuilder<'a> {
         }
     }
 
-    pub fn set_function(
+    pub fn set_transitive_with(
         &self,
         deductable_ref: DeductableBuilder<'a>,
+        other: ReadableBuilder<'a>,
+        result: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        match self {
+            Self::Symbol(symbol_ref) => {
+                symbol_ref.set_transitive_with(deductable_ref, other, result, errors)
+            }
+            Self::Definition(definition_ref) => {
+                definition_ref.set_transitive_with(deductable_ref, other, result, errors)
+            }
+        }
+    }
+
+    // The relation which follows from this relation and then `other`, along with the claim which
+    // proves it.
+    pub fn get_transitive_with(
+        &self,
+        other: ReadableBuilder<'a>,
+    ) -> Option<(ReadableBuilder<'a>, DeductableBuilder<'a>)> {
+        if *self == other {
+            return self
+                .get_transitive()
+                .map(|deductable_ref| (other, deductable_ref));
+        }
+
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.get_transitive_with(other),
+            Self::Definition(definition_ref) => definition_ref.get_transitive_with(other),
+        }
+    }
+
+    pub fn set_function(
+        &self,
+        function_ref: FunctionBuilder<'a>,
         relation: ReadableBuilder<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
         match self {
-            Self::Symbol(symbol_ref) => symbol_ref.set_function(deductable_ref, relation, errors),
+            Self::Symbol(symbol_ref) => symbol_ref.set_function(function_ref, relation, errors),
             Self::Definition(definition_ref) => {
-                definition_ref.set_function(deductable_ref, relation, errors)
+                definition_ref.set_function(function_ref, relation, errors)
             }
         }
     }
 
-    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+    pub fn get_function(&self, relation: ReadableBuilder<'a>) -> Option<FunctionBuilder<'a>> {
         match self {
             Self::Symbol(symbol_ref) => symbol_ref.get_function(relation),
             Self::Definition(definition_ref) => definition_ref.get_function(relation),
         }
     }
 
-    pub fn is_preorder(&self) -> bool {
+    pub fn set_commutative(
+        &self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
         match self {
-            Self::Symbol(symbol_ref) => symbol_ref.is_preorder(),
-            Self::Definition(definition_ref) => definition_ref.is_preorder(),
+            Self::Symbol(symbol_ref) => {
+                symbol_ref.set_commutative(deductable_ref, relation, errors)
+            }
+            Self::Definition(definition_ref) => {
+                definition_ref.set_commutative(deductable_ref, relation, errors)
+            }
         }
     }
 
-    pub fn is_symmetric(&self) -> bool {
+    pub fn get_commutative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
         match self {
-            Self::Symbol(symbol_ref) => symbol_ref.is_symmetric(),
-            Self::Definition(definition_ref) => definition_ref.is_symmetric(),
+            Self::Symbol(symbol_ref) => symbol_ref.get_commutative(relation),
+            Self::Definition(definition_ref) => definition_ref.get_commutative(relation),
         }
     }
-}
-
-#[derive(Debug)]
-pub struct VariableBuilder<'a> {
-    id: String,
-    index: usize,
-
-    type_signature: TypeSignatureBuilder<'a>,
-}
-
-impl<'a> VariableBuilder<'a> {
-    pub fn from_pest(pair: Pair<Rule>, index: usize) -> Self {
-        assert_eq!(pair.as_rule(), Rule::var_declaration);
+
+    pub fn set_associative(
+        &self,
+        deductable_ref: DeductableBuilder<'a>,
+        relation: ReadableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        match self {
+            Self::Symbol(symbol_ref) => {
+                symbol_ref.set_associative(deductable_ref, relation, errors)
+            }
+            Self::Definition(definition_ref) => {
+                definition_ref.set_associative(deductable_ref, relation, errors)
+            }
+        }
+    }
+
+    pub fn get_associative(&self, relation: ReadableBuilder<'a>) -> Option<DeductableBuilder<'a>> {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.get_associative(relation),
+            Self::Definition(definition_ref) => definition_ref.get_associative(relation),
+        }
+    }
+
+    pub fn get_detachable_function(&self) -> Option<(ReadableBuilder<'a>, FunctionBuilder<'a>)> {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.get_detachable_function(),
+            Self::Definition(definition_ref) => definition_ref.get_detachable_function(),
+        }
+    }
+
+    pub fn set_detachment(
+        &self,
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.set_detachment(deductable_ref, errors),
+            Self::Definition(definition_ref) => {
+                definition_ref.set_detachment(deductable_ref, errors)
+            }
+        }
+    }
+
+    pub fn get_detachment(&self) -> Option<DeductableBuilder<'a>> {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.get_detachment(),
+            Self::Definition(definition_ref) => definition_ref.get_detachment(),
+        }
+    }
+
+    pub fn is_preorder(&self) -> bool {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.is_preorder(),
+            Self::Definition(definition_ref) => definition_ref.is_preorder(),
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        match self {
+            Self::Symbol(symbol_ref) => symbol_ref.is_symmetric(),
+            Self::Definition(definition_ref) => definition_ref.is_symmetric(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VariableBuilder<'a> {
+    id: String,
+    index: usize,
+    file_location: FileLocation,
+
+    // Whether the variable is declared by a proof, rather than the theorem it proves.
+    local: bool,
+
+    type_signature: TypeSignatureBuilder<'a>,
+}
+
+impl<'a> VariableBuilder<'a> {
+    pub fn from_pest(path: &Path, pair: Pair<Rule>, index: usize) -> Self {
+        assert_eq!(pair.as_rule(), Rule::var_declaration);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let id = id_pair.as_str().to_owned();
+        let file_location = FileLocation::new(path, id_pair.as_span());
         let type_signature = TypeSignatureBuilder::from_pest(inner.next().unwrap());
 
         VariableBuilder {
             id,
             index,
+            file_location,
+
+            local: false,
 
             type_signature,
         }
     }
 
+    pub fn local_from_pest(path: &Path, pair: Pair<Rule>, index: usize) -> Self {
+        VariableBuilder {
+            local: true,
+
+            ..Self::from_pest(path, pair, index)
+        }
+    }
+
     pub fn verify_structure<F>(
         &'a self,
         parent_system: &str,
@@ -2023,6 +2879,14 @@ impl<'a> VariableBuilder<'a> {
         self.index
     }
 
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     pub fn type_signature(&'a self) -> &TypeSignatureBuilder {
         &self.type_signature
     }
@@ -2035,30 +2899,151 @@ impl<'a> PartialEq for VariableBuilder<'a> {
 }
 impl<'a> Eq for VariableBuilder<'a> {}
 
+// A symbol or definition referred to by its id alone, without any inputs.
 #[derive(Clone, Debug)]
 pub struct FormulaSymbolBuilder<'a> {
+    // `None` for a child of the formula's own system or one of its ancestors.
+    system_id: Option<String>,
     id: String,
+    file_location: FileLocation,
 
-    symbol_ref: OnceCell<&'a SymbolBuilder<'a>>,
+    readable_ref: OnceCell<ReadableBuilder<'a>>,
 }
 
 impl<'a> FormulaSymbolBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>) -> Self {
-        assert_eq!(pair.as_rule(), Rule::ident);
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        let file_location = FileLocation::new(path, pair.as_span());
+        let (system_id, id) = match pair.as_rule() {
+            Rule::fqid => {
+                let mut inner = pair.into_inner();
+                let system_id = inner.next().unwrap().as_str().to_owned();
+                let id = inner.next().unwrap().as_str().to_owned();
+
+                (Some(system_id), id)
+            }
+            Rule::ident => (None, pair.as_str().to_owned()),
 
-        let id = pair.as_str().to_owned();
+            _ => unreachable!(),
+        };
 
         FormulaSymbolBuilder {
+            system_id,
             id,
+            file_location,
+
+            readable_ref: OnceCell::new(),
+        }
+    }
+
+    // The id as it was written, qualified by its system if it was.
+    fn written_id(&self) -> String {
+        match &self.system_id {
+            Some(system_id) => format!("{}.{}", system_id, self.id),
+            None => self.id.clone(),
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a>,
+    {
+        let child = match &self.system_id {
+            Some(system_id) if !local_index.extends(system_id) => {
+                errors.err(generate_error(
+                    formula_ref,
+                    FormulaParsingError::SymbolSystemNotExtended(system_id.clone()),
+                ));
+                return false;
+            }
+
+            Some(system_id) => local_index.search_qualified_child(system_id, &self.id),
+            None => local_index.search_child(&self.id),
+        };
+
+        let readable_ref = match child {
+            Some(child) => match child.readable() {
+                Some(readable_ref) => readable_ref,
+
+                None => {
+                    errors.err(generate_error(
+                        formula_ref,
+                        FormulaParsingError::SymbolWrongKind(self.written_id()),
+                    ));
+                    return false;
+                }
+            },
+
+            None => {
+                errors.err(generate_error(
+                    formula_ref,
+                    FormulaParsingError::SymbolIdNotFound(self.written_id()),
+                ));
+                return false;
+            }
+        };
+
+        // Definitions only get their type once their own formulas are built, so anything declared
+        // later can't be used yet.
+        if !local_index.precedes(readable_ref.serial()) {
+            errors.err(generate_error(
+                formula_ref,
+                FormulaParsingError::SymbolForwardReference(self.written_id()),
+            ));
+            return false;
+        }
+
+        self.readable_ref.set(readable_ref).unwrap();
+        true
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        match self.readable_ref.get().unwrap() {
+            ReadableBuilder::Symbol(symbol) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                FormulaBlock::Symbol(symbol_ref)
+            }
+
+            // Definitions are always given all of their inputs, so one on its own is wrapped in an
+            // abstraction for each input.
+            ReadableBuilder::Definition(definition) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
 
-            symbol_ref: OnceCell::new(),
+                let inputs = definition.inputs();
+                let bound = (0..inputs.len()).rev().map(FormulaBlock::Bound).collect();
+
+                inputs.iter().rev().fold(
+                    FormulaBlock::Definition(definition_ref, bound),
+                    |body, input| {
+                        FormulaBlock::Abstraction(input.type_signature.finish(), Box::new(body))
+                    },
+                )
+            }
         }
     }
+
+    fn type_signature(&'a self) -> &'a TypeSignatureBuilder<'a> {
+        self.readable_ref.get().unwrap().type_signature()
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let display = self.readable_ref.get().unwrap().display();
+
+        elements.extend(display.math_applied(Vec::new()));
+    }
 }
 
 impl<'a> PartialEq for FormulaSymbolBuilder<'a> {
     fn eq(&self, other: &Self) -> bool {
-        todo!()
+        self.readable_ref.get().unwrap() == other.readable_ref.get().unwrap()
     }
 }
 impl<'a> Eq for FormulaSymbolBuilder<'a> {}
@@ -2066,21 +3051,31 @@ impl<'a> Eq for FormulaSymbolBuilder<'a> {}
 #[derive(Clone, Debug)]
 pub struct FormulaVariableBuilder<'a> {
     id: String,
+    file_location: FileLocation,
 
     var_ref: OnceCell<&'a VariableBuilder<'a>>,
+    bound_index: OnceCell<usize>,
+
+    // The formula abbreviated by the variable, if it was introduced by a `let` in a proof.
+    abbreviation: OnceCell<&'a FormulaBuilder<'a>>,
 }
 
 impl<'a> FormulaVariableBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
         assert_eq!(pair.as_rule(), Rule::var);
 
+        let file_location = FileLocation::new(path, pair.as_span());
         let pair = pair.into_inner().next().unwrap();
         let id = pair.as_str().to_owned();
 
         FormulaVariableBuilder {
             id,
+            file_location,
 
             var_ref: OnceCell::new(),
+            bound_index: OnceCell::new(),
+
+            abbreviation: OnceCell::new(),
         }
     }
 
@@ -2092,10 +3087,23 @@ impl<'a> FormulaVariableBuilder<'a> {
         generate_error: F,
     ) -> bool
     where
-        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError) -> ParsingError<'a>,
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a>,
     {
         assert!(self.var_ref.get().is_none());
 
+        if let Some((bound_index, var)) = local_index.search_bound(&self.id) {
+            self.var_ref.set(var).unwrap();
+            self.bound_index.set(bound_index).unwrap();
+
+            return true;
+        }
+
+        if let Some(formula) = local_index.search_abbreviation(&self.id) {
+            self.abbreviation.set(formula).unwrap();
+
+            return true;
+        }
+
         match local_index.search_variable(&self.id) {
             Some(var) => {
                 self.var_ref.set(var).unwrap();
@@ -2105,7 +3113,7 @@ impl<'a> FormulaVariableBuilder<'a> {
             None => {
                 errors.err(generate_error(
                     formula_ref,
-                    FormulaParsingError::VariableIdNotFound,
+                    FormulaParsingError::VariableIdNotFound(self.id.clone()),
                 ));
                 false
             }
@@ -2113,46 +3121,77 @@ impl<'a> FormulaVariableBuilder<'a> {
     }
 
     fn finish<'b>(&self) -> FormulaBlock<'b> {
-        let index = self.var_ref.get().unwrap().index();
-        let var_ref = VariableBlockRef::new(index);
+        if let Some(bound_index) = self.bound_index.get() {
+            return FormulaBlock::Bound(*bound_index);
+        }
+        if let Some(formula) = self.abbreviation.get() {
+            return formula.finish();
+        }
+
+        let var = self.var_ref.get().unwrap();
+        let var_ref = if var.is_local() {
+            VariableBlockRef::local(var.index())
+        } else {
+            VariableBlockRef::new(var.index())
+        };
 
         FormulaBlock::Variable(var_ref)
     }
 
     fn type_signature(&'a self) -> &TypeSignatureBuilder {
-        self.var_ref.get().unwrap().type_signature()
+        match self.abbreviation.get() {
+            Some(formula) => formula.type_signature(),
+            None => self.var_ref.get().unwrap().type_signature(),
+        }
+    }
+
+    fn is_bound(&self) -> bool {
+        self.bound_index.get().is_some()
+    }
+
+    fn abbreviation(&self) -> Option<&'a FormulaBuilder<'a>> {
+        self.abbreviation.get().copied()
+    }
+
+    fn math(&self) -> MathElement {
+        MathElement::Variable(map_ident(&self.id).to_owned())
     }
 }
 
 impl<'a> PartialEq for FormulaVariableBuilder<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.var_ref.get().unwrap() == other.var_ref.get().unwrap()
+        match (self.bound_index.get(), other.bound_index.get()) {
+            (Some(self_index), Some(other_index)) => self_index == other_index,
+            (None, None) => self.var_ref.get().unwrap() == other.var_ref.get().unwrap(),
+
+            _ => false,
+        }
     }
 }
 impl<'a> Eq for FormulaVariableBuilder<'a> {}
 
-// TODO: Figure out how to remove this.
-pub trait ExactSizeDoubleEndedIterator: ExactSizeIterator + DoubleEndedIterator {}
-
-impl<T> ExactSizeDoubleEndedIterator for std::iter::Once<T> {}
-impl<T, const N: usize> ExactSizeDoubleEndedIterator for std::array::IntoIter<T, N> {}
-impl<T> ExactSizeDoubleEndedIterator for Box<T> where T: ExactSizeDoubleEndedIterator + ?Sized {}
-
 #[derive(Clone, Debug)]
-pub struct FormulaPrefixBuilder<'a> {
-    operator: ReadOperator,
-    inner: Box<FormulaBuilder<'a>>,
-
-    operator_ref: OnceCell<ReadableBuilder<'a>>,
+pub struct FormulaVariableApplicationBuilder<'a> {
+    variable: FormulaVariableBuilder<'a>,
+    inputs: Vec<FormulaBuilder<'a>>,
+    file_location: FileLocation,
 }
 
-impl<'a> FormulaPrefixBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>, inner: FormulaBuilder<'a>) -> Self {
-        FormulaPrefixBuilder {
-            operator: ReadOperator::from_pest(pair),
-            inner: Box::new(inner),
+impl<'a> FormulaVariableApplicationBuilder<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::var_application);
 
-            operator_ref: OnceCell::new(),
+        let file_location = FileLocation::new(path, pair.as_span());
+        let mut inner = pair.into_inner();
+        let variable = FormulaVariableBuilder::from_pest(path, inner.next().unwrap());
+        let inputs = inner
+            .map(|pair| FormulaBuilder::from_pest(path, pair))
+            .collect();
+
+        FormulaVariableApplicationBuilder {
+            variable,
+            inputs,
+            file_location,
         }
     }
 
@@ -2164,102 +3203,576 @@ impl<'a> FormulaPrefixBuilder<'a> {
         generate_error: F,
     ) -> bool
     where
-        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError) -> ParsingError<'a> + Copy,
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
     {
-        if !self.inner.build(local_index, errors, generate_error) {
+        if !self
+            .variable
+            .build(formula_ref, local_index, errors, generate_error)
+        {
             return false;
-        };
-
-        let inner_type = self.inner.type_signature();
-        let read_signature = ReadSignature {
-            read: ReadBuilder {
-                style: ReadStyle::Prefix,
-                operator: self.operator,
-            },
-            inputs: vec![inner_type],
-        };
-
-        match local_index.search_operator(&read_signature) {
-            Some(operator_ref) => {
-                self.operator_ref.set(operator_ref).unwrap();
-                true
-            }
-
-            None => {
-                errors.err(generate_error(
-                    formula_ref,
-                    FormulaParsingError::OperatorNotFound,
-                ));
-                false
-            }
         }
-    }
-
-    fn finish<'b>(&self) -> FormulaBlock<'b> {
-        let inner = self.inner.finish();
-
-        match self.operator_ref.get().unwrap() {
-            ReadableBuilder::Symbol(symbol) => {
-                let symbol_location = symbol.location();
-                let symbol_ref = SymbolBlockRef::new(symbol_location);
 
-                FormulaBlock::Application(
-                    Box::new(FormulaBlock::Symbol(symbol_ref)),
-                    Box::new(inner),
-                )
+        for input in &self.inputs {
+            if !input.build(local_index, errors, generate_error) {
+                return false;
             }
+        }
 
-            ReadableBuilder::Definition(definition) => {
-                let definition_location = definition.location();
-                let definition_ref = DefinitionBlockRef::new(definition_location);
+        // The type variables of a variable's type are fixed, so the inputs have to match exactly.
+        let expected_inputs = self.variable.type_signature().inputs();
+        for (i, input) in self.inputs.iter().enumerate() {
+            let e = match expected_inputs.clone().nth(i) {
+                Some(expected) if expected == input.type_signature() => continue,
+
+                Some(expected) => FormulaParsingError::TypeMismatch(TypeError::new(
+                    expected.clone(),
+                    input.type_signature().clone(),
+                    input,
+                )),
+                None => FormulaParsingError::ApplicationTooManyInputs,
+            };
 
-                FormulaBlock::Definition(definition_ref, vec![inner])
-            }
+            errors.err(generate_error(formula_ref, e));
+            return false;
         }
+
+        true
     }
 
-    fn type_signature(&'a self) -> &TypeSignatureBuilder {
-        self.operator_ref.get().unwrap().type_signature().applied()
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        self.inputs
+            .iter()
+            .fold(self.variable.finish(), |curr, input| {
+                FormulaBlock::Application(Box::new(curr), Box::new(input.finish()))
+            })
     }
 
-    fn application(
-        &self,
-    ) -> Option<(
-        ReadableBuilder<'a>,
-        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
-    )> {
-        let readable = *self.operator_ref.get().unwrap();
-        let inputs = Box::new(std::iter::once(self.inner.as_ref()));
+    fn type_signature(&'a self) -> &'a TypeSignatureBuilder<'a> {
+        self.inputs
+            .iter()
+            .fold(self.variable.type_signature(), |curr, _| curr.applied())
+    }
 
-        Some((readable, inputs))
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        elements.push(self.variable.math());
+        elements.push(FormulaBuilder::math_inputs(&self.inputs));
     }
 }
 
-impl<'a> PartialEq for FormulaPrefixBuilder<'a> {
+impl<'a> PartialEq for FormulaVariableApplicationBuilder<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.operator_ref.get().unwrap() == other.operator_ref.get().unwrap()
-            && self.inner == other.inner
+        self.variable == other.variable && self.inputs == other.inputs
     }
 }
-impl<'a> Eq for FormulaPrefixBuilder<'a> {}
+impl<'a> Eq for FormulaVariableApplicationBuilder<'a> {}
 
 #[derive(Clone, Debug)]
-pub struct FormulaInfixBuilder<'a> {
-    operator: ReadOperator,
-    lhs: Box<FormulaBuilder<'a>>,
-    rhs: Box<FormulaBuilder<'a>>,
+pub struct FormulaSymbolApplicationBuilder<'a> {
+    symbol: FormulaSymbolBuilder<'a>,
+    inputs: Vec<FormulaBuilder<'a>>,
+    file_location: FileLocation,
 
-    operator_ref: OnceCell<ReadableBuilder<'a>>,
+    type_signature: OnceCell<TypeSignatureBuilder<'a>>,
 }
 
-impl<'a> FormulaInfixBuilder<'a> {
-    fn from_op(operator: ReadOperator, lhs: FormulaBuilder<'a>, rhs: FormulaBuilder<'a>) -> Self {
-        FormulaInfixBuilder {
-            operator,
-            lhs: Box::new(lhs),
+impl<'a> FormulaSymbolApplicationBuilder<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::symbol_application);
+
+        let file_location = FileLocation::new(path, pair.as_span());
+        let mut inner = pair.into_inner();
+        let symbol = FormulaSymbolBuilder::from_pest(path, inner.next().unwrap());
+        let inputs = inner
+            .map(|pair| FormulaBuilder::from_pest(path, pair))
+            .collect();
+
+        FormulaSymbolApplicationBuilder {
+            symbol,
+            inputs,
+            file_location,
+
+            type_signature: OnceCell::new(),
+        }
+    }
+
+    // The same application might also have been written with the symbol's operator.
+    fn test(&self, other: &FormulaBuilder<'a>) -> bool {
+        match (self.application(), other.application()) {
+            (Some((self_readable, self_inputs)), Some((other_readable, other_inputs))) => {
+                self_readable == other_readable && self_inputs.eq(other_inputs)
+            }
+
+            _ => false,
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
+    {
+        if !self
+            .symbol
+            .build(formula_ref, local_index, errors, generate_error)
+        {
+            return false;
+        }
+
+        for input in &self.inputs {
+            if !input.build(local_index, errors, generate_error) {
+                return false;
+            }
+        }
+
+        if let ReadableBuilder::Definition(definition) = self.symbol.readable_ref.get().unwrap() {
+            if self.inputs.len() < definition.inputs().len() {
+                errors.err(generate_error(
+                    formula_ref,
+                    FormulaParsingError::DefinitionPartialApplication(self.symbol.written_id()),
+                ));
+                return false;
+            }
+        }
+
+        match self.symbol.type_signature().apply_formulas(&self.inputs) {
+            Ok(type_signature) => {
+                self.type_signature.set(type_signature).unwrap();
+                true
+            }
+
+            Err(e) => {
+                errors.err(generate_error(formula_ref, e));
+                false
+            }
+        }
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        let (head, rest) = match self.symbol.readable_ref.get().unwrap() {
+            ReadableBuilder::Symbol(symbol) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                (FormulaBlock::Symbol(symbol_ref), &self.inputs[..])
+            }
+
+            // A definition takes its own inputs directly, and anything left over is applied to
+            // the result.
+            ReadableBuilder::Definition(definition) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
+
+                let (inputs, rest) = self.inputs.split_at(definition.inputs().len());
+                let inputs = inputs.iter().map(FormulaBuilder::finish).collect();
+
+                (FormulaBlock::Definition(definition_ref, inputs), rest)
+            }
+        };
+
+        rest.iter().fold(head, |curr, input| {
+            FormulaBlock::Application(Box::new(curr), Box::new(input.finish()))
+        })
+    }
+
+    fn type_signature(&'a self) -> &'a TypeSignatureBuilder<'a> {
+        self.type_signature.get().unwrap()
+    }
+
+    fn binary(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        &FormulaBuilder<'a>,
+        &FormulaBuilder<'a>,
+    )> {
+        match self.application()? {
+            (readable, _) if self.inputs.len() == 2 => {
+                Some((readable, &self.inputs[0], &self.inputs[1]))
+            }
+
+            _ => None,
+        }
+    }
+
+    fn application(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
+    )> {
+        let readable = *self.symbol.readable_ref.get().unwrap();
+
+        // Extra inputs to a definition are applied to its result, not read by it.
+        if let ReadableBuilder::Definition(definition) = readable {
+            if self.inputs.len() != definition.inputs().len() {
+                return None;
+            }
+        }
+
+        Some((readable, Box::new(self.inputs.iter())))
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let display = self.symbol.readable_ref.get().unwrap().display();
+        let inputs = FormulaBuilder::math_operands(&display, &self.inputs);
+
+        elements.extend(display.math_applied(inputs));
+    }
+
+    fn is_operator(&self) -> bool {
+        let display = self.symbol.readable_ref.get().unwrap().display();
+
+        display.is_operator(self.inputs.len())
+    }
+}
+
+impl<'a> PartialEq for FormulaSymbolApplicationBuilder<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol && self.inputs == other.inputs
+    }
+}
+impl<'a> Eq for FormulaSymbolApplicationBuilder<'a> {}
+
+#[derive(Clone, Debug)]
+pub struct FormulaBinderBuilder<'a> {
+    // `None` for a plain lambda abstraction, otherwise the symbol or definition applied to it.
+    binder_id: Option<String>,
+    var: Box<VariableBuilder<'a>>,
+    body: Box<FormulaBuilder<'a>>,
+    file_location: FileLocation,
+
+    binder_ref: OnceCell<ReadableBuilder<'a>>,
+    abstraction_type: OnceCell<TypeSignatureBuilder<'a>>,
+    type_signature: OnceCell<TypeSignatureBuilder<'a>>,
+}
+
+impl<'a> FormulaBinderBuilder<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::binder);
+
+        let file_location = FileLocation::new(path, pair.as_span());
+        let mut inner = pair.into_inner();
+        let binder = inner.next().unwrap();
+        let binder_id = match binder.as_rule() {
+            Rule::binder_lambda => None,
+            Rule::ident => Some(binder.as_str().to_owned()),
+
+            _ => unreachable!(),
+        };
+        let var = VariableBuilder::from_pest(path, inner.next().unwrap(), 0);
+        let body = FormulaBuilder::from_pest(path, inner.next().unwrap());
+
+        FormulaBinderBuilder {
+            binder_id,
+            var: Box::new(var),
+            body: Box::new(body),
+            file_location,
+
+            binder_ref: OnceCell::new(),
+            abstraction_type: OnceCell::new(),
+            type_signature: OnceCell::new(),
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
+    {
+        let var_success = local_index.verify_variable(&self.var, errors, |e| {
+            generate_error(formula_ref, FormulaParsingError::BinderVariableError(e))
+        });
+        if !var_success {
+            return false;
+        }
+
+        if !self
+            .body
+            .build(&local_index.bind(&self.var), errors, generate_error)
+        {
+            return false;
+        }
+
+        let abstraction_type = TypeSignatureBuilder::Compound(
+            Box::new(self.var.type_signature().clone()),
+            Box::new(self.body.type_signature().clone()),
+        );
+        self.abstraction_type.set(abstraction_type).unwrap();
+
+        let binder_id = match &self.binder_id {
+            Some(binder_id) => binder_id,
+
+            None => {
+                let abstraction_type = self.abstraction_type.get().unwrap().clone();
+                self.type_signature.set(abstraction_type).unwrap();
+
+                return true;
+            }
+        };
+
+        let binder_ref = match local_index.search_child(binder_id) {
+            Some(child) => match child.readable() {
+                Some(binder_ref) => binder_ref,
+
+                None => {
+                    errors.err(generate_error(
+                        formula_ref,
+                        FormulaParsingError::BinderWrongKind(binder_id.clone()),
+                    ));
+                    return false;
+                }
+            },
+
+            None => {
+                errors.err(generate_error(
+                    formula_ref,
+                    FormulaParsingError::BinderIdNotFound(binder_id.clone()),
+                ));
+                return false;
+            }
+        };
+
+        self.binder_ref.set(binder_ref).unwrap();
+
+        let abstraction_type = self.abstraction_type.get().unwrap();
+        let binder_type = self.binder_ref.get().unwrap().type_signature();
+        match binder_type.apply_inputs(std::iter::once(abstraction_type)) {
+            Ok(type_signature) => {
+                self.type_signature.set(type_signature).unwrap();
+                true
+            }
+
+            Err((_, expected)) => {
+                // The abstraction isn't a formula of its own, so the binder is blamed instead.
+                let e = match expected {
+                    Some(expected) => FormulaParsingError::TypeMismatch(TypeError::new(
+                        expected,
+                        abstraction_type.clone(),
+                        formula_ref,
+                    )),
+                    None => FormulaParsingError::ApplicationTooManyInputs,
+                };
+
+                errors.err(generate_error(formula_ref, e));
+                false
+            }
+        }
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        let abstraction = FormulaBlock::Abstraction(
+            self.var.type_signature.finish(),
+            Box::new(self.body.finish()),
+        );
+
+        match self.binder_ref.get() {
+            Some(ReadableBuilder::Symbol(symbol)) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                FormulaBlock::Application(
+                    Box::new(FormulaBlock::Symbol(symbol_ref)),
+                    Box::new(abstraction),
+                )
+            }
+
+            Some(ReadableBuilder::Definition(definition)) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
+
+                FormulaBlock::Definition(definition_ref, vec![abstraction])
+            }
+
+            None => abstraction,
+        }
+    }
+
+    pub fn var(&self) -> &VariableBuilder<'a> {
+        &self.var
+    }
+
+    fn type_signature(&'a self) -> &'a TypeSignatureBuilder<'a> {
+        self.type_signature.get().unwrap()
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let kind = self.binder_id.as_deref().unwrap_or("lambda");
+
+        elements.push(MathElement::Operator(map_ident(kind).to_owned()));
+        elements.push(MathElement::Variable(map_ident(self.var.id()).to_owned()));
+        elements.push(MathElement::Operator(".".to_owned()));
+        self.body.push_math(elements);
+    }
+}
+
+impl<'a> PartialEq for FormulaBinderBuilder<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        // Bound variables are compared by position, so this is equality up to renaming.
+        self.binder_ref.get() == other.binder_ref.get()
+            && self.var.type_signature == other.var.type_signature
+            && self.body == other.body
+    }
+}
+impl<'a> Eq for FormulaBinderBuilder<'a> {}
+
+// TODO: Figure out how to remove this.
+pub trait ExactSizeDoubleEndedIterator: ExactSizeIterator + DoubleEndedIterator {}
+
+impl<T> ExactSizeDoubleEndedIterator for std::iter::Once<T> {}
+impl<T, const N: usize> ExactSizeDoubleEndedIterator for std::array::IntoIter<T, N> {}
+impl<T> ExactSizeDoubleEndedIterator for std::slice::Iter<'_, T> {}
+impl<T> ExactSizeDoubleEndedIterator for Box<T> where T: ExactSizeDoubleEndedIterator + ?Sized {}
+
+#[derive(Clone, Debug)]
+pub struct FormulaPrefixBuilder<'a> {
+    operator: ReadOperator,
+    inner: Box<FormulaBuilder<'a>>,
+
+    operator_ref: OnceCell<ReadableBuilder<'a>>,
+    type_signature: OnceCell<TypeSignatureBuilder<'a>>,
+}
+
+impl<'a> FormulaPrefixBuilder<'a> {
+    fn from_op(operator: ReadOperator, inner: FormulaBuilder<'a>) -> Self {
+        FormulaPrefixBuilder {
+            operator,
+            inner: Box::new(inner),
+
+            operator_ref: OnceCell::new(),
+            type_signature: OnceCell::new(),
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
+    {
+        if !self.inner.build(local_index, errors, generate_error) {
+            return false;
+        };
+
+        let inner_type = self.inner.type_signature();
+        let read_signature = ReadSignature {
+            notation: ReadNotation::Prefix(self.operator.clone()),
+            inputs: vec![inner_type],
+        };
+
+        match local_index.search_operator(&read_signature) {
+            Some(operator_ref) => {
+                self.operator_ref.set(operator_ref).unwrap();
+
+                let type_signature = self.operator_ref.get().unwrap().type_signature();
+                let type_signature = type_signature.apply_inputs(read_signature.inputs);
+                self.type_signature.set(type_signature.unwrap()).unwrap();
+
+                true
+            }
+
+            None => {
+                let inputs = std::iter::once(self.inner.as_ref());
+                let e = local_index.operator_error(&read_signature, inputs);
+
+                errors.err(generate_error(formula_ref, e));
+                false
+            }
+        }
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        let inner = self.inner.finish();
+
+        match self.operator_ref.get().unwrap() {
+            ReadableBuilder::Symbol(symbol) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                FormulaBlock::Application(
+                    Box::new(FormulaBlock::Symbol(symbol_ref)),
+                    Box::new(inner),
+                )
+            }
+
+            ReadableBuilder::Definition(definition) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
+
+                FormulaBlock::Definition(definition_ref, vec![inner])
+            }
+        }
+    }
+
+    fn type_signature(&'a self) -> &TypeSignatureBuilder {
+        self.type_signature.get().unwrap()
+    }
+
+    fn application(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
+    )> {
+        let readable = *self.operator_ref.get().unwrap();
+        let inputs = Box::new(std::iter::once(self.inner.as_ref()));
+
+        Some((readable, inputs))
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let inputs = vec![self.inner.math()];
+
+        elements.extend(
+            self.operator_ref
+                .get()
+                .unwrap()
+                .display()
+                .math_applied(inputs),
+        );
+    }
+}
+
+impl<'a> PartialEq for FormulaPrefixBuilder<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.operator_ref.get().unwrap() == other.operator_ref.get().unwrap()
+            && self.inner == other.inner
+    }
+}
+impl<'a> Eq for FormulaPrefixBuilder<'a> {}
+
+#[derive(Clone, Debug)]
+pub struct FormulaInfixBuilder<'a> {
+    operator: ReadOperator,
+    lhs: Box<FormulaBuilder<'a>>,
+    rhs: Box<FormulaBuilder<'a>>,
+
+    operator_ref: OnceCell<ReadableBuilder<'a>>,
+    type_signature: OnceCell<TypeSignatureBuilder<'a>>,
+}
+
+impl<'a> FormulaInfixBuilder<'a> {
+    fn from_op(operator: ReadOperator, lhs: FormulaBuilder<'a>, rhs: FormulaBuilder<'a>) -> Self {
+        FormulaInfixBuilder {
+            operator,
+            lhs: Box::new(lhs),
             rhs: Box::new(rhs),
 
             operator_ref: OnceCell::new(),
+            type_signature: OnceCell::new(),
         }
     }
 
@@ -2271,7 +3784,7 @@ impl<'a> FormulaInfixBuilder<'a> {
         generate_error: F,
     ) -> bool
     where
-        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError) -> ParsingError<'a> + Copy,
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
     {
         if !self.lhs.build(local_index, errors, generate_error) {
             return false;
@@ -2283,24 +3796,26 @@ impl<'a> FormulaInfixBuilder<'a> {
         let lhs_type = self.lhs.type_signature();
         let rhs_type = self.rhs.type_signature();
         let read_signature = ReadSignature {
-            read: ReadBuilder {
-                style: ReadStyle::Infix,
-                operator: self.operator,
-            },
+            notation: ReadNotation::Infix(self.operator.clone()),
             inputs: vec![lhs_type, rhs_type],
         };
 
         match local_index.search_operator(&read_signature) {
             Some(operator_ref) => {
                 self.operator_ref.set(operator_ref).unwrap();
+
+                let type_signature = self.operator_ref.get().unwrap().type_signature();
+                let type_signature = type_signature.apply_inputs(read_signature.inputs);
+                self.type_signature.set(type_signature.unwrap()).unwrap();
+
                 true
             }
 
             None => {
-                errors.err(generate_error(
-                    formula_ref,
-                    FormulaParsingError::OperatorNotFound,
-                ));
+                let inputs = [self.lhs.as_ref(), self.rhs.as_ref()];
+                let e = local_index.operator_error(&read_signature, inputs);
+
+                errors.err(generate_error(formula_ref, e));
                 false
             }
         }
@@ -2334,12 +3849,7 @@ impl<'a> FormulaInfixBuilder<'a> {
     }
 
     fn type_signature(&'a self) -> &TypeSignatureBuilder {
-        self.operator_ref
-            .get()
-            .unwrap()
-            .type_signature()
-            .applied()
-            .applied()
+        self.type_signature.get().unwrap()
     }
 
     fn binary(
@@ -2356,145 +3866,599 @@ impl<'a> FormulaInfixBuilder<'a> {
         Some((readable, left, right))
     }
 
-    fn application(
-        &self,
-    ) -> Option<(
-        ReadableBuilder<'a>,
-        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
-    )> {
-        let readable = *self.operator_ref.get().unwrap();
-        let inputs = Box::new(std::array::IntoIter::new([
-            self.lhs.as_ref(),
-            self.rhs.as_ref(),
-        ]));
+    fn application(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
+    )> {
+        let readable = *self.operator_ref.get().unwrap();
+        let inputs = Box::new(std::array::IntoIter::new([
+            self.lhs.as_ref(),
+            self.rhs.as_ref(),
+        ]));
+
+        Some((readable, inputs))
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let inputs = vec![self.lhs.math(), self.rhs.math()];
+
+        elements.extend(
+            self.operator_ref
+                .get()
+                .unwrap()
+                .display()
+                .math_applied(inputs),
+        );
+    }
+}
+
+impl<'a> PartialEq for FormulaInfixBuilder<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.operator_ref.get().unwrap() == other.operator_ref.get().unwrap()
+            && self.lhs == other.lhs
+            && self.rhs == other.rhs
+    }
+}
+impl<'a> Eq for FormulaInfixBuilder<'a> {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FormulaReadableApplicationBuilder<'a> {
+    readable: ReadableBuilder<'a>,
+    inputs: Vec<FormulaBuilder<'a>>,
+}
+
+impl<'a> FormulaReadableApplicationBuilder<'a> {
+    pub fn new(readable: ReadableBuilder<'a>, inputs: Vec<FormulaBuilder<'a>>) -> Self {
+        FormulaReadableApplicationBuilder { readable, inputs }
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let display = self.readable.display();
+        let inputs = FormulaBuilder::math_operands(&display, &self.inputs);
+
+        elements.extend(display.math_applied(inputs));
+    }
+
+    fn is_operator(&self) -> bool {
+        self.readable.display().is_operator(self.inputs.len())
+    }
+
+    fn binary(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        &FormulaBuilder<'a>,
+        &FormulaBuilder<'a>,
+    )> {
+        match self.inputs.as_slice() {
+            [left, right] => Some((self.readable, left, right)),
+
+            _ => None,
+        }
+    }
+
+    fn application(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
+    )> {
+        Some((self.readable, Box::new(self.inputs.iter())))
+    }
+
+    pub fn test(&self, other: &FormulaBuilder<'a>) -> bool {
+        match other {
+            FormulaBuilder::Symbol(formula) => {
+                self.inputs.is_empty() && &self.readable == formula.readable_ref.get().unwrap()
+            }
+            FormulaBuilder::Variable(_) => false,
+            FormulaBuilder::VariableApplication(_) => false,
+            FormulaBuilder::SymbolApplication(formula) => match formula.application() {
+                Some((readable, inputs)) => {
+                    self.readable == readable && inputs.eq(self.inputs.iter())
+                }
+
+                None => false,
+            },
+            FormulaBuilder::Binder(_) => false,
+            FormulaBuilder::Operators(formula) => self.test(formula.resolved()),
+
+            FormulaBuilder::Prefix(formula) => {
+                self.inputs.len() == 1
+                    && &self.readable == formula.operator_ref.get().unwrap()
+                    && &self.inputs[0] == formula.inner.as_ref()
+            }
+            FormulaBuilder::Infix(formula) => {
+                self.inputs.len() == 2
+                    && &self.readable == formula.operator_ref.get().unwrap()
+                    && &self.inputs[0] == formula.lhs.as_ref()
+                    && &self.inputs[1] == formula.rhs.as_ref()
+            }
+            FormulaBuilder::Mixfix(formula) => {
+                &self.readable == formula.readable.get().unwrap() && self.inputs == formula.inputs
+            }
+
+            FormulaBuilder::ReadableApplication(app) => self == app,
+        }
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        match self.readable {
+            ReadableBuilder::Symbol(symbol) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                self.inputs
+                    .iter()
+                    .fold(FormulaBlock::Symbol(symbol_ref), |curr, input| {
+                        FormulaBlock::Application(Box::new(curr), Box::new(input.finish()))
+                    })
+            }
+
+            ReadableBuilder::Definition(definition) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
+
+                FormulaBlock::Definition(
+                    definition_ref,
+                    self.inputs.iter().map(FormulaBuilder::finish).collect(),
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FormulaMixfixBuilder<'a> {
+    delimiters: Vec<String>,
+    inputs: Vec<FormulaBuilder<'a>>,
+
+    readable: OnceCell<ReadableBuilder<'a>>,
+    type_signature: OnceCell<TypeSignatureBuilder<'a>>,
+}
+
+impl<'a> FormulaMixfixBuilder<'a> {
+    fn from_delimiters(delimiters: Vec<String>, inputs: Vec<FormulaBuilder<'a>>) -> Self {
+        FormulaMixfixBuilder {
+            delimiters,
+            inputs,
+
+            readable: OnceCell::new(),
+            type_signature: OnceCell::new(),
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
+    {
+        for input in &self.inputs {
+            if !input.build(local_index, errors, generate_error) {
+                return false;
+            }
+        }
+
+        let read_signature = ReadSignature {
+            notation: ReadNotation::Mixfix(self.delimiters.clone()),
+            inputs: self
+                .inputs
+                .iter()
+                .map(FormulaBuilder::type_signature)
+                .collect(),
+        };
+
+        match local_index.search_operator(&read_signature) {
+            Some(readable) => {
+                self.readable.set(readable).unwrap();
+
+                let type_signature = self.readable.get().unwrap().type_signature();
+                let type_signature = type_signature.apply_inputs(read_signature.inputs);
+                self.type_signature.set(type_signature.unwrap()).unwrap();
+
+                true
+            }
+
+            None => {
+                let e = local_index.operator_error(&read_signature, &self.inputs);
+
+                errors.err(generate_error(formula_ref, e));
+                false
+            }
+        }
+    }
+
+    fn finish<'b>(&self) -> FormulaBlock<'b> {
+        match self.readable.get().unwrap() {
+            ReadableBuilder::Symbol(symbol) => {
+                let symbol_location = symbol.location();
+                let symbol_ref = SymbolBlockRef::new(symbol_location);
+
+                self.inputs
+                    .iter()
+                    .fold(FormulaBlock::Symbol(symbol_ref), |curr, input| {
+                        FormulaBlock::Application(Box::new(curr), Box::new(input.finish()))
+                    })
+            }
+
+            ReadableBuilder::Definition(definition) => {
+                let definition_location = definition.location();
+                let definition_ref = DefinitionBlockRef::new(definition_location);
+
+                FormulaBlock::Definition(
+                    definition_ref,
+                    self.inputs.iter().map(FormulaBuilder::finish).collect(),
+                )
+            }
+        }
+    }
+
+    fn type_signature(&'a self) -> &'a TypeSignatureBuilder<'a> {
+        self.type_signature.get().unwrap()
+    }
+
+    fn binary(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        &FormulaBuilder<'a>,
+        &FormulaBuilder<'a>,
+    )> {
+        match self.inputs.as_slice() {
+            [left, right] => Some((*self.readable.get().unwrap(), left, right)),
+
+            _ => None,
+        }
+    }
+
+    fn application(
+        &self,
+    ) -> Option<(
+        ReadableBuilder<'a>,
+        Box<dyn ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>> + '_>,
+    )> {
+        let readable = *self.readable.get().unwrap();
+        let inputs = Box::new(self.inputs.iter());
+
+        Some((readable, inputs))
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        let inputs = self.inputs.iter().map(FormulaBuilder::math).collect();
+
+        elements.extend(self.readable.get().unwrap().display().math_applied(inputs));
+    }
+}
+
+impl<'a> PartialEq for FormulaMixfixBuilder<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.readable.get().unwrap() == other.readable.get().unwrap() && self.inputs == other.inputs
+    }
+}
+impl<'a> Eq for FormulaMixfixBuilder<'a> {}
+
+#[derive(Clone, Debug)]
+enum FormulaItemBuilder<'a> {
+    Primary(Box<FormulaBuilder<'a>>),
+    Operator(String),
+}
+
+type FormulaItems<'a, 'b> = std::iter::Peekable<std::slice::Iter<'b, FormulaItemBuilder<'a>>>;
+
+// A formula containing operators or notations, as the flat sequence of terms and tokens it was
+// written as. Neither the precedence of an operator nor the notations which it might open are
+// known until every read in the system has been indexed, so the sequence is only parsed when it's
+// built.
+#[derive(Clone, Debug)]
+pub struct FormulaOperatorsBuilder<'a> {
+    items: Vec<FormulaItemBuilder<'a>>,
+    parenthesized: bool,
+    // `None` for a formula put together from others rather than written out.
+    file_location: Option<FileLocation>,
+
+    resolved: OnceCell<Box<FormulaBuilder<'a>>>,
+}
+
+impl<'a> FormulaOperatorsBuilder<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>, parenthesized: bool) -> Self {
+        assert!(matches!(
+            pair.as_rule(),
+            Rule::formula | Rule::proof_calculation_formula
+        ));
+
+        let file_location = FileLocation::new(path, pair.as_span());
+        let mut items = Vec::new();
+        Self::push_items(path, pair, &mut items);
+
+        FormulaOperatorsBuilder {
+            items,
+            parenthesized,
+            file_location: Some(file_location),
+
+            resolved: OnceCell::new(),
+        }
+    }
+
+    // The delimiters of a bracketed group are tokens like any other, so the group is flattened
+    // into the surrounding formula.
+    fn push_items(path: &Path, pair: Pair<Rule>, items: &mut Vec<FormulaItemBuilder<'a>>) {
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::read_operator | Rule::proof_calculation_operator => {
+                    items.push(FormulaItemBuilder::Operator(pair.as_str().to_owned()))
+                }
+
+                Rule::notation_group => {
+                    for pair in pair.into_inner() {
+                        match pair.as_rule() {
+                            Rule::formula => Self::push_items(path, pair, items),
+
+                            _ => items.push(FormulaItemBuilder::Operator(pair.as_str().to_owned())),
+                        }
+                    }
+                }
+
+                _ => items.push(FormulaItemBuilder::Primary(Box::new(
+                    FormulaBuilder::primary(path, pair),
+                ))),
+            }
+        }
+    }
+
+    fn build<F>(
+        &'a self,
+        formula_ref: &'a FormulaBuilder<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
+    {
+        let mut items = self.items.iter().peekable();
+
+        // The operators and notations parsed out of the sequence weren't written on their own, so
+        // errors in them are reported at the whole sequence. The closure is passed on as a trait
+        // object, since sequences can be nested.
+        let resolved_error = move |formula: &'a FormulaBuilder<'a>, e| match formula.file_location()
+        {
+            Some(_) => generate_error(formula, e),
+            None => generate_error(formula_ref, e),
+        };
+        let resolved_error: &dyn Fn(_, _) -> _ = &resolved_error;
+
+        match Self::parse_expression(local_index, &mut items, 0, &[]) {
+            Ok(resolved) => {
+                self.resolved.set(Box::new(resolved)).unwrap();
+                self.resolved().build(local_index, errors, resolved_error)
+            }
 
-        Some((readable, inputs))
+            Err(e) => {
+                errors.err(generate_error(formula_ref, e));
+                false
+            }
+        }
     }
-}
 
-impl<'a> PartialEq for FormulaInfixBuilder<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.operator_ref.get().unwrap() == other.operator_ref.get().unwrap()
-            && self.lhs == other.lhs
-            && self.rhs == other.rhs
+    fn fixity(
+        local_index: &LocalBuilderIndex<'a, '_>,
+        notation: ReadNotation,
+    ) -> Result<ReadFixity, FormulaParsingError<'a>> {
+        local_index
+            .search_fixity(&notation)
+            .ok_or_else(|| FormulaParsingError::OperatorNotFound(notation.describe()))
     }
-}
-impl<'a> Eq for FormulaInfixBuilder<'a> {}
 
-#[derive(Clone, PartialEq, Eq, Debug)]
-pub struct FormulaReadableApplicationBuilder<'a> {
-    readable: ReadableBuilder<'a>,
-    inputs: Vec<FormulaBuilder<'a>>,
-}
+    // Parses operators until one which binds less tightly than `curr_prec`, or one of `stops`,
+    // which are the delimiters that could close the notation being parsed.
+    fn parse_expression(
+        local_index: &LocalBuilderIndex<'a, '_>,
+        items: &mut FormulaItems<'a, '_>,
+        curr_prec: usize,
+        stops: &[&str],
+    ) -> Result<FormulaBuilder<'a>, FormulaParsingError<'a>> {
+        let mut lhs = Self::parse_operand(local_index, items, stops)?;
+        let mut non_associative = None;
 
-impl<'a> FormulaReadableApplicationBuilder<'a> {
-    pub fn new(readable: ReadableBuilder<'a>, inputs: Vec<FormulaBuilder<'a>>) -> Self {
-        FormulaReadableApplicationBuilder { readable, inputs }
-    }
+        // Google "Precedence Climbing".
+        loop {
+            let infix = match items.peek() {
+                Some(FormulaItemBuilder::Operator(operator))
+                    if !stops.contains(&operator.as_str()) =>
+                {
+                    ReadOperator::new(operator)
+                }
+                Some(FormulaItemBuilder::Primary(_)) => {
+                    return Err(FormulaParsingError::OperatorMissing)
+                }
 
-    pub fn test(&self, other: &FormulaBuilder<'a>) -> bool {
-        match other {
-            FormulaBuilder::Symbol(_) => todo!(),
-            FormulaBuilder::Variable(_) => false,
+                _ => break,
+            };
 
-            FormulaBuilder::Prefix(_) => todo!(),
-            FormulaBuilder::Infix(formula) => {
-                self.inputs.len() == 2
-                    && &self.readable == formula.operator_ref.get().unwrap()
-                    && &self.inputs[0] == formula.lhs.as_ref()
-                    && &self.inputs[1] == formula.rhs.as_ref()
+            let fixity = Self::fixity(local_index, ReadNotation::Infix(infix.clone()))?;
+            let precedence = fixity.precedence;
+
+            if precedence < curr_prec {
+                break;
+            }
+            if non_associative == Some(precedence) {
+                return Err(FormulaParsingError::OperatorNotAssociative(
+                    infix.as_str().to_owned(),
+                ));
             }
 
-            FormulaBuilder::ReadableApplication(app) => self == app,
+            items.next();
+            let next_prec = match fixity.associativity {
+                Associativity::Right => precedence,
+                Associativity::Left | Associativity::NonAssociative => precedence + 1,
+            };
+
+            let rhs = Self::parse_expression(local_index, items, next_prec, stops)?;
+            lhs = FormulaBuilder::Infix(FormulaInfixBuilder::from_op(infix, lhs, rhs));
+
+            non_associative = match fixity.associativity {
+                Associativity::NonAssociative => Some(precedence),
+                Associativity::Left | Associativity::Right => None,
+            };
         }
+
+        Ok(lhs)
     }
 
-    fn finish<'b>(&self) -> FormulaBlock<'b> {
-        match self.readable {
-            ReadableBuilder::Symbol(symbol) => {
-                let symbol_location = symbol.location();
-                let symbol_ref = SymbolBlockRef::new(symbol_location);
+    fn parse_operand(
+        local_index: &LocalBuilderIndex<'a, '_>,
+        items: &mut FormulaItems<'a, '_>,
+        stops: &[&str],
+    ) -> Result<FormulaBuilder<'a>, FormulaParsingError<'a>> {
+        match items.next() {
+            Some(FormulaItemBuilder::Primary(primary)) => Ok(primary.as_ref().clone()),
+
+            Some(FormulaItemBuilder::Operator(operator)) => {
+                if !local_index.search_notations(operator).is_empty() {
+                    return Self::parse_mixfix(local_index, items, operator);
+                }
+                if stops.contains(&operator.as_str()) {
+                    return Err(FormulaParsingError::OperandMissing);
+                }
 
-                self.inputs
-                    .iter()
-                    .fold(FormulaBlock::Symbol(symbol_ref), |curr, input| {
-                        FormulaBlock::Application(Box::new(curr), Box::new(input.finish()))
-                    })
+                // A prefix operator takes in every infix operator which binds more tightly than it
+                // does.
+                let prefix = ReadOperator::new(operator);
+                let fixity = Self::fixity(local_index, ReadNotation::Prefix(prefix.clone()))?;
+                let inner =
+                    Self::parse_expression(local_index, items, fixity.precedence + 1, stops)?;
+
+                Ok(FormulaBuilder::Prefix(FormulaPrefixBuilder::from_op(
+                    prefix, inner,
+                )))
             }
 
-            ReadableBuilder::Definition(definition) => {
-                let definition_location = definition.location();
-                let definition_ref = DefinitionBlockRef::new(definition_location);
+            None => Err(FormulaParsingError::OperandMissing),
+        }
+    }
 
-                FormulaBlock::Definition(
-                    definition_ref,
-                    self.inputs.iter().map(FormulaBuilder::finish).collect(),
-                )
+    // Every notation opened by `opener` is a candidate, and the delimiters which follow each input
+    // narrow them down until one of them is closed.
+    fn parse_mixfix(
+        local_index: &LocalBuilderIndex<'a, '_>,
+        items: &mut FormulaItems<'a, '_>,
+        opener: &str,
+    ) -> Result<FormulaBuilder<'a>, FormulaParsingError<'a>> {
+        let mut candidates = local_index.search_notations(opener);
+        let mut inputs = Vec::new();
+        let mut position = 1;
+
+        loop {
+            let stops: Vec<&str> = candidates
+                .iter()
+                .map(|delimiters| delimiters[position].as_str())
+                .collect();
+            inputs.push(Self::parse_expression(local_index, items, 0, &stops)?);
+
+            let delimiter = match items.next() {
+                Some(FormulaItemBuilder::Operator(delimiter)) => delimiter,
+
+                _ => return Err(FormulaParsingError::NotationUnclosed(opener.to_owned())),
+            };
+            candidates.retain(|delimiters| &delimiters[position] == delimiter);
+            position += 1;
+
+            if let Some(delimiters) = candidates
+                .iter()
+                .find(|delimiters| delimiters.len() == position)
+            {
+                return Ok(FormulaBuilder::Mixfix(
+                    FormulaMixfixBuilder::from_delimiters(delimiters.to_vec(), inputs),
+                ));
             }
         }
     }
+
+    fn resolved(&self) -> &FormulaBuilder<'a> {
+        self.resolved.get().unwrap()
+    }
+
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        if self.parenthesized {
+            elements.push(MathElement::Fenced(MathBlock::new(self.resolved().math())));
+        } else {
+            self.resolved().push_math(elements);
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum FormulaBuilder<'a> {
     Symbol(FormulaSymbolBuilder<'a>),
     Variable(FormulaVariableBuilder<'a>),
+    VariableApplication(FormulaVariableApplicationBuilder<'a>),
+    SymbolApplication(FormulaSymbolApplicationBuilder<'a>),
+    Binder(FormulaBinderBuilder<'a>),
 
     Prefix(FormulaPrefixBuilder<'a>),
     Infix(FormulaInfixBuilder<'a>),
+    Mixfix(FormulaMixfixBuilder<'a>),
+    Operators(FormulaOperatorsBuilder<'a>),
 
     ReadableApplication(FormulaReadableApplicationBuilder<'a>),
 }
 
 impl<'a> FormulaBuilder<'a> {
-    fn primary(pair: Pair<Rule>) -> Self {
+    fn primary(path: &Path, pair: Pair<Rule>) -> Self {
         match pair.as_rule() {
-            Rule::ident => FormulaBuilder::Symbol(FormulaSymbolBuilder::from_pest(pair)),
-            Rule::var => FormulaBuilder::Variable(FormulaVariableBuilder::from_pest(pair)),
-
-            Rule::primary_paren => FormulaBuilder::from_pest(pair.into_inner().next().unwrap()),
+            Rule::fqid | Rule::ident => {
+                FormulaBuilder::Symbol(FormulaSymbolBuilder::from_pest(path, pair))
+            }
+            Rule::var => FormulaBuilder::Variable(FormulaVariableBuilder::from_pest(path, pair)),
+            Rule::var_application => FormulaBuilder::VariableApplication(
+                FormulaVariableApplicationBuilder::from_pest(path, pair),
+            ),
+            Rule::symbol_application => FormulaBuilder::SymbolApplication(
+                FormulaSymbolApplicationBuilder::from_pest(path, pair),
+            ),
+            Rule::binder => FormulaBuilder::Binder(FormulaBinderBuilder::from_pest(path, pair)),
+
+            Rule::primary_paren => FormulaBuilder::Operators(FormulaOperatorsBuilder::from_pest(
+                path,
+                pair.into_inner().next().unwrap(),
+                true,
+            )),
 
             _ => unreachable!(),
         }
     }
 
-    fn prec_climb(pairs: &mut Pairs<Rule>, curr_prec: usize) -> Self {
-        let prefix_list = pairs.next().unwrap().into_inner();
-
-        let mut primary =
-            prefix_list
-                .rev()
-                .fold(Self::primary(pairs.next().unwrap()), |primary, prefix| {
-                    FormulaBuilder::Prefix(FormulaPrefixBuilder::from_pest(prefix, primary))
-                });
-
-        // Google "Precedence Climbing".
-        while let Some(pair) = pairs.peek() {
-            let infix = ReadOperator::from_pest(pair);
-            let precedence = infix.precedence();
-
-            if precedence < curr_prec {
-                break;
-            }
-            pairs.next();
+    pub fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        let formula = FormulaOperatorsBuilder::from_pest(path, pair, false);
 
-            let next_prec = if infix.is_left_associative() {
-                precedence + 1
-            } else {
-                precedence
-            };
+        match formula.items.as_slice() {
+            [FormulaItemBuilder::Primary(primary)] => primary.as_ref().clone(),
 
-            let rhs = Self::prec_climb(pairs, next_prec);
-            primary = FormulaBuilder::Infix(FormulaInfixBuilder::from_op(infix, primary, rhs));
+            _ => FormulaBuilder::Operators(formula),
         }
-
-        primary
     }
 
-    fn from_pest(pair: Pair<Rule>) -> Self {
-        assert_eq!(pair.as_rule(), Rule::formula);
+    // Relates two formulas by an infix operator, as though the operator had been written between
+    // them with each formula in parentheses.
+    pub fn infix(left: FormulaBuilder<'a>, operator: &str, right: FormulaBuilder<'a>) -> Self {
+        FormulaBuilder::Operators(FormulaOperatorsBuilder {
+            items: vec![
+                FormulaItemBuilder::Primary(Box::new(left)),
+                FormulaItemBuilder::Operator(operator.to_owned()),
+                FormulaItemBuilder::Primary(Box::new(right)),
+            ],
+            parenthesized: false,
+            file_location: None,
 
-        Self::prec_climb(&mut pair.into_inner(), 0)
+            resolved: OnceCell::new(),
+        })
     }
 
     fn build<F>(
@@ -2504,14 +4468,23 @@ impl<'a> FormulaBuilder<'a> {
         generate_error: F,
     ) -> bool
     where
-        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError) -> ParsingError<'a> + Copy,
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
     {
         match self {
-            Self::Symbol(_) => todo!(),
+            Self::Symbol(formula) => formula.build(self, local_index, errors, generate_error),
             Self::Variable(formula) => formula.build(self, local_index, errors, generate_error),
+            Self::VariableApplication(formula) => {
+                formula.build(self, local_index, errors, generate_error)
+            }
+            Self::SymbolApplication(formula) => {
+                formula.build(self, local_index, errors, generate_error)
+            }
+            Self::Binder(formula) => formula.build(self, local_index, errors, generate_error),
 
             Self::Prefix(formula) => formula.build(self, local_index, errors, generate_error),
             Self::Infix(formula) => formula.build(self, local_index, errors, generate_error),
+            Self::Mixfix(formula) => formula.build(self, local_index, errors, generate_error),
+            Self::Operators(formula) => formula.build(self, local_index, errors, generate_error),
 
             Self::ReadableApplication(_) => unreachable!(),
         }
@@ -2519,19 +4492,47 @@ impl<'a> FormulaBuilder<'a> {
 
     fn type_signature(&'a self) -> &TypeSignatureBuilder {
         match self {
-            Self::Symbol(_) => todo!(),
+            Self::Symbol(formula) => formula.type_signature(),
             Self::Variable(formula) => formula.type_signature(),
+            Self::VariableApplication(formula) => formula.type_signature(),
+            Self::SymbolApplication(formula) => formula.type_signature(),
+            Self::Binder(formula) => formula.type_signature(),
 
             Self::Prefix(formula) => formula.type_signature(),
             Self::Infix(formula) => formula.type_signature(),
+            Self::Mixfix(formula) => formula.type_signature(),
+            Self::Operators(formula) => formula.resolved().type_signature(),
 
             Self::ReadableApplication(formula) => todo!(),
         }
     }
 
+    // Formulas which weren't written out on their own, like those parsed from a sequence of
+    // operators, have no location.
+    pub fn file_location(&self) -> Option<&FileLocation> {
+        match self {
+            Self::Symbol(formula) => Some(&formula.file_location),
+            Self::Variable(formula) => Some(&formula.file_location),
+            Self::VariableApplication(formula) => Some(&formula.file_location),
+            Self::SymbolApplication(formula) => Some(&formula.file_location),
+            Self::Binder(formula) => Some(&formula.file_location),
+            Self::Operators(formula) => formula.file_location.as_ref(),
+
+            Self::Prefix(_) | Self::Infix(_) | Self::Mixfix(_) | Self::ReadableApplication(_) => {
+                None
+            }
+        }
+    }
+
     pub fn variable(&'a self) -> Option<&VariableBuilder> {
         match self {
-            Self::Variable(formula) => Some(formula.var_ref.get().unwrap()),
+            Self::Variable(formula) => match formula.abbreviation() {
+                Some(abbreviation) => abbreviation.variable(),
+
+                None if !formula.is_bound() => Some(formula.var_ref.get().unwrap()),
+                None => None,
+            },
+            Self::Operators(formula) => formula.resolved().variable(),
 
             _ => None,
         }
@@ -2545,9 +4546,15 @@ impl<'a> FormulaBuilder<'a> {
         &FormulaBuilder<'a>,
     )> {
         match self {
+            Self::Symbol(_) => None,
+            Self::Variable(formula) => formula.abbreviation()?.binary(),
+            Self::SymbolApplication(formula) => formula.binary(),
             Self::Infix(formula) => formula.binary(),
+            Self::Mixfix(formula) => formula.binary(),
+            Self::Operators(formula) => formula.resolved().binary(),
+            Self::ReadableApplication(formula) => formula.binary(),
 
-            _ => todo!(),
+            Self::VariableApplication(_) | Self::Binder(_) | Self::Prefix(_) => None,
         }
     }
 
@@ -2566,40 +4573,140 @@ impl<'a> FormulaBuilder<'a> {
         impl ExactSizeDoubleEndedIterator<Item = &FormulaBuilder<'a>>,
     )> {
         match self {
+            Self::Symbol(_) => None,
+            Self::Variable(formula) => formula.abbreviation()?.application(),
+            Self::SymbolApplication(formula) => formula.application(),
             Self::Prefix(formula) => formula.application(),
             Self::Infix(formula) => formula.application(),
+            Self::Mixfix(formula) => formula.application(),
+            Self::Operators(formula) => formula.resolved().application(),
+            Self::ReadableApplication(formula) => formula.application(),
 
-            _ => todo!(),
+            Self::VariableApplication(_) | Self::Binder(_) => None,
         }
     }
 
     pub fn finish<'b>(&self) -> FormulaBlock<'b> {
         match self {
-            Self::Symbol(_) => todo!(),
+            Self::Symbol(formula) => formula.finish(),
             Self::Variable(formula) => formula.finish(),
+            Self::VariableApplication(formula) => formula.finish(),
+            Self::SymbolApplication(formula) => formula.finish(),
+            Self::Binder(formula) => formula.finish(),
 
             Self::Prefix(formula) => formula.finish(),
             Self::Infix(formula) => formula.finish(),
+            Self::Mixfix(formula) => formula.finish(),
+            Self::Operators(formula) => formula.resolved().finish(),
 
             Self::ReadableApplication(formula) => formula.finish(),
         }
     }
+
+    // The formula as it was written, with operators and notations displayed the way the symbols
+    // and definitions which read them are.
+    fn push_math(&self, elements: &mut Vec<MathElement>) {
+        match self {
+            Self::Symbol(formula) => formula.push_math(elements),
+            Self::Variable(formula) => elements.push(formula.math()),
+            Self::VariableApplication(formula) => formula.push_math(elements),
+            Self::SymbolApplication(formula) => formula.push_math(elements),
+            Self::Binder(formula) => formula.push_math(elements),
+
+            Self::Prefix(formula) => formula.push_math(elements),
+            Self::Infix(formula) => formula.push_math(elements),
+            Self::Mixfix(formula) => formula.push_math(elements),
+            Self::Operators(formula) => formula.push_math(elements),
+
+            Self::ReadableApplication(formula) => formula.push_math(elements),
+        }
+    }
+
+    fn math(&self) -> Vec<MathElement> {
+        let mut elements = Vec::new();
+        self.push_math(&mut elements);
+
+        elements
+    }
+
+    pub fn render_plain(&self) -> String {
+        MathBlock::new(self.math()).render_plain()
+    }
+
+    // The inputs of an application, where an operator applied to an operator gets parentheses.
+    // Formulas which were written with operators are already fenced.
+    fn math_operands(display: &Display, inputs: &[FormulaBuilder<'a>]) -> Vec<Vec<MathElement>> {
+        let fence_operators = display.is_operator(inputs.len());
+
+        inputs
+            .iter()
+            .map(|input| {
+                let is_operator = match input {
+                    Self::SymbolApplication(formula) => formula.is_operator(),
+                    Self::ReadableApplication(formula) => formula.is_operator(),
+
+                    _ => false,
+                };
+
+                if fence_operators && is_operator {
+                    vec![MathElement::Fenced(MathBlock::new(input.math()))]
+                } else {
+                    input.math()
+                }
+            })
+            .collect()
+    }
+
+    fn math_inputs(inputs: &[FormulaBuilder]) -> MathElement {
+        let mut elements = Vec::new();
+        for (i, input) in inputs.iter().enumerate() {
+            if i > 0 {
+                elements.push(MathElement::Separator);
+            }
+
+            input.push_math(&mut elements);
+        }
+
+        MathElement::Fenced(MathBlock::new(elements))
+    }
 }
 
 impl<'a> PartialEq for FormulaBuilder<'a> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Self::Operators(self_ops), _) => self_ops.resolved() == other,
+            (_, Self::Operators(other_ops)) => self == other_ops.resolved(),
+
+            // An abbreviation is equal to the formula it abbreviates.
+            (Self::Variable(self_variable), _) if self_variable.abbreviation().is_some() => {
+                self_variable.abbreviation().unwrap() == other
+            }
+            (_, Self::Variable(other_variable)) if other_variable.abbreviation().is_some() => {
+                self == other_variable.abbreviation().unwrap()
+            }
+
             (Self::Symbol(self_symbol), Self::Symbol(other_symbol)) => self_symbol == other_symbol,
             (Self::Variable(self_variable), Self::Variable(other_variable)) => {
                 self_variable == other_variable
             }
+            (Self::VariableApplication(self_app), Self::VariableApplication(other_app)) => {
+                self_app == other_app
+            }
+            (Self::SymbolApplication(self_app), Self::SymbolApplication(other_app)) => {
+                self_app == other_app
+            }
+            (Self::Binder(self_binder), Self::Binder(other_binder)) => self_binder == other_binder,
 
             (Self::Prefix(self_prefix), Self::Prefix(other_prefix)) => self_prefix == other_prefix,
             (Self::Infix(self_infix), Self::Infix(other_infix)) => self_infix == other_infix,
+            (Self::Mixfix(self_mixfix), Self::Mixfix(other_mixfix)) => self_mixfix == other_mixfix,
 
             (Self::ReadableApplication(self_app), _) => self_app.test(other),
             (_, Self::ReadableApplication(other_app)) => other_app.test(self),
 
+            (Self::SymbolApplication(self_app), _) => self_app.test(other),
+            (_, Self::SymbolApplication(other_app)) => other_app.test(self),
+
             _ => false,
         }
     }
@@ -2608,20 +4715,18 @@ impl<'a> Eq for FormulaBuilder<'a> {}
 
 #[derive(Debug)]
 pub struct DisplayFormulaBuilder<'a> {
-    display: MathBuilder,
     formula: FormulaBuilder<'a>,
 }
 
 impl<'a> DisplayFormulaBuilder<'a> {
-    pub fn from_pest(pair: Pair<Rule>) -> Self {
-        let display = MathBuilder::from_pest_formula(pair.clone());
-        let formula = FormulaBuilder::from_pest(pair);
-
-        DisplayFormulaBuilder { display, formula }
+    pub fn new(formula: FormulaBuilder<'a>) -> Self {
+        DisplayFormulaBuilder { formula }
     }
 
-    pub fn verify_structure(&'a self, errors: &mut ParsingErrorContext<'a>) {
-        self.display.verify_structure(errors, |_| unreachable!());
+    pub fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        let formula = FormulaBuilder::from_pest(path, pair);
+
+        DisplayFormulaBuilder { formula }
     }
 
     pub fn build<F>(
@@ -2631,13 +4736,13 @@ impl<'a> DisplayFormulaBuilder<'a> {
         generate_error: F,
     ) -> bool
     where
-        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError) -> ParsingError<'a> + Copy,
+        F: Fn(&'a FormulaBuilder<'a>, FormulaParsingError<'a>) -> ParsingError<'a> + Copy,
     {
         self.formula.build(local_index, errors, generate_error)
     }
 
     pub fn finish<'b>(&self) -> DisplayFormulaBlock<'b> {
-        let display = self.display.finish();
+        let display = self.display();
         let formula = self.formula.finish();
 
         DisplayFormulaBlock::new(display, formula)
@@ -2647,8 +4752,10 @@ impl<'a> DisplayFormulaBuilder<'a> {
         &self.formula
     }
 
-    pub fn display(&self) -> &MathBuilder {
-        &self.display
+    // Only available once the formula has been built, since operators are displayed by the
+    // symbols and definitions which read them.
+    pub fn display(&self) -> MathBlock {
+        MathBlock::new(self.formula.math())
     }
 
     pub fn type_signature(&'a self) -> &TypeSignatureBuilder {
@@ -2680,3 +4787,566 @@ impl<'a> DisplayFormulaBuilder<'a> {
         self.formula.application()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{check_page, page_diagnostics};
+
+    const QUANTIFIERS: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol implies : sys {
+  name = "Implies" tagline = {Implies.}
+  type = Prop -> Prop -> Prop
+  read = Infix ->
+}
+
+\Symbol forall : sys {
+  name = "Forall" tagline = {Forall.}
+  type = (Nat -> Prop) -> Prop
+  read = Prefix !
+}
+
+\Axiom inst : sys {
+  name = "Inst" tagline = {Inst.}
+  var P : Nat -> Prop
+  var a : Nat
+  premise = [ \forall x : Nat . 'P('x); ]
+  assertion = 'P('a);
+}
+"#;
+
+    // A theorem instantiating its universal hypothesis at 'b, whose proof restates the hypothesis
+    // as `written` first.
+    fn instantiate(written: &str, assertion: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\Theorem t : sys {{
+  name = "T" tagline = {{T.}}
+  var Q : Nat -> Prop
+  var b : Nat
+  premise = [ \forall x : Nat . 'Q('x) -> 'Q('x); ]
+  assertion = {assertion};
+}}
+
+\Proof t : sys {{
+  | 1 | {written};
+  | inst | {assertion};
+}}
+"#,
+            QUANTIFIERS,
+            written = written,
+            assertion = assertion,
+        ))
+    }
+
+    #[test]
+    fn binders() {
+        assert!(instantiate(r"\forall x : Nat . 'Q('x) -> 'Q('x)", "'Q('b) -> 'Q('b)").is_empty());
+
+        // Binders are compared up to renaming.
+        assert!(instantiate(r"\forall y : Nat . 'Q('y) -> 'Q('y)", "'Q('b) -> 'Q('b)").is_empty());
+        assert_eq!(
+            instantiate(r"\forall y : Nat . 'Q('y) -> 'Q('b)", "'Q('b) -> 'Q('b)"),
+            ["step-hypothesis-mismatch"]
+        );
+
+        // A binder's variable is only visible in its body, and only a symbol taking an
+        // abstraction can bind.
+        assert_eq!(
+            instantiate(r"(\forall y : Nat . 'Q('y)) -> 'Q('y)", "'Q('b) -> 'Q('b)"),
+            ["formula-variable-not-found"]
+        );
+        assert_eq!(
+            instantiate(r"\implies y : Nat . 'Q('y) -> 'Q('y)", "'Q('b) -> 'Q('b)"),
+            ["formula-type-mismatch"]
+        );
+    }
+
+    const FUNCTIONS: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol implies : sys {
+  name = "Implies" tagline = {Implies.}
+  type = Prop -> Prop -> Prop
+  read = Infix ->
+}
+
+\Symbol zero : sys {
+  name = "Zero" tagline = {Zero.}
+  type = Nat
+}
+
+\Symbol max : sys {
+  name = "Max" tagline = {Max.}
+  type = Nat -> Nat -> Nat
+}
+
+\Symbol between : sys {
+  name = "Between" tagline = {Between.}
+  type = Nat -> Nat -> Nat -> Prop
+}
+
+\Definition ordered : sys {
+  name = "Ordered" tagline = {Ordered.}
+  inputs = [ x : Nat, y : Nat ]
+  expanded = between('x, max('x, 'y), 'y);
+}
+"#;
+
+    // An axiom asserting `formula`, which mentions the variables 'a and 'b.
+    fn call(formula: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\Axiom t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Nat
+  var b : Nat
+  assertion = {};
+}}
+"#,
+            FUNCTIONS, formula
+        ))
+    }
+
+    #[test]
+    fn function_calls() {
+        assert!(call("between('a, max('a, zero), 'b)").is_empty());
+        assert!(call("ordered('a, 'b) -> between(zero, 'a, 'b)").is_empty());
+
+        // A symbol applied to too few inputs has the wrong type where it's used.
+        assert_eq!(call("between('a, max('a), 'b)"), ["formula-type-mismatch"]);
+        assert_eq!(call("between('a, 'b, 'a, 'b)"), ["formula-too-many-inputs"]);
+
+        // A definition has to be applied to all of its inputs, so that it can be expanded.
+        assert_eq!(
+            call("ordered('a) -> between(zero, 'a, 'b)"),
+            ["formula-definition-partial-application"]
+        );
+
+        assert_eq!(
+            call("min('a, 'b) -> between(zero, 'a, 'b)"),
+            ["formula-symbol-not-found"]
+        );
+    }
+
+    const RELATIONS: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol not : sys {
+  name = "Not" tagline = {Not.}
+  type = Prop -> Prop
+  read = Prefix !
+}
+
+\Symbol and : sys {
+  name = "And" tagline = {And.}
+  type = Prop -> Prop -> Prop
+  read = Infix &
+}
+
+\Symbol or : sys {
+  name = "Or" tagline = {Or.}
+  type = Prop -> Prop -> Prop
+  read = Infix |
+}
+
+\Symbol eq : sys {
+  name = "Eq" tagline = {Eq.}
+  type = Nat -> Nat -> Prop
+  read = Infix =
+}
+
+\Symbol lt : sys {
+  name = "Lt" tagline = {Lt.}
+  type = Nat -> Nat -> Prop
+  read = Infix <
+}
+
+\Symbol plus : sys {
+  name = "Plus" tagline = {Plus.}
+  type = Nat -> Nat -> Nat
+  read = Infix +
+}
+
+\Symbol times : sys {
+  name = "Times" tagline = {Times.}
+  type = Nat -> Nat -> Nat
+  read = Infix *
+}
+"#;
+
+    // A theorem whose proof restates its hypothesis, written as `written` in the proof and as
+    // `parsed` in the theorem.
+    fn relate(written: &str, parsed: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\Theorem t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ {parsed}; ]
+  assertion = {parsed};
+}}
+
+\Proof t : sys {{
+  | 1 | {written};
+}}
+"#,
+            RELATIONS,
+            parsed = parsed,
+            written = written,
+        ))
+    }
+
+    #[test]
+    fn relations() {
+        assert!(relate("'a + 'b = 'c", "('a + 'b) = 'c").is_empty());
+        assert!(relate("'a < 'b & 'b = 'c", "('a < 'b) & ('b = 'c)").is_empty());
+
+        // Negation takes in a relation, but not a connective.
+        assert!(relate("!'a = 'b & 'b < 'c", "(!('a = 'b)) & ('b < 'c)").is_empty());
+        assert!(!relate("!'a = 'b & 'b < 'c", "!(('a = 'b) & ('b < 'c))").is_empty());
+        assert!(relate("! !'a < 'b", "!(!('a < 'b))").is_empty());
+    }
+
+    #[test]
+    fn default_precedence() {
+        // Multiplication binds more tightly than addition.
+        assert!(relate("'a + 'b * 'c = 'a", "'a + ('b * 'c) = 'a").is_empty());
+        assert!(!relate("'a + 'b * 'c = 'a", "('a + 'b) * 'c = 'a").is_empty());
+        assert!(relate("'a * 'b + 'c = 'a", "('a * 'b) + 'c = 'a").is_empty());
+
+        // Conjunction binds more tightly than disjunction.
+        assert!(relate(
+            "'a = 'b | 'b = 'c & 'a < 'c",
+            "('a = 'b) | (('b = 'c) & ('a < 'c))"
+        )
+        .is_empty());
+        assert!(!relate(
+            "'a = 'b | 'b = 'c & 'a < 'c",
+            "(('a = 'b) | ('b = 'c)) & ('a < 'c)"
+        )
+        .is_empty());
+        assert!(relate(
+            "'a = 'b & 'b = 'c | 'a < 'c",
+            "(('a = 'b) & ('b = 'c)) | ('a < 'c)"
+        )
+        .is_empty());
+    }
+
+    const ARITHMETIC: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol eq : sys {
+  name = "Eq" tagline = {Eq.}
+  type = Nat -> Nat -> Prop
+  read = Infix = 30 none
+}
+
+\Symbol plus : sys {
+  name = "Plus" tagline = {Plus.}
+  type = Nat -> Nat -> Nat
+  read = Infix + 50 left
+}
+
+\Symbol times : sys {
+  name = "Times" tagline = {Times.}
+  type = Nat -> Nat -> Nat
+  read = Infix * 60 left
+}
+
+\Symbol power : sys {
+  name = "Power" tagline = {Power.}
+  type = Nat -> Nat -> Nat
+  read = Infix ^ 80 right
+}
+
+\Symbol neg : sys {
+  name = "Neg" tagline = {Neg.}
+  type = Nat -> Nat
+  read = Prefix - 70
+}
+
+\Symbol abs : sys {
+  name = "Abs" tagline = {Abs.}
+  type = Nat -> Nat
+  read = Mixfix "|" _ "|"
+}
+
+\Symbol pair : sys {
+  name = "Pair" tagline = {Pair.}
+  type = Nat -> Nat -> Nat
+  read = Mixfix "[" _ "," _ "]"
+}
+"#;
+
+    // A theorem whose proof only restates its hypothesis, written as `written` in the proof and
+    // as `parsed` in the theorem, so it checks exactly when both are read the same way.
+    fn restate(written: &str, parsed: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\Theorem restate : sys {{
+  name = "R" tagline = {{R.}}
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ {parsed}; ]
+  assertion = {parsed};
+}}
+
+\Proof restate : sys {{
+  | 1 | {written};
+}}
+"#,
+            ARITHMETIC,
+            parsed = parsed,
+            written = written,
+        ))
+    }
+
+    #[test]
+    fn precedence() {
+        assert!(restate("'a + 'b * 'c = 'd", "'a + ('b * 'c) = 'd").is_empty());
+        assert!(!restate("'a + 'b * 'c = 'd", "('a + 'b) * 'c = 'd").is_empty());
+
+        // A prefix operator takes in the operators which bind more tightly than it does.
+        assert!(restate("-'a ^ 'b * 'c = 'd", "(-('a ^ 'b)) * 'c = 'd").is_empty());
+    }
+
+    #[test]
+    fn associativity() {
+        assert!(restate("'a + 'b + 'c = 'd", "('a + 'b) + 'c = 'd").is_empty());
+        assert!(!restate("'a + 'b + 'c = 'd", "'a + ('b + 'c) = 'd").is_empty());
+
+        assert!(restate("'a ^ 'b ^ 'c = 'd", "'a ^ ('b ^ 'c) = 'd").is_empty());
+
+        assert_eq!(
+            restate("'a = 'b = 'c", "'a = 'b"),
+            ["formula-operator-not-associative"]
+        );
+    }
+
+    #[test]
+    fn mixfix() {
+        assert!(restate("|'a + 'b| * 'c = 'd", "(|('a + 'b)|) * 'c = 'd").is_empty());
+        assert!(restate("['a + 'b, |'c|] = 'd", "pair('a + 'b, abs('c)) = 'd").is_empty());
+
+        assert_eq!(
+            restate("|'a + 'b = 'd", "'a = 'd"),
+            ["formula-notation-unclosed"]
+        );
+
+        // Brackets are matched by the grammar, before any notation is looked up.
+        assert_eq!(restate("['a, 'b = 'd", "'a = 'd"), ["syntax"]);
+    }
+
+    const DISPLAYS: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol eq : sys {
+  name = "Eq" tagline = {Eq.}
+  type = Nat -> Nat -> Prop
+  read = Infix =
+}
+
+\Symbol times : sys {
+  name = "Times" tagline = {Times.}
+  type = Nat -> Nat -> Nat
+  read = Infix *
+  display = Infix sigma
+}
+
+\Symbol neg : sys {
+  name = "Neg" tagline = {Neg.}
+  type = Nat -> Nat
+  read = Prefix -
+  display = Suffix !
+}
+
+\Symbol pair : sys {
+  name = "Pair" tagline = {Pair.}
+  type = Nat -> Nat -> Nat
+  display = Standard pi
+}
+
+\Symbol succ : sys {
+  name = "Succ" tagline = {Succ.}
+  type = Nat -> Nat
+}
+
+\Axiom refl : sys {
+  name = "Refl" tagline = {Refl.}
+  var x : Nat
+  assertion = 'x = 'x;
+}
+"#;
+
+    // The message of the only error in a proof which cites `refl` for `'a = {formula}`.
+    fn render(formula: &str) -> String {
+        let diagnostics = page_diagnostics(&format!(
+            r#"{}
+\Theorem t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Nat
+  var b : Nat
+  assertion = 'a = {formula};
+}}
+
+\Proof t : sys {{
+  | refl | 'a = {formula};
+}}
+"#,
+            DISPLAYS,
+            formula = formula,
+        ));
+
+        assert_eq!(diagnostics.len(), 1);
+        diagnostics[0].message.clone()
+    }
+
+    #[test]
+    fn displays() {
+        assert!(render("'a * 'b").ends_with("both `a` and `a σ b`."));
+        assert!(render("-'b").ends_with("both `a` and `b¬`."));
+        assert!(render("-('a * 'b)").ends_with("both `a` and `(a σ b)¬`."));
+        assert!(render("pair('a, 'b)").ends_with("both `a` and `π(a, b)`."));
+
+        // Symbols which are neither read nor displayed are written out by name.
+        assert!(render("succ('b)").ends_with("both `a` and `succ(b)`."));
+    }
+
+    // The message of the only error in an axiom asserting `formula`.
+    fn mistype(formula: &str) -> String {
+        let diagnostics = page_diagnostics(&format!(
+            r#"{}
+\Axiom t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Nat
+  var b : Nat
+  assertion = {};
+}}
+"#,
+            FUNCTIONS, formula
+        ));
+
+        assert_eq!(diagnostics.len(), 1);
+        diagnostics[0].message.clone()
+    }
+
+    #[test]
+    fn type_errors() {
+        assert_eq!(
+            mistype("between('a, max('a), 'b)"),
+            "A formula expects something of type `Nat`, but `max('a)` has type `Nat -> Nat`."
+        );
+        assert_eq!(
+            mistype("between('a, zero, 'b) -> 'a"),
+            "A formula expects something of type `Prop`, but `'a` has type `Nat`."
+        );
+        assert_eq!(
+            mistype("ordered(zero, ordered('a, 'b))"),
+            "A formula expects something of type `Nat`, but `ordered('a, 'b)` has type `Prop`."
+        );
+    }
+
+    #[test]
+    fn display_arity() {
+        let diagnostics = page_diagnostics(&format!(
+            r#"{}
+\Symbol bad : sys {{
+  name = "Bad" tagline = {{Bad.}}
+  type = Nat -> Nat
+  display = Infix sigma
+}}
+"#,
+            DISPLAYS
+        ));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "symbol-display-arity");
+        assert_eq!(
+            diagnostics[0].message,
+            "The symbol `bad` takes 1 input, but it's written with the infix operator `sigma`, \
+             which lays out 2."
+        );
+    }
+
+    const COMPOSE: &str = r#"
+\Symbol compose : sys {
+  name = "Compose" tagline = {Compose.}
+  type = Nat -> Nat -> Nat
+  read = Infix ∘ 55 left
+}
+"#;
+
+    #[test]
+    fn unicode_operators() {
+        let page = |assertion: &str| {
+            format!(
+                r#"{}{}
+\Axiom comm : sys {{
+  name = "Comm" tagline = {{Comm.}}
+  var a : Nat
+  var b : Nat
+  assertion = {};
+}}
+"#,
+                ARITHMETIC, COMPOSE, assertion
+            )
+        };
+
+        assert!(check_page(&page("'a ∘ 'b = 'b ∘ 'a")).is_empty());
+
+        // The preview of the error's location doesn't split the operator's character.
+        let diagnostics = page_diagnostics(&page("'a ∘ 'b = 'b ∘ 'c"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "formula-variable-not-found");
+
+        // A declared operator takes its place among the others.
+        let restate = |written: &str, parsed: &str| {
+            check_page(&format!(
+                r#"{}{}
+\Theorem restate : sys {{
+  name = "R" tagline = {{R.}}
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ {parsed}; ]
+  assertion = {parsed};
+}}
+
+\Proof restate : sys {{
+  | 1 | {written};
+}}
+"#,
+                ARITHMETIC,
+                COMPOSE,
+                parsed = parsed,
+                written = written,
+            ))
+        };
+
+        assert!(restate("'a + 'b ∘ 'c * 'd = 'a", "'a + ('b ∘ ('c * 'd)) = 'a").is_empty());
+        assert!(restate("'a ∘ 'b ∘ 'c = 'd", "('a ∘ 'b) ∘ 'c = 'd").is_empty());
+        assert!(!restate("'a ∘ 'b ∘ 'c = 'd", "'a ∘ ('b ∘ 'c) = 'd").is_empty());
+    }
+}
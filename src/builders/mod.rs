@@ -18,6 +18,8 @@ use std::path::{Path, PathBuf};
 
 use pest::Parser;
 
+use crate::FileLocation;
+
 use crate::document::bibliography::Bibliography;
 use crate::document::structure::BlockLocation;
 use crate::document::Document;
@@ -42,7 +44,7 @@ mod hidden {
 use hidden::{DocumentParser, Rule};
 
 use bibliography::BibliographyBuilder;
-use errors::ParsingErrorContext;
+use errors::{ParsingError, ParsingErrorContext};
 use index::BuilderIndex;
 use structure::BookBuilder;
 
@@ -61,17 +63,23 @@ impl<'a> ManifestBuilder<'a> {
         let manifest_path: PathBuf = [&self.library_path, Path::new("manifest.math")]
             .iter()
             .collect();
-        let contents = match std::fs::read_to_string(manifest_path) {
+        let contents = match std::fs::read_to_string(&manifest_path) {
             Ok(contents) => contents,
             Err(e) => {
-                errors.err(e);
+                let file_location = FileLocation::file(&manifest_path);
+                errors.err(ParsingError::IoError(
+                    manifest_path.clone(),
+                    file_location,
+                    e,
+                ));
                 return Vec::new();
             }
         };
         let manifest_pair = match DocumentParser::parse(Rule::manifest, &contents) {
             Ok(mut parsed) => parsed.next().unwrap(),
             Err(e) => {
-                errors.err(e);
+                let file_location = FileLocation::from_pest_error(&manifest_path, &contents, &e);
+                errors.err(ParsingError::PestError(file_location, Box::new(e)));
                 return Vec::new();
             }
         };
@@ -184,3 +192,123 @@ impl<'a> ManifestBuilder<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::ManifestBuilder;
+    use crate::Diagnostic;
+
+    const MANIFEST: &str =
+        "b : \"Book\" { A book. [ c : \"Chapter\" { A chapter. [ p : \"Page\", ] } ] }";
+
+    // Writes a library with the given manifest whose only page file is `b/c/p.math`, then builds
+    // and checks it. Returns the diagnostics of the errors found, which are empty if the library
+    // is fine.
+    fn library_diagnostics(manifest: &str, page: &str) -> Vec<Diagnostic> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let library_path: PathBuf = std::env::temp_dir().join(format!(
+            "ckproof-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let page_dir = library_path.join("b").join("c");
+        std::fs::create_dir_all(&page_dir).unwrap();
+        std::fs::write(library_path.join("manifest.math"), manifest).unwrap();
+        std::fs::write(page_dir.join("p.math"), page).unwrap();
+
+        let builder = ManifestBuilder::from_lib(&library_path);
+        let diagnostics = match builder.build() {
+            Ok(document) => {
+                document.crosslink();
+
+                match document.check() {
+                    Ok(()) => Vec::new(),
+                    Err(errors) => errors.diagnostics(),
+                }
+            }
+
+            Err(errors) => errors.diagnostics(),
+        };
+
+        std::fs::remove_dir_all(&library_path).unwrap();
+        diagnostics
+    }
+
+    // The diagnostics of the errors found in a library whose only page is `page`.
+    pub(super) fn page_diagnostics(page: &str) -> Vec<Diagnostic> {
+        library_diagnostics(MANIFEST, page)
+    }
+
+    // The codes of the errors found in a library whose only page is `page`.
+    pub(super) fn check_page(page: &str) -> Vec<&'static str> {
+        page_diagnostics(page).iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn json_diagnostics() {
+        let diagnostics = page_diagnostics(
+            r#"\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Nat : sys { name = "Nat" tagline = {Nats again.} }
+"#,
+        );
+        assert_eq!(diagnostics.len(), 1);
+
+        // This is the line `--message-format=json` prints for the diagnostic.
+        let line = serde_json::to_string(&diagnostics[0]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["code"], "system-child-id-taken");
+        assert_eq!(
+            json["message"],
+            "The id `Nat` is used by more than one child of the system `sys`."
+        );
+
+        let path = json["location"]["path"].as_str().unwrap();
+        assert!(path.ends_with("p.math"));
+        assert_eq!(json["location"]["start_line"], 4);
+        assert_eq!(json["location"]["start_column"], 7);
+        assert!(json["location"].get("preview").is_none());
+
+        assert_eq!(json["related"].as_array().unwrap().len(), 1);
+        assert_eq!(json["related"][0]["start_line"], 3);
+    }
+
+    #[test]
+    fn file_locations() {
+        // A page the manifest names but which doesn't exist points at its manifest entry.
+        let diagnostics = library_diagnostics(
+            "b : \"Book\" { A book. [ c : \"Chapter\" { A chapter. [ q : \"Missing\", ] } ] }",
+            "",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "io");
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert!(location.path.ends_with("manifest.math"));
+        assert_eq!((location.start_line, location.start_column), (1, 53));
+
+        // A malformed url points at its hyperlink.
+        let diagnostics = page_diagnostics("See <a nowhere> here </a>.\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "url");
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert!(location.path.ends_with("p.math"));
+        assert_eq!((location.start_line, location.start_column), (1, 5));
+
+        // Errors in a tagline point into the manifest.
+        let diagnostics = library_diagnostics(
+            "b : \"Book\" { A <em> book. [ c : \"Chapter\" { A chapter. [ p : \"Page\", ] } ] }",
+            "",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "unclosed-em");
+        let location = diagnostics[0].location.as_ref().unwrap();
+        assert!(location.path.ends_with("manifest.math"));
+    }
+}
@@ -20,6 +20,8 @@ use std::path::{Path, PathBuf};
 use pest::iterators::Pair;
 use pest::Parser;
 
+use crate::FileLocation;
+
 use crate::document::structure::{Block, BlockLocation, Book, Chapter, Page};
 
 use super::bibliography::{BibliographyBuilderEntry, LocalBibliographyBuilder};
@@ -71,9 +73,9 @@ impl<'a> BlockBuilder<'a> {
             Rule::ol_block => Self::List(ListBuilder::from_pest(path, pair, true, location)),
 
             Rule::table_block => Self::Table(TableBuilder::from_pest(path, pair, location)),
-            Rule::quote_block => Self::Quote(QuoteBuilder::from_pest(pair, location)),
+            Rule::quote_block => Self::Quote(QuoteBuilder::from_pest(path, pair, location)),
             Rule::todo_block => Self::Todo(TodoBuilder::from_pest(path, pair)),
-            Rule::heading_block => Self::Heading(HeadingBuilder::from_pest(pair)),
+            Rule::heading_block => Self::Heading(HeadingBuilder::from_pest(path, pair)),
             Rule::text_block => Self::Text(TextBlockBuilder::from_pest(path, pair, location)),
 
             _ => unreachable!(),
@@ -260,7 +262,10 @@ impl<'a> PageBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::manifest_page);
         let mut inner = pair.into_inner();
 
-        let id = inner.next().unwrap().as_str().to_owned();
+        let manifest_path: PathBuf = [library_path, Path::new("manifest.math")].iter().collect();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(&manifest_path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
 
         let string = inner.next().unwrap();
         let string_contents = string.into_inner().next().unwrap();
@@ -278,7 +283,7 @@ impl<'a> PageBuilder<'a> {
         let contents = match std::fs::read_to_string(&page_path) {
             Ok(contents) => contents,
             Err(e) => {
-                errors.err(e);
+                errors.err(ParsingError::IoError(page_path.clone(), file_location, e));
                 return PageBuilder {
                     id,
                     name,
@@ -293,7 +298,8 @@ impl<'a> PageBuilder<'a> {
         let pair = match DocumentParser::parse(Rule::document, &contents) {
             Ok(mut parsed) => parsed.next().unwrap(),
             Err(e) => {
-                errors.err(e);
+                let file_location = FileLocation::from_pest_error(&page_path, &contents, &e);
+                errors.err(ParsingError::PestError(file_location, Box::new(e)));
                 return PageBuilder {
                     id,
                     name,
@@ -388,6 +394,8 @@ impl<'a> PageBuilder<'a> {
 }
 
 pub struct ChapterBuilder<'a> {
+    file_location: FileLocation,
+
     id: String,
     name: String,
     tagline: ParagraphBuilder<'a>,
@@ -408,13 +416,16 @@ impl<'a> ChapterBuilder<'a> {
     ) -> Self {
         assert_eq!(pair.as_rule(), Rule::manifest_chapter);
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+
+        let path: PathBuf = [library_path, Path::new("manifest.math")].iter().collect();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(&path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
 
         let string = inner.next().unwrap();
         let string_contents = string.into_inner().next().unwrap();
         let name = string_contents.as_str().to_owned();
 
-        let path: PathBuf = [library_path, Path::new("manifest.math")].iter().collect();
         let tagline = ParagraphBuilder::from_pest(&path, inner.next().unwrap());
 
         let pages = inner
@@ -424,6 +435,8 @@ impl<'a> ChapterBuilder<'a> {
         location.next_chapter();
 
         ChapterBuilder {
+            file_location,
+
             id,
             name,
             tagline,
@@ -442,7 +455,7 @@ impl<'a> ChapterBuilder<'a> {
 
     fn verify_structure(&'a self, index: &BuilderIndex<'a>, errors: &mut ParsingErrorContext<'a>) {
         self.tagline.verify_structure(index, errors, |e| {
-            ParsingError::ChapterError(self, ChapterParsingError::TaglineError(e))
+            ParsingError::ChapterError(self, ChapterParsingError::TaglineError(&self.tagline, e))
         });
 
         for page in &self.pages {
@@ -483,6 +496,10 @@ impl<'a> ChapterBuilder<'a> {
         }
     }
 
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     fn finish<'b>(&self) -> Chapter<'b> {
         let id = self.id.clone();
         let name = self.name.clone();
@@ -503,6 +520,8 @@ impl<'a> std::fmt::Debug for ChapterBuilder<'a> {
 }
 
 pub struct BookBuilder<'a> {
+    file_location: FileLocation,
+
     id: String,
     name: String,
     tagline: ParagraphBuilder<'a>,
@@ -522,13 +541,16 @@ impl<'a> BookBuilder<'a> {
     ) -> Self {
         assert_eq!(pair.as_rule(), Rule::manifest_book);
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+
+        let path: PathBuf = [library_path, Path::new("manifest.math")].iter().collect();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(&path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
 
         let string = inner.next().unwrap();
         let string_contents = string.into_inner().next().unwrap();
         let name = string_contents.as_str().to_owned();
 
-        let path: PathBuf = [library_path, Path::new("manifest.math")].iter().collect();
         let tagline = ParagraphBuilder::from_pest(&path, inner.next().unwrap());
 
         let chapters = inner
@@ -538,6 +560,8 @@ impl<'a> BookBuilder<'a> {
         location.next_book();
 
         BookBuilder {
+            file_location,
+
             id,
             name,
             tagline,
@@ -564,7 +588,7 @@ impl<'a> BookBuilder<'a> {
         errors: &mut ParsingErrorContext<'a>,
     ) {
         self.tagline.verify_structure(index, errors, |e| {
-            ParsingError::BookError(self, BookParsingError::TaglineError(e))
+            ParsingError::BookError(self, BookParsingError::TaglineError(&self.tagline, e))
         });
 
         for chapter in &self.chapters {
@@ -611,6 +635,10 @@ impl<'a> BookBuilder<'a> {
         }
     }
 
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     pub fn finish<'b>(&self) -> Book<'b> {
         let id = self.id.clone();
         let name = self.name.clone();
@@ -15,7 +15,7 @@
 
 use std::cell::{Cell, RefCell};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::lazy::OnceCell;
 use std::path::Path;
 
@@ -27,20 +27,21 @@ use crate::document::structure::{
     AxiomBlockRef, BlockLocation, BlockRef, DeductableBlockRef, SystemBlockRef, TheoremBlockRef,
 };
 use crate::document::system::{
-    AxiomBlock, ProofBlock, ProofBlockElement, ProofBlockSmallJustification, ProofBlockSmallStep,
-    ProofBlockStep, SystemBlock, TheoremBlock, TheoremKind,
+    AxiomBlock, ProofBlock, ProofBlockElement, ProofBlockPremise, ProofBlockSmallJustification,
+    ProofBlockSmallStep, ProofBlockStep, SystemBlock, TheoremBlock, TheoremKind,
 };
 
 use super::bibliography::BibliographyBuilderEntry;
 use super::errors::{
-    AxiomParsingError, FlagListParsingError, ParsingError, ParsingErrorContext, ProofParsingError,
-    ProofStepParsingError, SystemParsingError, TheoremParsingError,
+    AxiomParsingError, DistinctParsingError, FlagListParsingError, ParsingError,
+    ParsingErrorContext, ProofParsingError, ProofStepParsingError, SystemParsingError,
+    TheoremParsingError,
 };
 use super::index::{BuilderIndex, LocalBuilderIndex};
-use super::justification::ProofJustificationBuilder;
+use super::justification::{CalculationJustificationBuilder, ProofJustificationBuilder};
 use super::language::{
-    DefinitionBuilder, DisplayFormulaBuilder, FormulaBuilder, SymbolBuilder, TypeBuilder,
-    VariableBuilder,
+    DefinitionBuilder, DisplayFormulaBuilder, FormulaBuilder, FunctionBuilder,
+    FunctionInputBuilder, ReadableBuilder, SymbolBuilder, TypeBuilder, VariableBuilder,
 };
 use super::text::{ParagraphBuilder, TextBuilder};
 use super::Rule;
@@ -49,6 +50,7 @@ struct SystemBuilderEntries<'a> {
     names: Vec<String>,
     taglines: Vec<ParagraphBuilder<'a>>,
     descriptions: Vec<Vec<TextBuilder<'a>>>,
+    extends: Vec<Vec<String>>,
 
     verified: Cell<bool>,
 }
@@ -58,6 +60,7 @@ impl<'a> SystemBuilderEntries<'a> {
         let mut names = Vec::with_capacity(1);
         let mut taglines = Vec::with_capacity(1);
         let mut descriptions = Vec::with_capacity(1);
+        let mut extends = Vec::with_capacity(1);
 
         for pair in pairs {
             match pair.as_rule() {
@@ -68,6 +71,14 @@ impl<'a> SystemBuilderEntries<'a> {
 
                     names.push(name);
                 }
+                Rule::block_extends => {
+                    let parents = pair
+                        .into_inner()
+                        .map(|parent| parent.as_str().to_owned())
+                        .collect();
+
+                    extends.push(parents);
+                }
                 Rule::block_tagline => {
                     let tagline =
                         ParagraphBuilder::from_pest(path, pair.into_inner().next().unwrap());
@@ -91,6 +102,7 @@ impl<'a> SystemBuilderEntries<'a> {
             names,
             taglines,
             descriptions,
+            extends,
 
             verified: Cell::new(false),
         }
@@ -138,7 +150,7 @@ impl<'a> SystemBuilderEntries<'a> {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
                     ParsingError::SystemError(
                         system_ref,
-                        SystemParsingError::TaglineParsingError(e),
+                        SystemParsingError::TaglineParsingError(&self.taglines[0], e),
                     )
                 });
                 if !success {
@@ -181,6 +193,14 @@ impl<'a> SystemBuilderEntries<'a> {
             }
         }
 
+        if self.extends.len() > 1 {
+            found_error = true;
+            errors.err(ParsingError::SystemError(
+                system_ref,
+                SystemParsingError::DuplicateExtends,
+            ));
+        }
+
         self.verified.set(!found_error);
     }
 
@@ -216,13 +236,24 @@ impl<'a> SystemBuilderEntries<'a> {
             &self.descriptions[0]
         }
     }
+
+    // Unlike the other entries, this is needed to index the system, before it's been verified.
+    fn extends(&self) -> &[String] {
+        if self.extends.is_empty() {
+            &[]
+        } else {
+            &self.extends[0]
+        }
+    }
 }
 
 pub struct SystemBuilder<'a> {
     id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     entries: SystemBuilderEntries<'a>,
+    parents: OnceCell<Vec<&'a SystemBuilder<'a>>>,
 
     // TODO: Remove.
     href: OnceCell<String>,
@@ -233,14 +264,18 @@ impl<'a> SystemBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::system_block);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let entries = SystemBuilderEntries::from_pest(path, inner);
 
         SystemBuilder {
             id,
             location,
+            file_location,
 
             entries,
+            parents: OnceCell::new(),
 
             href: OnceCell::new(),
         }
@@ -252,12 +287,55 @@ impl<'a> SystemBuilder<'a> {
         errors: &mut ParsingErrorContext<'a>,
     ) {
         self.entries.verify_structure(self, index, errors);
+
+        let mut found_error = false;
+        let mut parents = Vec::with_capacity(self.extends().len());
+        for parent_id in self.extends() {
+            match index.search_system(parent_id) {
+                // Systems can only extend systems declared before them, so there are no cycles.
+                Some(parent) if parent.location.serial() < self.location.serial() => {
+                    parents.push(parent);
+                }
+
+                Some(parent) if std::ptr::eq(parent, self) => {
+                    found_error = true;
+                    errors.err(ParsingError::SystemError(
+                        self,
+                        SystemParsingError::ExtendsItself,
+                    ));
+                }
+
+                Some(parent) => {
+                    found_error = true;
+                    errors.err(ParsingError::SystemError(
+                        self,
+                        SystemParsingError::ExtendsForwardReference(parent),
+                    ));
+                }
+
+                None => {
+                    found_error = true;
+                    errors.err(ParsingError::SystemError(
+                        self,
+                        SystemParsingError::ExtendsNotFound(parent_id),
+                    ));
+                }
+            }
+        }
+
+        if !found_error {
+            self.parents.set(parents).unwrap();
+        }
     }
 
     pub fn id(&self) -> &str {
         &self.id
     }
 
+    pub fn extends(&self) -> &[String] {
+        self.entries.extends()
+    }
+
     pub fn bib_refs(&'a self) -> Box<dyn Iterator<Item = &BibliographyBuilderEntry> + '_> {
         self.entries.bib_refs()
     }
@@ -282,14 +360,25 @@ impl<'a> SystemBuilder<'a> {
             .iter()
             .map(TextBuilder::finish)
             .collect();
+        let parents = self
+            .parents
+            .get()
+            .unwrap()
+            .iter()
+            .map(|parent| SystemBlockRef::new(parent.location()))
+            .collect();
         let href = self.href.get().unwrap().clone();
 
-        SystemBlock::new(id, name, tagline, description, href)
+        SystemBlock::new(id, name, tagline, description, parents, href)
     }
 
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> std::fmt::Debug for SystemBuilder<'a> {
@@ -346,6 +435,15 @@ impl<'a> SystemBuilderChild<'a> {
         }
     }
 
+    pub fn readable(self) -> Option<ReadableBuilder<'a>> {
+        match self {
+            Self::Symbol(symbol_ref) => Some(ReadableBuilder::Symbol(symbol_ref)),
+            Self::Definition(definition_ref) => Some(ReadableBuilder::Definition(definition_ref)),
+
+            _ => None,
+        }
+    }
+
     pub fn theorem(self) -> Option<&'a TheoremBuilder<'a>> {
         match self {
             Self::Theorem(theorem_ref) => Some(theorem_ref),
@@ -354,6 +452,16 @@ impl<'a> SystemBuilderChild<'a> {
         }
     }
 
+    pub fn file_location(self) -> &'a FileLocation {
+        match self {
+            Self::Type(type_ref) => type_ref.file_location(),
+            Self::Symbol(symbol_ref) => symbol_ref.file_location(),
+            Self::Definition(definition_ref) => definition_ref.file_location(),
+            Self::Axiom(axiom_ref) => axiom_ref.file_location(),
+            Self::Theorem(theorem_ref) => theorem_ref.file_location(),
+        }
+    }
+
     pub fn finish<'b>(&self) -> BlockRef<'b> {
         let location = match self {
             Self::Type(type_ref) => type_ref.location(),
@@ -384,13 +492,16 @@ impl<'a> std::fmt::Debug for SystemBuilderChild<'a> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum Flag {
     Reflexive,
     Symmetric,
     Transitive,
 
     Function,
+    Commutative,
+    Associative,
+    Detachment,
 }
 
 impl Flag {
@@ -401,10 +512,26 @@ impl Flag {
             Rule::flag_transitive => Flag::Transitive,
 
             Rule::flag_function => Flag::Function,
+            Rule::flag_commutative => Flag::Commutative,
+            Rule::flag_associative => Flag::Associative,
+            Rule::flag_detachment => Flag::Detachment,
 
             _ => unreachable!(),
         }
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Reflexive => "reflexive",
+            Self::Symmetric => "symmetric",
+            Self::Transitive => "transitive",
+
+            Self::Function => "function",
+            Self::Commutative => "commutative",
+            Self::Associative => "associative",
+            Self::Detachment => "detachment",
+        }
+    }
 }
 
 struct FlagList {
@@ -415,6 +542,9 @@ struct FlagList {
     transitive: Cell<bool>,
 
     function: Cell<bool>,
+    commutative: Cell<bool>,
+    associative: Cell<bool>,
+    detachment: Cell<bool>,
 
     verified: Cell<bool>,
 }
@@ -433,6 +563,9 @@ impl FlagList {
             transitive: Cell::new(false),
 
             function: Cell::new(false),
+            commutative: Cell::new(false),
+            associative: Cell::new(false),
+            detachment: Cell::new(false),
 
             verified: Cell::new(false),
         }
@@ -494,6 +627,39 @@ impl FlagList {
                         self.function.set(true);
                     }
                 }
+
+                Flag::Commutative => {
+                    if self.commutative.get() {
+                        found_error = true;
+                        errors.err(generate_error(FlagListParsingError::DuplicateFlag(
+                            Flag::Commutative,
+                        )));
+                    } else {
+                        self.commutative.set(true);
+                    }
+                }
+
+                Flag::Associative => {
+                    if self.associative.get() {
+                        found_error = true;
+                        errors.err(generate_error(FlagListParsingError::DuplicateFlag(
+                            Flag::Associative,
+                        )));
+                    } else {
+                        self.associative.set(true);
+                    }
+                }
+
+                Flag::Detachment => {
+                    if self.detachment.get() {
+                        found_error = true;
+                        errors.err(generate_error(FlagListParsingError::DuplicateFlag(
+                            Flag::Detachment,
+                        )));
+                    } else {
+                        self.detachment.set(true);
+                    }
+                }
             }
         }
 
@@ -616,13 +782,6 @@ impl FlagList {
                 return;
             };
 
-        if first_premise_function != second_premise_function {
-            errors.err(generate_error(
-                FlagListParsingError::TransitivityPremiseSymbolNotEqual,
-            ));
-            return;
-        }
-
         if first_premise_right != second_premise_left {
             errors.err(generate_error(
                 FlagListParsingError::TransitivityPremiseArgumentMismatch,
@@ -640,13 +799,6 @@ impl FlagList {
                 return;
             };
 
-        if assertion_function != first_premise_function {
-            errors.err(generate_error(
-                FlagListParsingError::TransitivityAssertionSymbolNotEqual,
-            ));
-            return;
-        }
-
         if assertion_left != first_premise_left {
             errors.err(generate_error(
                 FlagListParsingError::TransitivityAssertionLeftMismatch,
@@ -661,7 +813,20 @@ impl FlagList {
             return;
         }
 
-        assertion_function.set_transitive(deductable_ref, errors);
+        // A claim with different relations is an entry in the table of what follows from one
+        // relation and then another, such as `<=` from `=` and then `<=`.
+        if first_premise_function == second_premise_function
+            && first_premise_function == assertion_function
+        {
+            assertion_function.set_transitive(deductable_ref, errors);
+        } else {
+            first_premise_function.set_transitive_with(
+                deductable_ref,
+                second_premise_function,
+                assertion_function,
+                errors,
+            );
+        }
     }
 
     fn verify_function<'a, F>(
@@ -687,13 +852,6 @@ impl FlagList {
                 return;
             };
 
-        if !relation.is_preorder() {
-            errors.err(generate_error(
-                FlagListParsingError::FunctionRelationNotPreorder,
-            ));
-            return;
-        }
-
         let (assertion_left_function, assertion_left_inputs) =
             if let Some(info) = assertion_left.application() {
                 info
@@ -735,6 +893,8 @@ impl FlagList {
             return;
         }
 
+        let mut inputs = Vec::with_capacity(premise.len());
+
         let iter = premise
             .iter()
             .zip(assertion_left_inputs)
@@ -774,29 +934,219 @@ impl FlagList {
                     return;
                 };
 
-            if hypothesis_relation != relation {
+            // Each input can use its own relation, which needn't be the relation of the
+            // assertion, and a hypothesis relating the inputs backwards claims that the function
+            // reverses that relation in this input.
+            let reversed = if hypothesis_left == assertion_left_var
+                && hypothesis_right == assertion_right_var
+            {
+                false
+            } else if hypothesis_left == assertion_right_var
+                && hypothesis_right == assertion_left_var
+            {
+                true
+            } else {
+                errors.err(generate_error(
+                    FlagListParsingError::FunctionHypothesisVarMismatch(hypothesis),
+                ));
+                return;
+            };
+
+            inputs.push(FunctionInputBuilder::new(hypothesis_relation, reversed));
+        }
+
+        let function = FunctionBuilder::new(deductable_ref, inputs);
+        assertion_left_function.set_function(function, relation, errors);
+    }
+
+    fn verify_commutativity<'a, F>(
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) where
+        F: Fn(FlagListParsingError<'a>) -> ParsingError<'a>,
+    {
+        if !deductable_ref.premise().is_empty() {
+            errors.err(generate_error(
+                FlagListParsingError::CommutativityPremiseNotEmpty,
+            ));
+            return;
+        }
+
+        let (relation, assertion_left, assertion_right) =
+            if let Some(info) = deductable_ref.assertion().binary() {
+                info
+            } else {
                 errors.err(generate_error(
-                    FlagListParsingError::FunctionHypothesisRelationMismatch(hypothesis),
+                    FlagListParsingError::CommutativityAssertionNotBinary,
                 ));
                 return;
+            };
+
+        // Both sides must apply the same binary function to variables.
+        let sides = assertion_left.binary().zip(assertion_right.binary());
+        let (function, left_vars, right_vars) = match sides {
+            Some(((left_function, ll, lr), (right_function, rl, rr)))
+                if left_function == right_function =>
+            {
+                let vars = ll.variable().zip(lr.variable());
+                let vars = vars.zip(rl.variable().zip(rr.variable()));
+
+                match vars {
+                    Some((left_vars, right_vars)) => (left_function, left_vars, right_vars),
+
+                    None => {
+                        errors.err(generate_error(
+                            FlagListParsingError::CommutativityAssertionNotApplication,
+                        ));
+                        return;
+                    }
+                }
             }
 
-            if hypothesis_left != assertion_left_var {
+            _ => {
                 errors.err(generate_error(
-                    FlagListParsingError::FunctionHypothesisLeftVarMismatch(hypothesis),
+                    FlagListParsingError::CommutativityAssertionNotApplication,
                 ));
                 return;
             }
+        };
+
+        if left_vars.0 == left_vars.1 || left_vars.0 != right_vars.1 || left_vars.1 != right_vars.0
+        {
+            errors.err(generate_error(
+                FlagListParsingError::CommutativityArgumentMismatch,
+            ));
+            return;
+        }
+
+        function.set_commutative(deductable_ref, relation, errors);
+    }
+
+    fn verify_associativity<'a, F>(
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) where
+        F: Fn(FlagListParsingError<'a>) -> ParsingError<'a>,
+    {
+        if !deductable_ref.premise().is_empty() {
+            errors.err(generate_error(
+                FlagListParsingError::AssociativityPremiseNotEmpty,
+            ));
+            return;
+        }
 
-            if hypothesis_right != assertion_right_var {
+        let (relation, assertion_left, assertion_right) =
+            if let Some(info) = deductable_ref.assertion().binary() {
+                info
+            } else {
                 errors.err(generate_error(
-                    FlagListParsingError::FunctionHypothesisRightVarMismatch(hypothesis),
+                    FlagListParsingError::AssociativityAssertionNotBinary,
                 ));
                 return;
+            };
+
+        // The left side must be `f(f('a, 'b), 'c)`, and the right side `f('a, f('b, 'c))`.
+        let shape = assertion_left.binary().and_then(|(function, inner, c)| {
+            let (inner_function, a, b) = inner.binary()?;
+            let (right_function, right_a, right_inner) = assertion_right.binary()?;
+            let (right_inner_function, right_b, right_c) = right_inner.binary()?;
+
+            if inner_function != function
+                || right_function != function
+                || right_inner_function != function
+            {
+                return None;
             }
+
+            let left_vars = [a.variable()?, b.variable()?, c.variable()?];
+            let right_vars = [
+                right_a.variable()?,
+                right_b.variable()?,
+                right_c.variable()?,
+            ];
+
+            Some((function, left_vars, right_vars))
+        });
+
+        let (function, left_vars, right_vars) = if let Some(info) = shape {
+            info
+        } else {
+            errors.err(generate_error(
+                FlagListParsingError::AssociativityAssertionNotApplication,
+            ));
+            return;
+        };
+
+        if left_vars[0] == left_vars[1]
+            || left_vars[1] == left_vars[2]
+            || left_vars[0] == left_vars[2]
+            || left_vars != right_vars
+        {
+            errors.err(generate_error(
+                FlagListParsingError::AssociativityArgumentMismatch,
+            ));
+            return;
+        }
+
+        function.set_associative(deductable_ref, relation, errors);
+    }
+
+    // A detachment claim proves its assertion from its first hypothesis and a relation between the
+    // two, as in `'p; 'p <-> 'q;` proving `'q`. Rewriting with `!rw` relies on it to move from the
+    // step before to a proposition rewritten inside.
+    fn verify_detachment<'a, F>(
+        deductable_ref: DeductableBuilder<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) where
+        F: Fn(FlagListParsingError<'a>) -> ParsingError<'a>,
+    {
+        let premise = deductable_ref.premise();
+        if premise.len() != 2 {
+            errors.err(generate_error(
+                FlagListParsingError::DetachmentWrongPremiseLength,
+            ));
+            return;
         }
 
-        assertion_left_function.set_function(deductable_ref, relation, errors);
+        let proved = if let Some(var) = premise[0].formula().variable() {
+            var
+        } else {
+            errors.err(generate_error(
+                FlagListParsingError::DetachmentFirstPremiseNotVariable,
+            ));
+            return;
+        };
+
+        let (relation, premise_left, premise_right) = if let Some(info) = premise[1].simple_binary()
+        {
+            info
+        } else {
+            errors.err(generate_error(
+                FlagListParsingError::DetachmentSecondPremiseNotBinary,
+            ));
+            return;
+        };
+
+        let assertion = if let Some(var) = deductable_ref.assertion().formula().variable() {
+            var
+        } else {
+            errors.err(generate_error(
+                FlagListParsingError::DetachmentAssertionNotVariable,
+            ));
+            return;
+        };
+
+        if premise_left != proved || premise_right != assertion {
+            errors.err(generate_error(
+                FlagListParsingError::DetachmentArgumentMismatch,
+            ));
+            return;
+        }
+
+        relation.set_detachment(deductable_ref, errors);
     }
 
     fn verify_formulas<'a, F>(
@@ -824,47 +1174,138 @@ impl FlagList {
         if self.function.get() {
             Self::verify_function(deductable_ref, errors, generate_error);
         }
-    }
-}
 
-struct AxiomBuilderEntries<'a> {
-    names: Vec<String>,
-    taglines: Vec<ParagraphBuilder<'a>>,
-    descriptions: Vec<Vec<TextBuilder<'a>>>,
+        if self.commutative.get() {
+            Self::verify_commutativity(deductable_ref, errors, generate_error);
+        }
 
-    flag_lists: Vec<FlagList>,
-    vars: Vec<VariableBuilder<'a>>,
-    premises: Vec<Vec<DisplayFormulaBuilder<'a>>>,
-    assertions: Vec<DisplayFormulaBuilder<'a>>,
+        if self.associative.get() {
+            Self::verify_associativity(deductable_ref, errors, generate_error);
+        }
 
-    verified: Cell<bool>,
+        if self.detachment.get() {
+            Self::verify_detachment(deductable_ref, errors, generate_error);
+        }
+    }
 }
 
-impl<'a> AxiomBuilderEntries<'a> {
-    fn from_pest(path: &Path, pairs: Pairs<Rule>) -> Self {
-        let mut names = Vec::with_capacity(1);
-        let mut taglines = Vec::with_capacity(1);
-        let mut descriptions = Vec::with_capacity(1);
-        let mut vars = Vec::new();
-        let mut premises = Vec::new();
-        let mut assertions = Vec::new();
-        let mut flag_lists = Vec::new();
+#[derive(Debug)]
+pub struct DistinctBuilder {
+    ids: Vec<String>,
+    file_location: FileLocation,
 
-        for pair in pairs {
-            match pair.as_rule() {
-                Rule::block_name => {
-                    let string = pair.into_inner().next().unwrap();
-                    let string_contents = string.into_inner().next().unwrap();
-                    let name = string_contents.as_str().to_owned();
+    indices: OnceCell<Vec<usize>>,
+}
 
-                    names.push(name);
-                }
-                Rule::block_tagline => {
-                    let tagline =
-                        ParagraphBuilder::from_pest(path, pair.into_inner().next().unwrap());
+impl DistinctBuilder {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::block_distinct);
 
-                    taglines.push(tagline);
-                }
+        let file_location = FileLocation::new(path, pair.as_span());
+        let ids = pair
+            .into_inner()
+            .map(|pair| pair.into_inner().next().unwrap().as_str().to_owned())
+            .collect();
+
+        DistinctBuilder {
+            ids,
+            file_location,
+
+            indices: OnceCell::new(),
+        }
+    }
+
+    fn verify_structure<'a, F>(
+        &self,
+        vars: &[VariableBuilder<'a>],
+        errors: &mut ParsingErrorContext<'a>,
+        generate_error: F,
+    ) -> bool
+    where
+        F: Fn(DistinctParsingError) -> ParsingError<'a>,
+    {
+        let mut indices = Vec::with_capacity(self.ids.len());
+
+        for id in &self.ids {
+            let index = match vars.iter().find(|var| var.id() == id) {
+                Some(var) => var.index(),
+                None => {
+                    errors.err(generate_error(DistinctParsingError::VariableIdNotFound(
+                        id.clone(),
+                    )));
+                    return false;
+                }
+            };
+
+            if indices.contains(&index) {
+                errors.err(generate_error(DistinctParsingError::DuplicateVariable(
+                    id.clone(),
+                )));
+                return false;
+            }
+
+            indices.push(index);
+        }
+
+        self.indices.set(indices).unwrap();
+        true
+    }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
+    // Every pair of variables in the list is distinct.
+    fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let indices = self.indices.get().unwrap();
+
+        indices
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, left)| indices[i + 1..].iter().map(move |right| (*left, *right)))
+    }
+}
+
+struct AxiomBuilderEntries<'a> {
+    names: Vec<String>,
+    taglines: Vec<ParagraphBuilder<'a>>,
+    descriptions: Vec<Vec<TextBuilder<'a>>>,
+
+    flag_lists: Vec<FlagList>,
+    vars: Vec<VariableBuilder<'a>>,
+    distincts: Vec<DistinctBuilder>,
+    premises: Vec<Vec<DisplayFormulaBuilder<'a>>>,
+    assertions: Vec<DisplayFormulaBuilder<'a>>,
+
+    verified: Cell<bool>,
+}
+
+impl<'a> AxiomBuilderEntries<'a> {
+    fn from_pest(path: &Path, pairs: Pairs<Rule>) -> Self {
+        let mut names = Vec::with_capacity(1);
+        let mut taglines = Vec::with_capacity(1);
+        let mut descriptions = Vec::with_capacity(1);
+        let mut vars = Vec::new();
+        let mut distincts = Vec::new();
+        let mut premises = Vec::new();
+        let mut assertions = Vec::new();
+        let mut flag_lists = Vec::new();
+
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::block_name => {
+                    let string = pair.into_inner().next().unwrap();
+                    let string_contents = string.into_inner().next().unwrap();
+                    let name = string_contents.as_str().to_owned();
+
+                    names.push(name);
+                }
+                Rule::block_tagline => {
+                    let tagline =
+                        ParagraphBuilder::from_pest(path, pair.into_inner().next().unwrap());
+
+                    taglines.push(tagline);
+                }
                 Rule::block_description => {
                     let description = pair
                         .into_inner()
@@ -879,18 +1320,21 @@ impl<'a> AxiomBuilderEntries<'a> {
 
                     flag_lists.push(flag_list);
                 }
-                Rule::var_declaration => vars.push(VariableBuilder::from_pest(pair, vars.len())),
+                Rule::var_declaration => {
+                    vars.push(VariableBuilder::from_pest(path, pair, vars.len()))
+                }
+                Rule::block_distinct => distincts.push(DistinctBuilder::from_pest(path, pair)),
                 Rule::premise => {
                     let premise = pair
                         .into_inner()
-                        .map(DisplayFormulaBuilder::from_pest)
+                        .map(|pair| DisplayFormulaBuilder::from_pest(path, pair))
                         .collect();
 
                     premises.push(premise);
                 }
                 Rule::assertion => {
                     let assertion =
-                        DisplayFormulaBuilder::from_pest(pair.into_inner().next().unwrap());
+                        DisplayFormulaBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     assertions.push(assertion);
                 }
@@ -906,6 +1350,7 @@ impl<'a> AxiomBuilderEntries<'a> {
 
             flag_lists,
             vars,
+            distincts,
             premises,
             assertions,
 
@@ -953,7 +1398,10 @@ impl<'a> AxiomBuilderEntries<'a> {
 
             1 => {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
-                    ParsingError::AxiomError(axiom_ref, AxiomParsingError::TaglineParsingError(e))
+                    ParsingError::AxiomError(
+                        axiom_ref,
+                        AxiomParsingError::TaglineParsingError(&self.taglines[0], e),
+                    )
                 });
 
                 if !success {
@@ -1031,13 +1479,18 @@ impl<'a> AxiomBuilderEntries<'a> {
             }
         }
 
-        match self.premises.len() {
-            0 => {}
-            1 => {
-                for hypothesis in &self.premises[0] {
-                    hypothesis.verify_structure(errors);
-                }
+        for distinct in &self.distincts {
+            let success = distinct.verify_structure(&self.vars, errors, |e| {
+                ParsingError::AxiomError(axiom_ref, AxiomParsingError::DistinctError(distinct, e))
+            });
+
+            if !success {
+                found_error = true
             }
+        }
+
+        match self.premises.len() {
+            0 | 1 => {}
 
             _ => {
                 found_error = true;
@@ -1057,7 +1510,7 @@ impl<'a> AxiomBuilderEntries<'a> {
                 ));
             }
 
-            1 => self.assertions[0].verify_structure(errors),
+            1 => {}
 
             _ => {
                 found_error = true;
@@ -1092,7 +1545,7 @@ impl<'a> AxiomBuilderEntries<'a> {
         index: &BuilderIndex<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
-        let local_index = index.get_local(axiom_ref.system_id(), &self.vars);
+        let local_index = index.get_local(axiom_ref.system_id(), axiom_ref.serial(), &self.vars);
 
         for hypothesis in self.premise() {
             hypothesis.build(&local_index, errors, |formula, e| {
@@ -1140,6 +1593,11 @@ impl<'a> AxiomBuilderEntries<'a> {
         &self.vars
     }
 
+    fn distinct(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        assert!(self.verified.get());
+        self.distincts.iter().flat_map(DistinctBuilder::pairs)
+    }
+
     fn premise(&self) -> &[DisplayFormulaBuilder<'a>] {
         assert!(self.verified.get());
         if self.premises.is_empty() {
@@ -1159,6 +1617,7 @@ pub struct AxiomBuilder<'a> {
     id: String,
     system_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     system_ref: OnceCell<&'a SystemBuilder<'a>>,
     entries: AxiomBuilderEntries<'a>,
@@ -1172,7 +1631,9 @@ impl<'a> AxiomBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::axiom_block);
 
         let mut inner = pair.into_inner();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
 
         let entries = AxiomBuilderEntries::from_pest(path, inner);
@@ -1181,6 +1642,7 @@ impl<'a> AxiomBuilder<'a> {
             id,
             system_id,
             location,
+            file_location,
 
             system_ref: OnceCell::new(),
             entries,
@@ -1247,6 +1709,7 @@ impl<'a> AxiomBuilder<'a> {
             .iter()
             .map(VariableBuilder::finish)
             .collect();
+        let distinct = self.entries.distinct().collect();
         let premise = self
             .entries
             .premise()
@@ -1264,6 +1727,7 @@ impl<'a> AxiomBuilder<'a> {
             tagline,
             description,
             vars,
+            distinct,
             premise,
             assertion,
             href,
@@ -1278,6 +1742,10 @@ impl<'a> AxiomBuilder<'a> {
         &self.system_id
     }
 
+    pub fn vars(&'a self) -> &'a [VariableBuilder<'a>] {
+        self.entries.vars()
+    }
+
     pub fn premise(&'a self) -> &[DisplayFormulaBuilder] {
         self.entries.premise()
     }
@@ -1293,6 +1761,10 @@ impl<'a> AxiomBuilder<'a> {
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> std::fmt::Debug for AxiomBuilder<'a> {
@@ -1320,6 +1792,7 @@ struct TheoremBuilderEntries<'a> {
 
     flag_lists: Vec<FlagList>,
     vars: Vec<VariableBuilder<'a>>,
+    distincts: Vec<DistinctBuilder>,
     premises: Vec<Vec<DisplayFormulaBuilder<'a>>>,
     assertions: Vec<DisplayFormulaBuilder<'a>>,
 
@@ -1333,6 +1806,7 @@ impl<'a> TheoremBuilderEntries<'a> {
         let mut descriptions = Vec::with_capacity(1);
         let mut flag_lists = Vec::new();
         let mut vars = Vec::new();
+        let mut distincts = Vec::new();
         let mut premises = Vec::new();
         let mut assertions = Vec::new();
 
@@ -1365,18 +1839,21 @@ impl<'a> TheoremBuilderEntries<'a> {
 
                     flag_lists.push(flag_list);
                 }
-                Rule::var_declaration => vars.push(VariableBuilder::from_pest(pair, vars.len())),
+                Rule::var_declaration => {
+                    vars.push(VariableBuilder::from_pest(path, pair, vars.len()))
+                }
+                Rule::block_distinct => distincts.push(DistinctBuilder::from_pest(path, pair)),
                 Rule::premise => {
                     let premise = pair
                         .into_inner()
-                        .map(DisplayFormulaBuilder::from_pest)
+                        .map(|pair| DisplayFormulaBuilder::from_pest(path, pair))
                         .collect();
 
                     premises.push(premise);
                 }
                 Rule::assertion => {
                     let assertion =
-                        DisplayFormulaBuilder::from_pest(pair.into_inner().next().unwrap());
+                        DisplayFormulaBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     assertions.push(assertion);
                 }
@@ -1392,6 +1869,7 @@ impl<'a> TheoremBuilderEntries<'a> {
 
             flag_lists,
             vars,
+            distincts,
             premises,
             assertions,
 
@@ -1441,7 +1919,7 @@ impl<'a> TheoremBuilderEntries<'a> {
                 let success = self.taglines[0].verify_structure(index, errors, |e| {
                     ParsingError::TheoremError(
                         theorem_ref,
-                        TheoremParsingError::TaglineParsingError(e),
+                        TheoremParsingError::TaglineParsingError(&self.taglines[0], e),
                     )
                 });
 
@@ -1521,13 +1999,21 @@ impl<'a> TheoremBuilderEntries<'a> {
             );
         }
 
-        match self.premises.len() {
-            0 => {}
-            1 => {
-                for hypothesis in &self.premises[0] {
-                    hypothesis.verify_structure(errors);
-                }
+        for distinct in &self.distincts {
+            let success = distinct.verify_structure(&self.vars, errors, |e| {
+                ParsingError::TheoremError(
+                    theorem_ref,
+                    TheoremParsingError::DistinctError(distinct, e),
+                )
+            });
+
+            if !success {
+                found_error = true
             }
+        }
+
+        match self.premises.len() {
+            0 | 1 => {}
 
             _ => {
                 found_error = true;
@@ -1547,7 +2033,7 @@ impl<'a> TheoremBuilderEntries<'a> {
                 ));
             }
 
-            1 => self.assertions[0].verify_structure(errors),
+            1 => {}
 
             _ => {
                 found_error = true;
@@ -1582,7 +2068,8 @@ impl<'a> TheoremBuilderEntries<'a> {
         index: &BuilderIndex<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
-        let local_index = index.get_local(theorem_ref.system_id(), &self.vars);
+        let local_index =
+            index.get_local(theorem_ref.system_id(), theorem_ref.serial(), &self.vars);
 
         for hypothesis in self.premise() {
             hypothesis.build(&local_index, errors, |formula, e| {
@@ -1633,6 +2120,11 @@ impl<'a> TheoremBuilderEntries<'a> {
         &self.vars
     }
 
+    fn distinct(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        assert!(self.verified.get());
+        self.distincts.iter().flat_map(DistinctBuilder::pairs)
+    }
+
     fn premise(&self) -> &[DisplayFormulaBuilder<'a>] {
         assert!(self.verified.get());
         if self.premises.is_empty() {
@@ -1653,6 +2145,7 @@ pub struct TheoremBuilder<'a> {
     id: String,
     system_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
     system_ref: OnceCell<&'a SystemBuilder<'a>>,
     entries: TheoremBuilderEntries<'a>,
@@ -1669,7 +2162,9 @@ impl<'a> TheoremBuilder<'a> {
 
         let mut inner = pair.into_inner();
         let head = inner.next().unwrap();
-        let id = inner.next().unwrap().as_str().to_owned();
+        let id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, id_pair.as_span());
+        let id = id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
 
         let kind = TheoremKind::from_pest(head.into_inner().next().unwrap());
@@ -1680,6 +2175,7 @@ impl<'a> TheoremBuilder<'a> {
             id,
             system_id,
             location,
+            file_location,
 
             system_ref: OnceCell::new(),
             entries,
@@ -1749,6 +2245,7 @@ impl<'a> TheoremBuilder<'a> {
             .iter()
             .map(VariableBuilder::finish)
             .collect();
+        let distinct = self.entries.distinct().collect();
         let premise = self
             .entries
             .premise()
@@ -1767,6 +2264,7 @@ impl<'a> TheoremBuilder<'a> {
             tagline,
             description,
             vars,
+            distinct,
             premise,
             assertion,
             href,
@@ -1814,6 +2312,10 @@ impl<'a> TheoremBuilder<'a> {
     pub fn location(&self) -> BlockLocation {
         self.location
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> std::fmt::Debug for TheoremBuilder<'a> {
@@ -1829,21 +2331,42 @@ pub enum DeductableBuilder<'a> {
 }
 
 impl<'a> DeductableBuilder<'a> {
-    fn premise(self) -> &'a [DisplayFormulaBuilder<'a>] {
+    pub fn id(self) -> &'a str {
+        match self {
+            Self::Axiom(axiom_ref) => axiom_ref.id(),
+            Self::Theorem(theorem_ref) => theorem_ref.id(),
+        }
+    }
+
+    pub fn file_location(self) -> &'a FileLocation {
+        match self {
+            Self::Axiom(axiom_ref) => axiom_ref.file_location(),
+            Self::Theorem(theorem_ref) => theorem_ref.file_location(),
+        }
+    }
+
+    pub fn vars(self) -> &'a [VariableBuilder<'a>] {
+        match self {
+            Self::Axiom(axiom_ref) => axiom_ref.vars(),
+            Self::Theorem(theorem_ref) => theorem_ref.vars(),
+        }
+    }
+
+    pub fn premise(self) -> &'a [DisplayFormulaBuilder<'a>] {
         match self {
             Self::Axiom(axiom_ref) => axiom_ref.premise(),
             Self::Theorem(theorem_ref) => theorem_ref.premise(),
         }
     }
 
-    fn assertion(self) -> &'a DisplayFormulaBuilder<'a> {
+    pub fn assertion(self) -> &'a DisplayFormulaBuilder<'a> {
         match self {
             Self::Axiom(axiom_ref) => axiom_ref.assertion(),
             Self::Theorem(theorem_ref) => theorem_ref.assertion(),
         }
     }
 
-    fn finish<'b>(self) -> DeductableBlockRef<'b> {
+    pub fn finish<'b>(self) -> DeductableBlockRef<'b> {
         match self {
             Self::Axiom(axiom) => {
                 let axiom_location = axiom.location();
@@ -1862,27 +2385,228 @@ impl<'a> DeductableBuilder<'a> {
     }
 }
 
+#[derive(Debug)]
+enum ProofBuilderPremise<'a> {
+    Tag(String, OnceCell<&'a ProofBuilderStep<'a>>),
+    Hypothesis(usize),
+}
+
+impl<'a> ProofBuilderPremise<'a> {
+    fn from_pest(pair: Pair<Rule>) -> Self {
+        match pair.as_rule() {
+            Rule::tag => {
+                let tag = pair.into_inner().next().unwrap().as_str().to_owned();
+
+                Self::Tag(tag, OnceCell::new())
+            }
+            Rule::integer => Self::Hypothesis(pair.as_str().parse().unwrap()),
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn verify_structure(
+        &self,
+        theorem_ref: &'a TheoremBuilder<'a>,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
+        tags: &HashMap<&str, &'a ProofBuilderStep<'a>>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        let e = match self {
+            Self::Tag(tag, premise_ref) => match tags.get(tag.as_str()) {
+                Some(premise_step) if premise_step.index() < step_ref.index() => {
+                    premise_ref.set(*premise_step).unwrap();
+                    return true;
+                }
+
+                Some(premise_step) => ProofStepParsingError::PremiseNotBefore(premise_step),
+                None => ProofStepParsingError::PremiseTagNotFound(tag.clone()),
+            },
+
+            Self::Hypothesis(0) => ProofStepParsingError::HypothesisZeroIndex,
+            Self::Hypothesis(id) if *id > theorem_ref.premise().len() => {
+                ProofStepParsingError::HypothesisIndexOutOfRange
+            }
+            Self::Hypothesis(_) => return true,
+        };
+
+        errors.err(ParsingError::ProofError(
+            proof_ref,
+            ProofParsingError::StepError(step_ref, e),
+        ));
+
+        false
+    }
+
+    fn build(&self, prev_steps: &[ProofBuilderElement<'a>]) -> Option<ProofBlockPremise> {
+        match self {
+            Self::Tag(_, premise_ref) => {
+                let premise_index = premise_ref.get().unwrap().index();
+
+                // Proofs are checked as a flat list of small steps, and the last small step of a
+                // step is the one which derives its formula.
+                let mut small_step_count = 0;
+                for step in prev_steps[..=premise_index]
+                    .iter()
+                    .filter_map(ProofBuilderElement::step)
+                {
+                    small_step_count += step.small_steps.get()?.len();
+                }
+
+                Some(ProofBlockPremise::Step(small_step_count - 1))
+            }
+
+            Self::Hypothesis(id) => Some(ProofBlockPremise::Hypothesis(*id)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProofBuilderSubstitution<'a> {
+    file_location: FileLocation,
+
+    var: String,
+    formula: DisplayFormulaBuilder<'a>,
+
+    var_ref: OnceCell<&'a VariableBuilder<'a>>,
+}
+
+impl<'a> ProofBuilderSubstitution<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::proof_substitution_item);
+
+        let mut inner = pair.into_inner();
+
+        let var_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, var_pair.as_span());
+        let var = var_pair.into_inner().next().unwrap().as_str().to_owned();
+
+        let formula = DisplayFormulaBuilder::from_pest(path, inner.next().unwrap());
+
+        ProofBuilderSubstitution {
+            file_location,
+
+            var,
+            formula,
+
+            var_ref: OnceCell::new(),
+        }
+    }
+
+    fn verify_structure(
+        &'a self,
+        deductable: DeductableBuilder<'a>,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
+        seen: &mut HashSet<&'a str>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        let e = match deductable.vars().iter().find(|var| var.id() == self.var) {
+            Some(var_ref) if seen.insert(var_ref.id()) => {
+                self.var_ref.set(var_ref).unwrap();
+                return true;
+            }
+
+            Some(_) => ProofStepParsingError::SubstitutionDuplicateVariable(self),
+            None => ProofStepParsingError::SubstitutionVariableNotFound(self),
+        };
+
+        errors.err(ParsingError::ProofError(
+            proof_ref,
+            ProofParsingError::StepError(step_ref, e),
+        ));
+
+        false
+    }
+
+    fn build(
+        &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        let generate_error = |formula, e| {
+            ParsingError::ProofError(
+                proof_ref,
+                ProofParsingError::StepError(
+                    step_ref,
+                    ProofStepParsingError::FormulaError(formula, e),
+                ),
+            )
+        };
+
+        if !self.formula.build(local_index, errors, generate_error) {
+            return false;
+        }
+
+        let var_type = self.var_ref.get().unwrap().type_signature();
+        if var_type.accepts(self.formula.type_signature()) {
+            true
+        } else {
+            errors.err(ParsingError::ProofError(
+                proof_ref,
+                ProofParsingError::StepError(
+                    step_ref,
+                    ProofStepParsingError::SubstitutionTypeMismatch(self),
+                ),
+            ));
+
+            false
+        }
+    }
+
+    fn pair(&self) -> (usize, FormulaBuilder<'a>) {
+        let index = self.var_ref.get().unwrap().index();
+
+        (index, self.formula.formula().clone())
+    }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
+    pub fn var(&self) -> &str {
+        &self.var
+    }
+}
+
 #[derive(Debug)]
 struct ProofBuilderMeta<'a> {
     justifications: Vec<ProofJustificationBuilder<'a>>,
     tags: Vec<String>,
 
+    // Tags and hypotheses given in parentheses after an axiom or theorem, in the order of its
+    // hypotheses.
+    premises: Vec<ProofBuilderPremise<'a>>,
+
+    // Formulas for some of the variables of an axiom or theorem, given after the step's formula.
+    substitution: Vec<ProofBuilderSubstitution<'a>>,
+
     justification_verified: Cell<bool>,
     tag_verified: Cell<bool>,
 }
 
 impl<'a> ProofBuilderMeta<'a> {
-    fn from_pest(pair: Pair<Rule>) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>, substitution_pair: Option<Pair<Rule>>) -> Self {
         assert_eq!(pair.as_rule(), Rule::proof_meta);
 
         let mut justifications = Vec::with_capacity(1);
         let mut tags = Vec::with_capacity(1);
+        let mut premises = Vec::new();
 
         for pair in pair.into_inner() {
             match pair.as_rule() {
-                Rule::proof_justification => justifications.push(
-                    ProofJustificationBuilder::from_pest(pair.into_inner().next().unwrap()),
-                ),
+                Rule::proof_justification => {
+                    let mut inner = pair.into_inner();
+                    justifications
+                        .push(ProofJustificationBuilder::from_pest(inner.next().unwrap()));
+
+                    if let Some(pair) = inner.next() {
+                        premises.extend(pair.into_inner().map(ProofBuilderPremise::from_pest));
+                    }
+                }
                 Rule::integer => {
                     justifications.push(ProofJustificationBuilder::hypothesis_from_pest(pair))
                 }
@@ -1892,11 +2616,39 @@ impl<'a> ProofBuilderMeta<'a> {
             }
         }
 
+        let substitution = match substitution_pair {
+            Some(pair) => pair
+                .into_inner()
+                .map(|pair| ProofBuilderSubstitution::from_pest(path, pair))
+                .collect(),
+
+            None => Vec::new(),
+        };
+
         ProofBuilderMeta {
             justifications,
             tags,
 
-            justification_verified: Cell::new(false),
+            premises,
+            substitution,
+
+            justification_verified: Cell::new(false),
+            tag_verified: Cell::new(false),
+        }
+    }
+
+    // The conclusion of a calculation, justified by the `links` steps before it.
+    fn calculation(tags: Vec<String>, links: usize) -> Self {
+        ProofBuilderMeta {
+            justifications: vec![ProofJustificationBuilder::Calculation(
+                CalculationJustificationBuilder::new(links),
+            )],
+            tags,
+
+            premises: Vec::new(),
+            substitution: Vec::new(),
+
+            justification_verified: Cell::new(false),
             tag_verified: Cell::new(false),
         }
     }
@@ -1946,12 +2698,85 @@ impl<'a> ProofBuilderMeta<'a> {
         self.tag_verified.set(!found_error);
     }
 
-    fn verify_structure(
+    fn verify_premises(
         &self,
         theorem_ref: &'a TheoremBuilder<'a>,
         proof_ref: &'a ProofBuilder<'a>,
         step_ref: &'a ProofBuilderStep<'a>,
+        tags: &HashMap<&str, &'a ProofBuilderStep<'a>>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        if self.premises.is_empty() {
+            return true;
+        }
+
+        let mut found_error = false;
+        for premise in &self.premises {
+            if !premise.verify_structure(theorem_ref, proof_ref, step_ref, tags, errors) {
+                found_error = true;
+            }
+        }
+
+        // Premises can only be given to a system child, so this is the only justification.
+        let deductable = self.justifications[0].system_child().unwrap().deductable();
+        let premise_len = deductable.premise().len();
+        if premise_len != self.premises.len() {
+            found_error = true;
+            errors.err(ParsingError::ProofError(
+                proof_ref,
+                ProofParsingError::StepError(
+                    step_ref,
+                    ProofStepParsingError::PremiseCountMismatch(premise_len, self.premises.len()),
+                ),
+            ));
+        }
+
+        !found_error
+    }
+
+    fn verify_substitution(
+        &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        if self.substitution.is_empty() {
+            return true;
+        }
+
+        let deductable = match self.justifications[0].system_child() {
+            Some(justification) => justification.deductable(),
+
+            None => {
+                errors.err(ParsingError::ProofError(
+                    proof_ref,
+                    ProofParsingError::StepError(
+                        step_ref,
+                        ProofStepParsingError::SubstitutionWithoutDeductable,
+                    ),
+                ));
+                return false;
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut found_error = false;
+        for substitution in &self.substitution {
+            if !substitution.verify_structure(deductable, proof_ref, step_ref, &mut seen, errors) {
+                found_error = true;
+            }
+        }
+
+        !found_error
+    }
+
+    fn verify_structure(
+        &'a self,
+        theorem_ref: &'a TheoremBuilder<'a>,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
         index: &BuilderIndex<'a>,
+        tags: &HashMap<&str, &'a ProofBuilderStep<'a>>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
         assert!(!self.justification_verified.get());
@@ -1979,7 +2804,10 @@ impl<'a> ProofBuilderMeta<'a> {
                     errors,
                 );
 
-                if !success {
+                if !success
+                    || !self.verify_premises(theorem_ref, proof_ref, step_ref, tags, errors)
+                    || !self.verify_substitution(proof_ref, step_ref, errors)
+                {
                     found_error = true;
                 }
             }
@@ -2001,14 +2829,67 @@ impl<'a> ProofBuilderMeta<'a> {
 
     fn build_small_steps(
         &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
         formula: &'a FormulaBuilder<'a>,
         prev_steps: &'a [ProofBuilderElement<'a>],
-        errors: &mut ParsingErrorContext,
-    ) -> Option<Vec<ProofBuilderSmallStep>> {
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> Option<Vec<ProofBuilderSmallStep<'a>>> {
         assert!(self.justification_verified.get());
 
-        self.justification()
-            .build_small_steps(formula, prev_steps, errors)
+        let premises = if self.premises.is_empty() {
+            None
+        } else {
+            let premises = self
+                .premises
+                .iter()
+                .map(|premise| premise.build(prev_steps))
+                .collect::<Option<_>>()?;
+
+            Some(premises)
+        };
+
+        let substitution = self
+            .substitution
+            .iter()
+            .map(ProofBuilderSubstitution::pair)
+            .collect();
+
+        let small_steps =
+            self.justification()
+                .build_small_steps(formula, prev_steps, premises, substitution);
+
+        match small_steps {
+            Ok(small_steps) => Some(small_steps),
+
+            Err(e) => {
+                errors.err(ParsingError::ProofError(
+                    proof_ref,
+                    ProofParsingError::StepError(step_ref, e),
+                ));
+
+                None
+            }
+        }
+    }
+
+    fn build_substitution(
+        &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        step_ref: &'a ProofBuilderStep<'a>,
+        local_index: &LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) -> bool {
+        assert!(self.justification_verified.get());
+
+        let mut found_error = false;
+        for substitution in &self.substitution {
+            if !substitution.build(proof_ref, step_ref, local_index, errors) {
+                found_error = true;
+            }
+        }
+
+        !found_error
     }
 
     fn justification(&self) -> &ProofJustificationBuilder<'a> {
@@ -2023,7 +2904,12 @@ impl<'a> ProofBuilderMeta<'a> {
 
 #[derive(Debug)]
 pub enum ProofBuilderSmallJustification<'a> {
-    Deductable(DeductableBuilder<'a>),
+    // The substitution pairs the index of a variable of the deductable with its replacement.
+    Deductable(
+        DeductableBuilder<'a>,
+        Option<Vec<ProofBlockPremise>>,
+        Vec<(usize, FormulaBuilder<'a>)>,
+    ),
     Hypothesis(usize),
 
     Definition,
@@ -2032,8 +2918,17 @@ pub enum ProofBuilderSmallJustification<'a> {
 impl<'a> ProofBuilderSmallJustification<'a> {
     fn finish<'b>(&self) -> ProofBlockSmallJustification<'b> {
         match self {
-            Self::Deductable(deductable) => {
-                ProofBlockSmallJustification::Deductable(deductable.finish())
+            Self::Deductable(deductable, premises, substitution) => {
+                let substitution = substitution
+                    .iter()
+                    .map(|(index, formula)| (*index, formula.finish()))
+                    .collect();
+
+                ProofBlockSmallJustification::Deductable(
+                    deductable.finish(),
+                    premises.clone(),
+                    substitution,
+                )
             }
             Self::Hypothesis(id) => ProofBlockSmallJustification::Hypothesis(*id),
 
@@ -2065,6 +2960,10 @@ impl<'a> ProofBuilderSmallStep<'a> {
 
         ProofBlockSmallStep::new(justification, formula)
     }
+
+    pub fn formula(&self) -> &FormulaBuilder<'a> {
+        &self.formula
+    }
 }
 
 #[derive(Debug)]
@@ -2073,7 +2972,9 @@ pub struct ProofBuilderStep<'a> {
 
     index: usize,
     meta: ProofBuilderMeta<'a>,
-    formula: DisplayFormulaBuilder<'a>,
+    // Only the conclusion of a calculation is parsed without a formula, since its relation isn't
+    // known until its links have been built.
+    formula: OnceCell<DisplayFormulaBuilder<'a>>,
     end: String,
 
     small_steps: OnceCell<Vec<ProofBuilderSmallStep<'a>>>,
@@ -2096,14 +2997,110 @@ impl<'a> ProofBuilderStep<'a> {
 
         let mut inner = pair.into_inner();
 
-        let meta = ProofBuilderMeta::from_pest(inner.next().unwrap());
+        let meta_pair = inner.next().unwrap();
 
-        let formula = DisplayFormulaBuilder::from_pest(inner.next().unwrap());
+        let formula = DisplayFormulaBuilder::from_pest(path, inner.next().unwrap());
+
+        let mut substitution_pair = None;
+        let mut end_pair = inner.next().unwrap();
+        if end_pair.as_rule() == Rule::proof_substitution {
+            substitution_pair = Some(end_pair);
+            end_pair = inner.next().unwrap();
+        }
+        let meta = ProofBuilderMeta::from_pest(path, meta_pair, substitution_pair);
 
-        let end_pair = inner.next().unwrap();
         let end_inner = end_pair.into_inner().next().unwrap();
         let end = end_inner.as_str().to_owned();
 
+        Self::new(file_location, index, meta, OnceCell::from(formula), end)
+    }
+
+    // Each link of a calculation is a step relating the formula of the link before it to its own,
+    // followed by a step concluding that the first formula is related to the last.
+    fn calculation_from_pest(path: &Path, pair: Pair<Rule>, index: usize) -> Vec<Self> {
+        assert_eq!(pair.as_rule(), Rule::proof_calculation);
+
+        let file_location = FileLocation::new(path, pair.as_span());
+
+        let mut inner = pair.into_inner().peekable();
+
+        let mut tags = Vec::with_capacity(1);
+        if inner.peek().unwrap().as_rule() == Rule::tag {
+            let tag_pair = inner.next().unwrap();
+            tags.push(tag_pair.into_inner().next().unwrap().as_str().to_owned());
+        }
+
+        let mut left = FormulaBuilder::from_pest(path, inner.next().unwrap());
+        let mut steps: Vec<Self> = Vec::new();
+        for link_pair in inner {
+            let (step, right) = Self::link_from_pest(path, link_pair, left, index + steps.len());
+
+            steps.push(step);
+            left = right;
+        }
+
+        let links = steps.len();
+        let meta = ProofBuilderMeta::calculation(tags, links);
+        let end = steps[links - 1].end.clone();
+        steps.push(Self::new(
+            file_location,
+            index + links,
+            meta,
+            OnceCell::new(),
+            end,
+        ));
+
+        steps
+    }
+
+    // Returns the step, along with the formula which the next link relates from.
+    fn link_from_pest(
+        path: &Path,
+        pair: Pair<Rule>,
+        left: FormulaBuilder<'a>,
+        index: usize,
+    ) -> (Self, FormulaBuilder<'a>) {
+        assert_eq!(pair.as_rule(), Rule::proof_calculation_link);
+
+        let file_location = FileLocation::new(path, pair.as_span());
+
+        let mut inner = pair.into_inner();
+
+        let operator = inner.next().unwrap().as_str().to_owned();
+        let right = FormulaBuilder::from_pest(path, inner.next().unwrap());
+        let formula = FormulaBuilder::infix(left, &operator, right.clone());
+
+        let meta_pair = inner.next().unwrap();
+
+        let mut substitution_pair = None;
+        let mut end_pair = inner.next().unwrap();
+        if end_pair.as_rule() == Rule::proof_substitution {
+            substitution_pair = Some(end_pair);
+            end_pair = inner.next().unwrap();
+        }
+        let meta = ProofBuilderMeta::from_pest(path, meta_pair, substitution_pair);
+
+        let end_inner = end_pair.into_inner().next().unwrap();
+        let end = end_inner.as_str().to_owned();
+
+        let step = Self::new(
+            file_location,
+            index,
+            meta,
+            OnceCell::from(DisplayFormulaBuilder::new(formula)),
+            end,
+        );
+
+        (step, right)
+    }
+
+    fn new(
+        file_location: FileLocation,
+        index: usize,
+        meta: ProofBuilderMeta<'a>,
+        formula: OnceCell<DisplayFormulaBuilder<'a>>,
+        end: String,
+    ) -> Self {
         ProofBuilderStep {
             file_location,
 
@@ -2136,12 +3133,11 @@ impl<'a> ProofBuilderStep<'a> {
         theorem_ref: &'a TheoremBuilder<'a>,
         proof_ref: &'a ProofBuilder<'a>,
         index: &BuilderIndex<'a>,
+        tags: &HashMap<&str, &'a ProofBuilderStep<'a>>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
-        self.formula.verify_structure(errors);
-
         self.meta
-            .verify_structure(theorem_ref, proof_ref, self, index, errors);
+            .verify_structure(theorem_ref, proof_ref, self, index, tags, errors);
     }
 
     fn build(
@@ -2151,21 +3147,66 @@ impl<'a> ProofBuilderStep<'a> {
         local_index: &LocalBuilderIndex<'a, '_>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
-        self.formula.build(local_index, errors, |formula, e| {
+        if let Some(calculation) = self.meta.justification().calculation() {
+            self.build_calculation(proof_ref, calculation, prev_steps, errors);
+            return;
+        }
+
+        let formula = self.formula.get().unwrap();
+        let formula_built = formula.build(local_index, errors, |formula, e| {
             ParsingError::ProofError(
                 proof_ref,
                 ProofParsingError::StepError(self, ProofStepParsingError::FormulaError(formula, e)),
             )
         });
 
+        if !formula_built {
+            return;
+        }
+
+        if !self
+            .meta
+            .build_substitution(proof_ref, self, local_index, errors)
+        {
+            return;
+        }
+
         if let Some(small_steps) =
             self.meta
-                .build_small_steps(self.formula.formula(), prev_steps, errors)
+                .build_small_steps(proof_ref, self, formula.formula(), prev_steps, errors)
         {
             self.small_steps.set(small_steps).unwrap();
         }
     }
 
+    // The formula of the conclusion is that of the last small step which joins its links.
+    fn build_calculation(
+        &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        calculation: &CalculationJustificationBuilder,
+        prev_steps: &'a [ProofBuilderElement<'a>],
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        match calculation.build_small_steps(prev_steps) {
+            Some(Ok(small_steps)) => {
+                let formula = small_steps.last().unwrap().formula().clone();
+
+                self.formula
+                    .set(DisplayFormulaBuilder::new(formula))
+                    .unwrap();
+                self.small_steps.set(small_steps).unwrap();
+            }
+
+            Some(Err(e)) => errors.err(ParsingError::ProofError(
+                proof_ref,
+                ProofParsingError::StepError(self, e),
+            )),
+
+            // A link failed to build, which has already been reported.
+            None => {}
+        }
+    }
+
     // TODO: Remove.
     fn set_href(
         &self,
@@ -2203,7 +3244,7 @@ impl<'a> ProofBuilderStep<'a> {
             .iter()
             .map(ProofBuilderSmallStep::finish)
             .collect();
-        let formula = self.formula.display().finish();
+        let formula = self.formula.get().unwrap().display();
         let end = self.end.clone();
 
         let id = self.id.get().unwrap().clone();
@@ -2236,8 +3277,12 @@ impl<'a> ProofBuilderStep<'a> {
         self.meta.justification_unchecked()
     }
 
-    pub fn formula(&self) -> &DisplayFormulaBuilder<'a> {
-        &self.formula
+    pub fn formula(&self) -> Option<&DisplayFormulaBuilder<'a>> {
+        self.formula.get()
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.small_steps.get().is_some()
     }
 }
 
@@ -2283,7 +3328,7 @@ impl<'a> ProofBuilderElement<'a> {
                 });
             }
 
-            Self::Step(step) => step.verify_structure(theorem_ref, proof_ref, index, errors),
+            Self::Step(step) => step.verify_structure(theorem_ref, proof_ref, index, tags, errors),
         }
     }
 
@@ -2312,7 +3357,7 @@ impl<'a> ProofBuilderElement<'a> {
     pub fn eq_formula(&self, formula: &FormulaBuilder<'a>) -> bool {
         match self {
             Self::Text(_) => false,
-            Self::Step(step) => step.formula.formula() == formula,
+            Self::Step(step) => step.formula().map(DisplayFormulaBuilder::formula) == Some(formula),
         }
     }
 
@@ -2360,12 +3405,76 @@ impl<'a> ProofBuilderElement<'a> {
     }
 }
 
+// An abbreviation for a formula, which every step of the proof can refer to by a variable.
+#[derive(Debug)]
+pub struct ProofBuilderLet<'a> {
+    file_location: FileLocation,
+
+    var: String,
+    formula: DisplayFormulaBuilder<'a>,
+}
+
+impl<'a> ProofBuilderLet<'a> {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
+        assert_eq!(pair.as_rule(), Rule::proof_let);
+
+        let mut inner = pair.into_inner();
+
+        let var_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, var_pair.as_span());
+        let var = var_pair.into_inner().next().unwrap().as_str().to_owned();
+
+        let formula = DisplayFormulaBuilder::from_pest(path, inner.next().unwrap());
+
+        ProofBuilderLet {
+            file_location,
+
+            var,
+            formula,
+        }
+    }
+
+    fn build(
+        &'a self,
+        proof_ref: &'a ProofBuilder<'a>,
+        local_index: &mut LocalBuilderIndex<'a, '_>,
+        errors: &mut ParsingErrorContext<'a>,
+    ) {
+        let success = self.formula.build(local_index, errors, |formula, e| {
+            ParsingError::ProofError(proof_ref, ProofParsingError::LetError(self, formula, e))
+        });
+        if !success {
+            return;
+        }
+
+        if !local_index.add_abbreviation(&self.var, self.formula.formula()) {
+            errors.err(ParsingError::ProofError(
+                proof_ref,
+                ProofParsingError::LetIdTaken(self),
+            ));
+        }
+    }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
+    pub fn var(&self) -> &str {
+        &self.var
+    }
+}
+
 #[derive(Debug)]
 pub struct ProofBuilder<'a> {
     system_id: String,
     theorem_id: String,
     location: BlockLocation,
+    file_location: FileLocation,
 
+    // Variables and abbreviations declared by the proof, which its steps can use on top of the
+    // variables of the theorem.
+    vars: Vec<VariableBuilder<'a>>,
+    lets: Vec<ProofBuilderLet<'a>>,
     elements: Vec<ProofBuilderElement<'a>>,
 
     theorem_ref: OnceCell<&'a TheoremBuilder<'a>>,
@@ -2376,19 +3485,38 @@ impl<'a> ProofBuilder<'a> {
         assert_eq!(pair.as_rule(), Rule::proof_block);
 
         let mut inner = pair.into_inner();
-        let theorem_id = inner.next().unwrap().as_str().to_owned();
+        let theorem_id_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, theorem_id_pair.as_span());
+        let theorem_id = theorem_id_pair.as_str().to_owned();
         let system_id = inner.next().unwrap().as_str().to_owned();
 
-        let elements = inner
-            .enumerate()
-            .map(|(i, pair)| ProofBuilderElement::from_pest(path, pair, i))
-            .collect();
+        let mut vars = Vec::new();
+        let mut lets = Vec::new();
+        let mut elements = Vec::new();
+        for pair in inner {
+            match pair.as_rule() {
+                Rule::var_declaration => {
+                    vars.push(VariableBuilder::local_from_pest(path, pair, vars.len()))
+                }
+                Rule::proof_let => lets.push(ProofBuilderLet::from_pest(path, pair)),
+                Rule::proof_calculation => elements.extend(
+                    ProofBuilderStep::calculation_from_pest(path, pair, elements.len())
+                        .into_iter()
+                        .map(ProofBuilderElement::Step),
+                ),
+
+                _ => elements.push(ProofBuilderElement::from_pest(path, pair, elements.len())),
+            }
+        }
 
         ProofBuilder {
             system_id,
             theorem_id,
             location,
+            file_location,
 
+            vars,
+            lets,
             elements,
 
             theorem_ref: OnceCell::new(),
@@ -2400,27 +3528,36 @@ impl<'a> ProofBuilder<'a> {
         index: &BuilderIndex<'a>,
         errors: &mut ParsingErrorContext<'a>,
     ) {
-        let theorem_ref =
-            if let Some(child) = index.search_system_child(&self.system_id, &self.theorem_id) {
-                if let Some(theorem_ref) = child.theorem() {
-                    theorem_ref
-                } else {
-                    errors.err(ParsingError::ProofError(
-                        self,
-                        ProofParsingError::ParentNotTheorem,
-                    ));
-                    return;
-                }
+        // A theorem is proved in its own system, even if other systems extend it.
+        let child = index
+            .search_system_child(&self.system_id, &self.theorem_id)
+            .filter(|child| child.system_id() == self.system_id);
+        let theorem_ref = if let Some(child) = child {
+            if let Some(theorem_ref) = child.theorem() {
+                theorem_ref
             } else {
                 errors.err(ParsingError::ProofError(
                     self,
-                    ProofParsingError::ParentNotFound,
+                    ProofParsingError::ParentNotTheorem,
                 ));
                 return;
-            };
+            }
+        } else {
+            errors.err(ParsingError::ProofError(
+                self,
+                ProofParsingError::ParentNotFound,
+            ));
+            return;
+        };
         self.theorem_ref.set(theorem_ref).unwrap();
         theorem_ref.add_proof(self);
 
+        for var in &self.vars {
+            var.verify_structure(&self.system_id, self.serial(), index, errors, |e| {
+                ParsingError::ProofError(self, ProofParsingError::VariableError(var, e))
+            });
+        }
+
         // TODO: Make a TagIndex struct.
         let mut tags = HashMap::new();
         for element in &self.elements {
@@ -2452,7 +3589,21 @@ impl<'a> ProofBuilder<'a> {
         errors: &mut ParsingErrorContext<'a>,
     ) {
         let theorem = self.theorem_ref.get().unwrap();
-        let local_index = index.get_local(theorem.system_id(), theorem.vars());
+        let mut local_index = index.get_local(theorem.system_id(), self.serial(), theorem.vars());
+
+        for var in &self.vars {
+            if !local_index.add_variable(var) {
+                errors.err(ParsingError::ProofError(
+                    self,
+                    ProofParsingError::VariableIdTaken(var),
+                ));
+            }
+        }
+
+        // Each abbreviation can use the ones declared before it.
+        for abbreviation in &self.lets {
+            abbreviation.build(self, &mut local_index, errors);
+        }
 
         for (i, element) in self.elements.iter().enumerate() {
             element.build_formulas(self, &self.elements[..i], &local_index, errors);
@@ -2476,20 +3627,722 @@ impl<'a> ProofBuilder<'a> {
     pub fn finish<'b>(&self) -> ProofBlock<'b> {
         let theorem_location = self.theorem_ref.get().unwrap().location();
         let theorem_ref = TheoremBlockRef::new(theorem_location);
+        let vars = self.vars.iter().map(VariableBuilder::finish).collect();
         let elements = self
             .elements
             .iter()
             .map(ProofBuilderElement::finish)
             .collect();
 
-        ProofBlock::new(theorem_ref, elements)
+        ProofBlock::new(self.file_location.clone(), theorem_ref, vars, elements)
     }
 
     pub fn system_id(&self) -> &str {
         &self.system_id
     }
 
+    pub fn theorem_id(&self) -> &str {
+        &self.theorem_id
+    }
+
     pub fn theorem_name(&self) -> &str {
         self.theorem_ref.get().unwrap().name()
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{check_page, page_diagnostics};
+
+    const IMPLICATION: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+
+\Symbol implies : sys {
+  name = "Implies" tagline = {Implies.}
+  type = Prop -> Prop -> Prop
+  read = Infix ->
+}
+
+\Definition or : sys {
+  name = "Or" tagline = {Or.}
+  inputs = [ p : Prop, q : Prop ]
+  read = Infix |
+  expanded = ('p -> 'q) -> 'q;
+}
+
+\Axiom mp : sys {
+  name = "MP" tagline = {MP.}
+  var p : Prop
+  var q : Prop
+  premise = [ 'p; 'p -> 'q; ]
+  assertion = 'q;
+}
+
+\Axiom ax_k : sys {
+  name = "K" tagline = {K.}
+  var p : Prop
+  var q : Prop
+  assertion = 'p -> 'q -> 'p;
+}
+
+\Axiom ax_k_apart : sys {
+  name = "K apart" tagline = {K apart.}
+  var p : Prop
+  var q : Prop
+  distinct = [ 'p, 'q ]
+  assertion = 'p -> 'q -> 'p;
+}
+"#;
+
+    fn theorem(premise: &str, assertion: &str, proof: &str) -> String {
+        format!(
+            r#"{}
+\Theorem t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Prop
+  var b : Prop
+  var n : Nat
+  distinct = [ 'a, 'b ]
+  premise = [ {} ]
+  assertion = {};
+}}
+
+\Proof t : sys {{
+  {}
+}}
+"#,
+            IMPLICATION, premise, assertion, proof
+        )
+    }
+
+    fn prove(premise: &str, assertion: &str, proof: &str) -> Vec<&'static str> {
+        check_page(&theorem(premise, assertion, proof))
+    }
+
+    // The message of the only error in the proof.
+    fn explain(premise: &str, assertion: &str, proof: &str) -> String {
+        let diagnostics = page_diagnostics(&theorem(premise, assertion, proof));
+        assert_eq!(diagnostics.len(), 1);
+
+        diagnostics[0].message.clone()
+    }
+
+    #[test]
+    fn proof_errors() {
+        assert!(prove(
+            "'a;",
+            "'b -> 'a",
+            "|1| 'a; |ax_k| 'a -> 'b -> 'a; |mp| 'b -> 'a;"
+        )
+        .is_empty());
+
+        assert_eq!(prove("'a;", "'a", ""), ["proof-empty"]);
+        assert_eq!(
+            prove("'a;", "'b -> 'a", "|1| 'a;"),
+            ["proof-assertion-mismatch"]
+        );
+        assert_eq!(prove("'a;", "'b", "|1| 'b;"), ["step-hypothesis-mismatch"]);
+        assert_eq!(
+            prove("", "'a -> 'b", "|ax_k| 'a -> 'b;"),
+            ["step-not-substitutable"]
+        );
+        assert_eq!(prove("", "'a", "|mp| 'a;"), ["step-hypotheses-not-found"]);
+    }
+
+    #[test]
+    fn definitions() {
+        assert!(prove(
+            "'a | 'b;",
+            "('a -> 'b) -> 'b",
+            "|1| 'a | 'b; |!def| ('a -> 'b) -> 'b;"
+        )
+        .is_empty());
+        assert_eq!(
+            prove(
+                "'a | 'b;",
+                "('b -> 'a) -> 'a",
+                "|1| 'a | 'b; |!def| ('b -> 'a) -> 'a;"
+            ),
+            ["step-definition-mismatch"]
+        );
+    }
+
+    #[test]
+    fn distinct() {
+        assert!(prove("", "'a -> 'b -> 'a", "|ax_k_apart| 'a -> 'b -> 'a;").is_empty());
+        assert_eq!(
+            prove("", "'a -> 'a -> 'a", "|ax_k_apart| 'a -> 'a -> 'a;"),
+            ["step-not-distinct"]
+        );
+    }
+
+    #[test]
+    fn unification_failures() {
+        // The first pair of subformulas which can't be matched.
+        assert!(explain("", "'a -> 'b", "|ax_k| 'a -> 'b;")
+            .ends_with("`q → p` in the justification can't be matched with `b`."));
+
+        // A variable matched against two different formulas.
+        assert!(explain("", "'a -> 'b -> 'b", "|ax_k| 'a -> 'b -> 'b;")
+            .ends_with("`p` would have to be replaced by both `a` and `b`."));
+
+        // The hypothesis for which no earlier step fits.
+        assert!(explain("", "'a", "|mp| 'a;").ends_with("no earlier step matches hypothesis 1."));
+        assert!(explain("'a;", "'b", "|1| 'a; |mp| 'b;")
+            .ends_with("no earlier step matches hypothesis 2."));
+    }
+
+    #[test]
+    fn premises() {
+        // Premises are cited by hypothesis number or by tag, in the order of the hypotheses.
+        assert!(prove(
+            "'a;",
+            "'b -> 'a",
+            "|#k, ax_k| 'a -> 'b -> 'a; |mp(1, #k)| 'b -> 'a;"
+        )
+        .is_empty());
+
+        // A tag outside of the parentheses names the step, even after an axiom or theorem.
+        assert!(prove(
+            "'a;",
+            "'b -> 'a",
+            "|ax_k, #k| 'a -> 'b -> 'a; |mp(1, #k)| 'b -> 'a;"
+        )
+        .is_empty());
+        assert_eq!(
+            prove(
+                "'a;",
+                "'b -> 'a",
+                "|#k, ax_k| 'a -> 'b -> 'a; |mp(#k, 1)| 'b -> 'a;"
+            ),
+            ["step-hypotheses-not-found"]
+        );
+
+        assert_eq!(
+            prove(
+                "'a;",
+                "'b -> 'a",
+                "|ax_k| 'a -> 'b -> 'a; |mp(1)| 'b -> 'a;"
+            ),
+            ["step-premise-count-mismatch"]
+        );
+        assert!(explain(
+            "'a;",
+            "'b -> 'a",
+            "|ax_k| 'a -> 'b -> 'a; |mp(1)| 'b -> 'a;"
+        )
+        .ends_with("cites 1 premise, but `mp` has 2 hypotheses."));
+        assert!(explain(
+            "'a;",
+            "'b -> 'a",
+            "|ax_k| 'a -> 'b -> 'a; |mp(1, 1, 1)| 'b -> 'a;"
+        )
+        .ends_with("cites 3 premises, but `mp` has 2 hypotheses."));
+        assert_eq!(
+            prove(
+                "'a;",
+                "'b -> 'a",
+                "|ax_k| 'a -> 'b -> 'a; |mp(1, #k)| 'b -> 'a;"
+            ),
+            ["step-premise-not-found"]
+        );
+        assert_eq!(
+            prove(
+                "'a;",
+                "'b -> 'a",
+                "|mp(1, #k)| 'b -> 'a; |#k, ax_k| 'a -> 'b -> 'a;"
+            ),
+            ["step-premise-not-before"]
+        );
+    }
+
+    #[test]
+    fn substitutions() {
+        let steps = |last: &str| format!("|1| 'a; |2| 'a -> 'b; {}", last);
+
+        // A variable which only appears in the hypotheses can be given explicitly.
+        assert!(prove("'a; 'a -> 'b;", "'b", &steps("|mp| 'b with 'p := 'a;")).is_empty());
+        assert_eq!(
+            prove("'a; 'a -> 'b;", "'b", &steps("|mp| 'b with 'p := 'b;")),
+            ["step-hypotheses-not-found"]
+        );
+
+        assert_eq!(
+            prove("'a; 'a -> 'b;", "'b", &steps("|mp| 'b with 'r := 'a;")),
+            ["step-substitution-variable-not-found"]
+        );
+        assert_eq!(
+            prove(
+                "'a; 'a -> 'b;",
+                "'b",
+                &steps("|mp| 'b with 'p := 'a, 'p := 'a;")
+            ),
+            ["step-substitution-duplicate"]
+        );
+        assert_eq!(
+            prove("'a; 'a -> 'b;", "'b", &steps("|mp| 'b with 'p := 'n;")),
+            ["step-substitution-type-mismatch"]
+        );
+        assert_eq!(
+            prove("'a; 'a -> 'b;", "'a", "|1| 'a with 'p := 'a;"),
+            ["step-substitution-without-deductable"]
+        );
+    }
+
+    // A proof in the system `child`, which needs the axioms and types of `sys`. The systems
+    // are declared after those of `IMPLICATION`.
+    fn extend(systems: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+{}
+
+\Theorem t : child {{
+  name = "T" tagline = {{T.}}
+  var a : Prop
+  var b : Prop
+  premise = [ 'a; ]
+  assertion = 'b -> 'a;
+}}
+
+\Proof t : child {{
+  |1| 'a; |ax_k| 'a -> 'b -> 'a; |mp| 'b -> 'a;
+}}
+"#,
+            IMPLICATION, systems
+        ))
+    }
+
+    #[test]
+    fn extends() {
+        let child = |entries: &str| {
+            format!(
+                r#"\System child {{ name = "C" tagline = {{C.}} {} }}"#,
+                entries
+            )
+        };
+        let mid = r#"\System mid { name = "M" tagline = {M.} extends = [ sys ] }"#;
+
+        assert!(extend(&child("extends = [ sys ]")).is_empty());
+        assert!(extend(&format!("{}\n{}", mid, child("extends = [ mid ]"))).is_empty());
+        assert!(extend(&child("")).contains(&"step-justification-not-found"));
+
+        assert!(extend(&child("extends = [ nope ]")).contains(&"system-extends-not-found"));
+        assert_eq!(
+            extend(&format!("{}\n{}", child("extends = [ mid ]"), mid)),
+            ["system-extends-forward-reference"]
+        );
+
+        let codes = extend(&child("extends = [ child ]"));
+        assert!(codes.contains(&"system-extends-itself"));
+        assert!(!codes.contains(&"system-extends-forward-reference"));
+
+        assert_eq!(
+            extend(&child("extends = [ sys ] extends = [ sys ]")),
+            ["system-duplicate-extends"]
+        );
+    }
+
+    // A proof in the system `child`, which extends `sys` but not its sibling `other`.
+    fn qualify(assertion: &str, proof: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\System other {{ name = "O" tagline = {{O.}} extends = [ sys ] }}
+
+\Symbol top : other {{
+  name = "Top" tagline = {{Top.}}
+  type = Prop
+}}
+
+\Axiom ax_i : other {{
+  name = "I" tagline = {{I.}}
+  var p : Prop
+  assertion = 'p -> 'p;
+}}
+
+\System child {{ name = "C" tagline = {{C.}} extends = [ sys ] }}
+
+\Theorem t : child {{
+  name = "T" tagline = {{T.}}
+  var a : Prop
+  var b : Prop
+  premise = [ 'a; ]
+  assertion = {};
+}}
+
+\Proof t : child {{
+  {}
+}}
+"#,
+            IMPLICATION, assertion, proof
+        ))
+    }
+
+    #[test]
+    fn qualified_references() {
+        let proof = "|1| 'a; |sys.ax_k| 'a -> 'b -> 'a; |sys.mp| sys.implies('b, 'a);";
+        assert!(qualify("'b -> 'a", proof).is_empty());
+        assert!(qualify("sys.implies('b, 'a)", proof).is_empty());
+
+        assert_eq!(
+            qualify("'a -> 'a", "|other.ax_i| 'a -> 'a;"),
+            ["step-justification-system-not-extended"]
+        );
+        assert_eq!(
+            qualify("'a -> 'a", "|sys.ax_i| 'a -> 'a;"),
+            ["step-justification-not-found"]
+        );
+        assert_eq!(
+            qualify("'a -> other.top", "|1| 'a; |ax_k| 'a -> 'b -> 'a;"),
+            ["formula-symbol-system-not-extended"]
+        );
+    }
+
+    #[test]
+    fn proof_variables() {
+        assert!(prove(
+            "'a;",
+            "'a",
+            "var c : Prop |1| 'a; |ax_k| 'a -> 'c -> 'a; |mp| 'c -> 'a; |1| 'a;"
+        )
+        .is_empty());
+
+        // A variable declared by the proof is distinct from every other variable, but not from
+        // itself.
+        assert!(prove(
+            "",
+            "'a -> 'b -> 'a",
+            "var c : Prop |ax_k_apart| 'a -> 'c -> 'a; |ax_k_apart| 'a -> 'b -> 'a;"
+        )
+        .is_empty());
+        assert_eq!(
+            prove(
+                "",
+                "'a -> 'b -> 'a",
+                "var c : Prop |ax_k_apart| 'c -> 'c -> 'c; |ax_k| 'a -> 'b -> 'a;"
+            ),
+            ["step-not-distinct"]
+        );
+
+        assert_eq!(
+            prove("", "'a -> 'b -> 'a", "var a : Prop |ax_k| 'a -> 'b -> 'a;"),
+            ["proof-variable-taken"]
+        );
+        assert_eq!(
+            prove("", "'a -> 'b -> 'a", "var c : Nope |ax_k| 'a -> 'b -> 'a;"),
+            ["type-signature-id-not-found"]
+        );
+    }
+
+    #[test]
+    fn abbreviations() {
+        assert!(prove(
+            "'a;",
+            "'b -> 'a",
+            "let 'd := 'b -> 'a; |1| 'a; |ax_k| 'a -> 'd; |mp| 'd;"
+        )
+        .is_empty());
+
+        let proof = "|1| 'a; |ax_k| 'a -> 'b -> 'a; |mp| 'b -> 'a;";
+        assert_eq!(
+            prove("'a;", "'b -> 'a", &format!("let 'a := 'b; {}", proof)),
+            ["proof-let-taken"]
+        );
+        assert_eq!(
+            prove("'a;", "'b -> 'a", &format!("let 'd := 'z; {}", proof)),
+            ["formula-variable-not-found"]
+        );
+    }
+
+    const ORDERS: &str = r#"
+\System sys { name = "S" tagline = {Sys.} }
+
+\Type Nat : sys { name = "Nat" tagline = {Nats.} }
+\Type Prop : sys { name = "Prop" tagline = {Props.} }
+
+\Symbol eq : sys {
+  name = "Eq" tagline = {Eq.}
+  type = Nat -> Nat -> Prop
+  read = Infix = 30 none
+}
+
+\Symbol le : sys {
+  name = "Le" tagline = {Le.}
+  type = Nat -> Nat -> Prop
+  read = Infix <= 30 none
+}
+
+\Symbol lt : sys {
+  name = "Lt" tagline = {Lt.}
+  type = Nat -> Nat -> Prop
+  read = Infix < 30 none
+}
+
+\Symbol plus : sys {
+  name = "Plus" tagline = {Plus.}
+  type = Nat -> Nat -> Nat
+  read = Infix + 50 left
+}
+
+\Symbol minus : sys {
+  name = "Minus" tagline = {Minus.}
+  type = Nat -> Nat -> Nat
+  read = Infix - 50 left
+}
+
+\Symbol times : sys {
+  name = "Times" tagline = {Times.}
+  type = Nat -> Nat -> Nat
+  read = Infix * 60 left
+}
+
+\Symbol succ : sys {
+  name = "Succ" tagline = {Succ.}
+  type = Nat -> Nat
+}
+
+\Axiom eq_refl : sys {
+  name = "ER" tagline = {ER.}
+  flags = [reflexive]
+  var a : Nat
+  assertion = 'a = 'a;
+}
+
+\Axiom eq_trans : sys {
+  name = "ET" tagline = {ET.}
+  flags = [transitive]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ 'a = 'b; 'b = 'c; ]
+  assertion = 'a = 'c;
+}
+
+\Axiom lt_trans : sys {
+  name = "LT" tagline = {LT.}
+  flags = [transitive]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  premise = [ 'a < 'b; 'b < 'c; ]
+  assertion = 'a < 'c;
+}
+
+\Axiom plus_fun : sys {
+  name = "PF" tagline = {PF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ 'a = 'b; 'c = 'd; ]
+  assertion = 'a + 'c = 'b + 'd;
+}
+
+\Axiom succ_fun : sys {
+  name = "SF" tagline = {SF.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  premise = [ 'a = 'b; ]
+  assertion = succ('a) = succ('b);
+}
+
+\Axiom minus_lt : sys {
+  name = "ML" tagline = {ML.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ 'a < 'b; 'd < 'c; ]
+  assertion = 'a - 'c < 'b - 'd;
+}
+
+\Axiom times_lt : sys {
+  name = "TL" tagline = {TL.}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ 'a < 'b; 'c = 'd; ]
+  assertion = 'a * 'c < 'b * 'd;
+}
+"#;
+
+    // A theorem about the naturals 'a, 'b and 'c, and the proposition 'p.
+    fn ordered(premise: &str, assertion: &str, proof: &str) -> String {
+        format!(
+            r#"{}
+\Theorem t : sys {{
+  name = "T" tagline = {{T.}}
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var p : Prop
+  premise = [ {} ]
+  assertion = {};
+}}
+
+\Proof t : sys {{
+  {}
+}}
+"#,
+            ORDERS, premise, assertion, proof
+        )
+    }
+
+    fn order(premise: &str, assertion: &str, proof: &str) -> Vec<&'static str> {
+        check_page(&ordered(premise, assertion, proof))
+    }
+
+    #[test]
+    fn relation_macros() {
+        // A strict order can be substituted into without being reflexive.
+        assert!(order(
+            "'a < 'b; 'b < 'c;",
+            "'a < 'c",
+            "|1| 'a < 'b; |2| 'b < 'c; |!sub| 'a < 'c;"
+        )
+        .is_empty());
+        assert!(order(
+            "'a = 'b; 'b = 'c;",
+            "'a + 'a = 'c + 'a",
+            "|1| 'a = 'b; |2| 'b = 'c; |!fun| 'a + 'a = 'b + 'a; |!sub| 'a + 'a = 'c + 'a;"
+        )
+        .is_empty());
+
+        assert_eq!(
+            order("'p;", "'p", "|1| 'p; |!fun| 'p;"),
+            ["step-macro-not-binary"]
+        );
+        assert_eq!(
+            order("'a < 'b;", "'a = 'c", "|1| 'a < 'b; |!sub| 'a = 'c;"),
+            ["step-macro-substitution"]
+        );
+    }
+
+    #[test]
+    fn missing_flags() {
+        let diagnostics = page_diagnostics(&ordered(
+            "'a <= 'b; 'b <= 'c;",
+            "'a <= 'c",
+            "|1| 'a <= 'b; |2| 'b <= 'c; |!sub| 'a <= 'c;",
+        ));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "step-macro-missing-flag");
+        assert!(diagnostics[0].message.ends_with(
+            "this needs `le` to be flagged `transitive`, and no axiom or theorem claims this."
+        ));
+
+        let diagnostics = page_diagnostics(&ordered("", "'a < 'a", "|!fun| 'a < 'a;"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "step-macro-missing-flag");
+        assert!(diagnostics[0].message.ends_with(
+            "this needs `lt` to be flagged `reflexive`, and no axiom or theorem claims this."
+        ));
+    }
+
+    // The message of the only error in a proof which relates `left` and `right` by `!fun`.
+    fn apply_functions(premise: &str, left: &str, relation: &str, right: &str) -> String {
+        let assertion = format!("{} {} {}", left, relation, right);
+        let proof = format!("|!fun| {};", assertion);
+
+        let diagnostics = page_diagnostics(&ordered(premise, &assertion, &proof));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "step-macro-function-application");
+
+        diagnostics[0].message.clone()
+    }
+
+    #[test]
+    fn function_application_failures() {
+        assert!(apply_functions("'a < 'b;", "'a + 'c", "<", "'b + 'c").ends_with(
+            "this needs `'a + 'c` and `'b + 'c` to be related by `lt`: no axiom or theorem flags \
+             `plus` as a `function` for `lt`."
+        ));
+        assert!(apply_functions("", "'a + 'b", "=", "succ('a)").ends_with(
+            "this needs `'a + 'b` and `succ('a)` to be related by `eq`: they apply different \
+             functions, `plus` and `succ`."
+        ));
+
+        // The error is about the innermost pair which couldn't be related.
+        assert!(
+            apply_functions("", "'a + succ('a)", "=", "'a + succ('b)").ends_with(
+                "this needs `'a` and `'b` to be related by `eq`: they aren't both function \
+             applications and no previous step relates them."
+            )
+        );
+        assert!(order(
+            "'a = 'b;",
+            "'a + succ('a) = 'a + succ('b)",
+            "|1| 'a = 'b; |!fun| 'a + succ('a) = 'a + succ('b);"
+        )
+        .is_empty());
+    }
+
+    // A function claim about `*`, with the given premise.
+    fn claim(premise: &str) -> Vec<&'static str> {
+        check_page(&format!(
+            r#"{}
+\Axiom claim : sys {{
+  name = "C" tagline = {{C.}}
+  flags = [function]
+  var a : Nat
+  var b : Nat
+  var c : Nat
+  var d : Nat
+  premise = [ {} ]
+  assertion = 'a * 'b = 'c * 'd;
+}}
+"#,
+            ORDERS, premise
+        ))
+    }
+
+    #[test]
+    fn input_relations() {
+        // `-` is antitone in its second input, so that input is related the other way around.
+        assert!(order(
+            "'a < 'b;",
+            "'a - 'b < 'b - 'a",
+            "|1| 'a < 'b; |!fun| 'a - 'b < 'b - 'a;"
+        )
+        .is_empty());
+        assert_eq!(
+            order(
+                "'b < 'a;",
+                "'a - 'b < 'b - 'a",
+                "|1| 'b < 'a; |!fun| 'a - 'b < 'b - 'a;"
+            ),
+            ["step-macro-function-application"]
+        );
+
+        // The second input of `*` only needs to be equal on both sides.
+        assert!(order(
+            "'a < 'b;",
+            "'a * 'c < 'b * 'c",
+            "|1| 'a < 'b; |!fun| 'a * 'c < 'b * 'c;"
+        )
+        .is_empty());
+        assert_eq!(
+            order(
+                "'a < 'b;",
+                "'c * 'a < 'c * 'b",
+                "|1| 'a < 'b; |!fun| 'c * 'a < 'c * 'b;"
+            ),
+            ["step-macro-missing-flag"]
+        );
+
+        // Each hypothesis of the claim relates one input, in either direction.
+        assert!(claim("'a < 'c; 'd = 'b;").is_empty());
+        assert_eq!(claim("'a < 'd; 'b < 'c;"), ["flag-function-hypothesis"]);
+    }
 }
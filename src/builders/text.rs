@@ -80,6 +80,8 @@ impl BareText {
 
 #[derive(Debug)]
 pub struct CitationBuilder<'a> {
+    file_location: FileLocation,
+
     bib_key: String,
 
     bib_ref: OnceCell<&'a BibliographyBuilderEntry>,
@@ -87,12 +89,16 @@ pub struct CitationBuilder<'a> {
 }
 
 impl<'a> CitationBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
         assert_eq!(pair.as_rule(), Rule::citation);
 
+        let file_location = FileLocation::new(path, pair.as_span());
+
         let bib_key = pair.into_inner().next().unwrap().as_str().to_owned();
 
         CitationBuilder {
+            file_location,
+
             bib_key,
 
             bib_ref: OnceCell::new(),
@@ -124,10 +130,18 @@ impl<'a> CitationBuilder<'a> {
         }
     }
 
+    pub fn bib_key(&self) -> &str {
+        &self.bib_key
+    }
+
     fn bib_ref(&self) -> &BibliographyBuilderEntry {
         self.bib_ref.get().unwrap()
     }
 
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     fn set_local_bib_ref(&self, index: &HashMap<&BibliographyBuilderEntry, usize>) {
         let local_bib_index = *index.get(self.bib_ref.get().unwrap()).unwrap();
         self.local_bib_index.set(local_bib_index).unwrap();
@@ -140,6 +154,8 @@ impl<'a> CitationBuilder<'a> {
 
 #[derive(Debug)]
 pub struct HyperlinkBuilder {
+    file_location: FileLocation,
+
     url: String,
     contents: BareText,
 
@@ -147,14 +163,17 @@ pub struct HyperlinkBuilder {
 }
 
 impl HyperlinkBuilder {
-    fn from_pest(pair: Pair<Rule>) -> HyperlinkBuilder {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> HyperlinkBuilder {
         assert_eq!(pair.as_rule(), Rule::hyperlink);
 
+        let file_location = FileLocation::new(path, pair.as_span());
         let mut inner = pair.into_inner();
         let url = inner.next().unwrap().as_str().to_owned();
         let contents = BareText::from_pest(inner.next().unwrap());
 
         HyperlinkBuilder {
+            file_location,
+
             url,
             contents,
 
@@ -170,7 +189,7 @@ impl HyperlinkBuilder {
             }
 
             Err(e) => {
-                errors.err(e);
+                errors.err(ParsingError::UrlError(self.file_location.clone(), e));
                 false
             }
         }
@@ -191,9 +210,13 @@ pub enum UnformattedBuilderElement {
 }
 
 impl UnformattedBuilderElement {
-    fn from_pest(pair: Pair<Rule>, whitespace_rule: Rule) -> UnformattedBuilderElement {
+    fn from_pest(
+        path: &Path,
+        pair: Pair<Rule>,
+        whitespace_rule: Rule,
+    ) -> UnformattedBuilderElement {
         match pair.as_rule() {
-            Rule::hyperlink => Self::Hyperlink(HyperlinkBuilder::from_pest(pair)),
+            Rule::hyperlink => Self::Hyperlink(HyperlinkBuilder::from_pest(path, pair)),
 
             _ => Self::BareElement(BareElement::from_pest(pair, whitespace_rule)),
         }
@@ -221,12 +244,12 @@ struct UnformattedBuilder {
 }
 
 impl UnformattedBuilder {
-    fn from_pest(pair: Pair<Rule>) -> UnformattedBuilder {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> UnformattedBuilder {
         assert_eq!(pair.as_rule(), Rule::unformatted);
 
         let elements = pair
             .into_inner()
-            .map(|pair| UnformattedBuilderElement::from_pest(pair, Rule::oneline_whitespace))
+            .map(|pair| UnformattedBuilderElement::from_pest(path, pair, Rule::oneline_whitespace))
             .collect();
 
         UnformattedBuilder { elements }
@@ -269,7 +292,7 @@ pub struct RawCitationContainerBuilder {
 }
 
 impl RawCitationContainerBuilder {
-    fn from_pest(pairs: Pairs<Rule>) -> RawCitationContainerBuilder {
+    fn from_pest(path: &Path, pairs: Pairs<Rule>) -> RawCitationContainerBuilder {
         let mut container_titles = Vec::with_capacity(1);
         let mut other_contributors = Vec::with_capacity(1);
         let mut versions = Vec::with_capacity(1);
@@ -282,46 +305,49 @@ impl RawCitationContainerBuilder {
             match pair.as_rule() {
                 Rule::raw_citation_container_title => {
                     let container_title =
-                        UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     container_titles.push(container_title);
                 }
 
                 Rule::raw_citation_other_contributors => {
                     let other_contributor =
-                        UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     other_contributors.push(other_contributor);
                 }
 
                 Rule::raw_citation_version => {
-                    let version = UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                    let version =
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     versions.push(version);
                 }
 
                 Rule::raw_citation_number => {
-                    let number = UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                    let number =
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     numbers.push(number);
                 }
 
                 Rule::raw_citation_publisher => {
                     let publisher =
-                        UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     publishers.push(publisher);
                 }
 
                 Rule::raw_citation_publication_date => {
                     let publication_date =
-                        UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     publication_dates.push(publication_date);
                 }
 
                 Rule::raw_citation_location => {
-                    let location = UnformattedBuilder::from_pest(pair.into_inner().next().unwrap());
+                    let location =
+                        UnformattedBuilder::from_pest(path, pair.into_inner().next().unwrap());
 
                     locations.push(location);
                 }
@@ -557,7 +583,7 @@ pub struct RawCitationBuilder {
 }
 
 impl RawCitationBuilder {
-    pub fn from_pest_entries(pairs: Pairs<Rule>) -> RawCitationBuilder {
+    pub fn from_pest_entries(path: &Path, pairs: Pairs<Rule>) -> RawCitationBuilder {
         let mut authors = Vec::with_capacity(1);
         let mut titles = Vec::with_capacity(1);
         let mut containers = Vec::new();
@@ -565,14 +591,16 @@ impl RawCitationBuilder {
         for pair in pairs {
             match pair.as_rule() {
                 Rule::raw_citation_authors => authors.push(UnformattedBuilder::from_pest(
+                    path,
                     pair.into_inner().next().unwrap(),
                 )),
                 Rule::raw_citation_title => titles.push(UnformattedBuilder::from_pest(
+                    path,
                     pair.into_inner().next().unwrap(),
                 )),
-                Rule::raw_citation_container => {
-                    containers.push(RawCitationContainerBuilder::from_pest(pair.into_inner()))
-                }
+                Rule::raw_citation_container => containers.push(
+                    RawCitationContainerBuilder::from_pest(path, pair.into_inner()),
+                ),
 
                 _ => unreachable!(),
             }
@@ -587,9 +615,9 @@ impl RawCitationBuilder {
         }
     }
 
-    pub fn from_pest(pair: Pair<Rule>) -> RawCitationBuilder {
+    pub fn from_pest(path: &Path, pair: Pair<Rule>) -> RawCitationBuilder {
         assert_eq!(pair.as_rule(), Rule::raw_citation);
-        Self::from_pest_entries(pair.into_inner())
+        Self::from_pest_entries(path, pair.into_inner())
     }
 
     pub fn verify_structure<'a, F>(
@@ -881,24 +909,6 @@ impl MathBuilderElement {
         }
     }
 
-    fn from_pest_formula(pair: Pair<Rule>) -> MathBuilderElement {
-        match pair.as_rule() {
-            Rule::primary_paren => Self::Fenced(MathBuilder::from_pest_formula(
-                pair.into_inner().next().unwrap(),
-            )),
-
-            Rule::read_operator => Self::Operator(
-                Self::map_operator(pair.into_inner().next().unwrap().as_rule()).to_owned(),
-            ),
-            Rule::ident => Self::Symbol(map_ident(pair.as_str()).to_owned()),
-            Rule::var => {
-                Self::Variable(map_ident(pair.into_inner().next().unwrap().as_str()).to_owned())
-            }
-
-            _ => unreachable!(),
-        }
-    }
-
     fn verify_structure<'a, F>(
         &'a self,
         errors: &mut ParsingErrorContext<'a>,
@@ -956,29 +966,6 @@ impl MathBuilder {
         }
     }
 
-    pub fn from_pest_formula(pair: Pair<Rule>) -> MathBuilder {
-        assert_eq!(pair.as_rule(), Rule::formula);
-
-        let elements = pair
-            .into_inner()
-            .flat_map(|pair| match pair.as_rule() {
-                Rule::prefix_list => {
-                    Box::new(pair.into_inner().map(MathBuilderElement::from_pest_formula))
-                        as Box<dyn Iterator<Item = MathBuilderElement>>
-                }
-
-                _ => Box::new(std::iter::once(MathBuilderElement::from_pest_formula(pair)))
-                    as Box<dyn Iterator<Item = MathBuilderElement>>,
-            })
-            .collect();
-
-        MathBuilder {
-            elements,
-
-            verified: Cell::new(false),
-        }
-    }
-
     pub fn verify_structure<'a, F>(
         &'a self,
         errors: &mut ParsingErrorContext<'a>,
@@ -1209,6 +1196,8 @@ impl<'a> std::fmt::Debug for SystemChildReferenceBuilder<'a> {
 }
 
 pub struct TagReferenceBuilder<'a> {
+    file_location: FileLocation,
+
     tag: String,
     text: Option<BareText>,
 
@@ -1216,12 +1205,16 @@ pub struct TagReferenceBuilder<'a> {
 }
 
 impl<'a> TagReferenceBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>, text: Option<BareText>) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>, text: Option<BareText>) -> Self {
         assert_eq!(pair.as_rule(), Rule::tag);
 
+        let file_location = FileLocation::new(path, pair.as_span());
+
         let tag = pair.into_inner().next().unwrap().as_str().to_owned();
 
         TagReferenceBuilder {
+            file_location,
+
             tag,
             text,
 
@@ -1261,6 +1254,14 @@ impl<'a> TagReferenceBuilder<'a> {
 
         ParagraphElement::Tag(text, step_ref)
     }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 impl<'a> std::fmt::Debug for TagReferenceBuilder<'a> {
@@ -1296,7 +1297,7 @@ impl<'a> ReferenceBuilder<'a> {
                 ref_pair,
                 Some(text),
             )),
-            Rule::tag => Self::Tag(TagReferenceBuilder::from_pest(ref_pair, Some(text))),
+            Rule::tag => Self::Tag(TagReferenceBuilder::from_pest(path, ref_pair, Some(text))),
 
             _ => unreachable!(),
         }
@@ -1311,7 +1312,7 @@ impl<'a> ReferenceBuilder<'a> {
             Rule::fqid => {
                 Self::SystemChild(SystemChildReferenceBuilder::from_pest(path, pair, None))
             }
-            Rule::tag => Self::Tag(TagReferenceBuilder::from_pest(pair, None)),
+            Rule::tag => Self::Tag(TagReferenceBuilder::from_pest(path, pair, None)),
 
             _ => unreachable!(),
         }
@@ -1385,6 +1386,14 @@ impl<'a> ReferenceBuilder<'a> {
             _ => None,
         }
     }
+
+    fn tag_reference(&'a self) -> Option<&'a TagReferenceBuilder<'a>> {
+        match self {
+            Self::Tag(tag) => Some(tag),
+
+            _ => None,
+        }
+    }
 }
 
 enum ParagraphFormattingState {
@@ -1517,14 +1526,18 @@ impl<'a> ParagraphBuilderElement<'a> {
         match pair.as_rule() {
             Rule::text_reference => Self::Reference(ReferenceBuilder::from_pest(path, pair)),
             Rule::math_row => Self::InlineMath(MathBuilder::from_pest(pair)),
-            Rule::citation => Self::Citation(CitationBuilder::from_pest(pair)),
+            Rule::citation => Self::Citation(CitationBuilder::from_pest(path, pair)),
 
             Rule::unicorn_vomit_begin => Self::UnicornVomitBegin,
             Rule::unicorn_vomit_end => Self::UnicornVomitEnd,
             Rule::em_begin => Self::EmBegin,
             Rule::em_end => Self::EmEnd,
 
-            _ => Self::Unformatted(UnformattedBuilderElement::from_pest(pair, whitespace_rule)),
+            _ => Self::Unformatted(UnformattedBuilderElement::from_pest(
+                path,
+                pair,
+                whitespace_rule,
+            )),
         }
     }
 
@@ -1625,10 +1638,28 @@ impl<'a> ParagraphBuilderElement<'a> {
             _ => None,
         }
     }
+
+    pub fn tag_reference(&'a self) -> Option<&'a TagReferenceBuilder<'a>> {
+        match self {
+            Self::Reference(r) => r.tag_reference(),
+
+            _ => None,
+        }
+    }
+
+    pub fn citation(&'a self) -> Option<&'a CitationBuilder<'a>> {
+        match self {
+            Self::Citation(citation) => Some(citation),
+
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ParagraphBuilder<'a> {
+    file_location: FileLocation,
+
     elements: Vec<ParagraphBuilderElement<'a>>,
 
     verified: Cell<bool>,
@@ -1643,12 +1674,16 @@ impl<'a> ParagraphBuilder<'a> {
             _ => unreachable!(),
         };
 
+        let file_location = FileLocation::new(path, pair.as_span());
+
         let elements = pair
             .into_inner()
             .map(|pair| ParagraphBuilderElement::from_pest(path, pair, whitespace_rule))
             .collect();
 
         ParagraphBuilder {
+            file_location,
+
             elements,
 
             verified: Cell::new(false),
@@ -1732,6 +1767,10 @@ impl<'a> ParagraphBuilder<'a> {
     pub fn get_element(&'a self, index: usize) -> &ParagraphBuilderElement {
         &self.elements[index]
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
 
 #[derive(Debug)]
@@ -1927,7 +1966,7 @@ impl<'a> TableBuilder<'a> {
 
         if let Some(caption) = &self.caption {
             caption.verify_structure(index, errors, |e| {
-                ParsingError::TableError(self, TableParsingError::CaptionError(e))
+                ParsingError::TableError(self, TableParsingError::CaptionError(caption, e))
             });
         }
     }
@@ -1981,6 +2020,8 @@ impl<'a> TableBuilder<'a> {
 
 #[derive(Debug)]
 struct QuoteValueBuilder<'a> {
+    file_location: FileLocation,
+
     bib_key: String,
     quote: UnformattedBuilder,
 
@@ -1989,14 +2030,18 @@ struct QuoteValueBuilder<'a> {
 }
 
 impl<'a> QuoteValueBuilder<'a> {
-    fn from_pest(pair: Pair<Rule>) -> Self {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> Self {
         assert!(pair.as_rule() == Rule::quote_value || pair.as_rule() == Rule::quote_original);
 
         let mut inner = pair.into_inner();
-        let bib_key = inner.next().unwrap().as_str().to_owned();
-        let quote = UnformattedBuilder::from_pest(inner.next().unwrap());
+        let bib_key_pair = inner.next().unwrap();
+        let file_location = FileLocation::new(path, bib_key_pair.as_span());
+        let bib_key = bib_key_pair.as_str().to_owned();
+        let quote = UnformattedBuilder::from_pest(path, inner.next().unwrap());
 
         QuoteValueBuilder {
+            file_location,
+
             bib_key,
             quote,
 
@@ -2020,10 +2065,18 @@ impl<'a> QuoteValueBuilder<'a> {
         }
     }
 
+    fn bib_key(&self) -> &str {
+        &self.bib_key
+    }
+
     fn bib_ref(&self) -> &BibliographyBuilderEntry {
         self.bib_ref.get().unwrap()
     }
 
+    fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
+
     fn set_local_bib_ref(&self, index: &HashMap<&BibliographyBuilderEntry, usize>) {
         let local_bib_ref = *index.get(self.bib_ref.get().unwrap()).unwrap();
         self.local_bib_ref.set(local_bib_ref).unwrap();
@@ -2046,7 +2099,7 @@ pub struct QuoteBuilder<'a> {
 }
 
 impl<'a> QuoteBuilder<'a> {
-    pub fn from_pest(pair: Pair<Rule>, location: BlockLocation) -> Self {
+    pub fn from_pest(path: &Path, pair: Pair<Rule>, location: BlockLocation) -> Self {
         assert_eq!(pair.as_rule(), Rule::quote_block);
 
         let mut inner = pair.into_inner();
@@ -2056,12 +2109,12 @@ impl<'a> QuoteBuilder<'a> {
             let original = curr;
             curr = inner.next().unwrap();
 
-            Some(QuoteValueBuilder::from_pest(original))
+            Some(QuoteValueBuilder::from_pest(path, original))
         } else {
             None
         };
 
-        let value = QuoteValueBuilder::from_pest(curr);
+        let value = QuoteValueBuilder::from_pest(path, curr);
 
         QuoteBuilder {
             location,
@@ -2108,6 +2161,22 @@ impl<'a> QuoteBuilder<'a> {
 
         QuoteBlock::new(original, value)
     }
+
+    pub fn original_bib_key(&self) -> Option<&str> {
+        self.original.as_ref().map(QuoteValueBuilder::bib_key)
+    }
+
+    pub fn original_file_location(&self) -> Option<&FileLocation> {
+        self.original.as_ref().map(QuoteValueBuilder::file_location)
+    }
+
+    pub fn value_bib_key(&self) -> &str {
+        self.value.bib_key()
+    }
+
+    pub fn value_file_location(&self) -> &FileLocation {
+        self.value.file_location()
+    }
 }
 
 pub struct TodoBuilder<'a> {
@@ -2169,13 +2238,13 @@ struct SubHeadingBuilder {
 }
 
 impl SubHeadingBuilder {
-    fn from_pest(pair: Pair<Rule>) -> SubHeadingBuilder {
+    fn from_pest(path: &Path, pair: Pair<Rule>) -> SubHeadingBuilder {
         assert_eq!(pair.as_rule(), Rule::subheading);
 
         let mut inner = pair.into_inner();
         let level = HeadingLevel::from_pest(inner.next().unwrap());
         let contents = inner
-            .map(|pair| UnformattedBuilderElement::from_pest(pair, Rule::heading_whitespace))
+            .map(|pair| UnformattedBuilderElement::from_pest(path, pair, Rule::heading_whitespace))
             .collect();
 
         SubHeadingBuilder { level, contents }
@@ -2204,12 +2273,12 @@ pub struct HeadingBuilder {
 }
 
 impl HeadingBuilder {
-    pub fn from_pest(pair: Pair<Rule>) -> HeadingBuilder {
+    pub fn from_pest(path: &Path, pair: Pair<Rule>) -> HeadingBuilder {
         assert_eq!(pair.as_rule(), Rule::heading_block);
 
         let subheadings = pair
             .into_inner()
-            .map(SubHeadingBuilder::from_pest)
+            .map(|pair| SubHeadingBuilder::from_pest(path, pair))
             .collect();
 
         HeadingBuilder { subheadings }
@@ -2250,7 +2319,7 @@ impl<'a> TextBuilder<'a> {
         let pair = pair.into_inner().next().unwrap();
 
         match pair.as_rule() {
-            Rule::raw_citation => Self::RawCitation(RawCitationBuilder::from_pest(pair)),
+            Rule::raw_citation => Self::RawCitation(RawCitationBuilder::from_pest(path, pair)),
             Rule::sublist => Self::Sublist(SublistBuilder::from_pest(pair)),
             Rule::display_math => Self::DisplayMath(DisplayMathBuilder::from_pest(pair)),
             Rule::paragraph => Self::Paragraph(ParagraphBuilder::from_pest(path, pair)),
@@ -2343,6 +2412,14 @@ impl<'a> TextBuilder<'a> {
             _ => None,
         }
     }
+
+    pub fn file_location(&self) -> Option<&FileLocation> {
+        match self {
+            Self::Paragraph(paragraph) => Some(paragraph.file_location()),
+
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -13,13 +13,26 @@
 // You should have received a copy of the GNU Affero General Public License along with ckproof. If
 // not, see <https://www.gnu.org/licenses/>.
 
+use super::language::{Formula, TypeSignature, Variable};
+
+// Why a formula couldn't be obtained from a deductable by substituting for its variables.
+#[derive(Clone, Debug)]
+pub enum SubstitutionError<'a> {
+    // The subformula of the deductable, then the subformula it was matched against.
+    Mismatch(Formula<'a>, Formula<'a>),
+    Conflict(&'a Variable<'a>, Formula<'a>, Formula<'a>),
+    TypeConflict(String, TypeSignature<'a>, TypeSignature<'a>),
+    HypothesisNotFound(usize),
+}
+
 #[derive(Debug)]
-pub enum CheckingError {
+pub enum CheckingError<'a> {
     EmptyProof,
     AssertionMismatch,
 
-    DeductableAssertionNotSubstitutable(usize),
-    DeductableNotSubstitutable(usize),
+    DeductableAssertionNotSubstitutable(usize, SubstitutionError<'a>),
+    DeductableNotSubstitutable(usize, SubstitutionError<'a>),
+    DeductableNotDistinct(usize),
 
     HypothesisMismatch(usize),
 
@@ -13,6 +13,8 @@
 // You should have received a copy of the GNU Affero General Public License along with ckproof. If
 // not, see <https://www.gnu.org/licenses/>.
 
+use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::lazy::OnceCell;
@@ -24,7 +26,7 @@ use super::system::{DeductableRef, ProofStep, System};
 #[derive(Debug)]
 pub struct Type<'a> {
     id: String,
-    system_ref: OnceCell<&'a System>,
+    system_ref: OnceCell<&'a System<'a>>,
 }
 
 impl<'a> Type<'a> {
@@ -35,9 +37,13 @@ impl<'a> Type<'a> {
         }
     }
 
-    pub fn set_system(&self, system: &'a System) {
+    pub fn set_system(&self, system: &'a System<'a>) {
         self.system_ref.set(system).unwrap();
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 impl<'a> PartialEq for Type<'a> {
@@ -50,6 +56,14 @@ impl<'a> Eq for Type<'a> {}
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum TypeSignature<'a> {
     Ground(&'a Type<'a>),
+
+    // A type parameter like `'A`. Each use of a symbol or definition can choose a different type
+    // for it, while within a deductable it stands for the same type throughout.
+    Variable(String),
+
+    // A type which hasn't been worked out yet while checking a formula.
+    Unknown(usize),
+
     Compound(Box<TypeSignature<'a>>, Box<TypeSignature<'a>>),
 }
 
@@ -62,15 +76,119 @@ impl<'a> TypeSignature<'a> {
         }
     }
 
-    fn input(&self) -> Option<&TypeSignature<'a>> {
-        self.compound().map(|(input, _)| input)
+    fn inputs(&self) -> impl Iterator<Item = &TypeSignature<'a>> {
+        let mut curr = self;
+
+        std::iter::from_fn(move || {
+            let (input, output) = curr.compound()?;
+            curr = output;
+
+            Some(input)
+        })
     }
 
-    fn apply(self) -> Option<TypeSignature<'a>> {
+    // Replaces each type variable by the type it stands for, if that's known.
+    pub fn substitute(&self, types: &HashMap<String, TypeSignature<'a>>) -> TypeSignature<'a> {
         match self {
-            Self::Compound(_, output) => Some(*output),
+            Self::Variable(id) => match types.get(id) {
+                Some(ty) => ty.clone(),
+                None => self.clone(),
+            },
+            Self::Ground(_) | Self::Unknown(_) => self.clone(),
+
+            Self::Compound(input, output) => Self::Compound(
+                Box::new(input.substitute(types)),
+                Box::new(output.substitute(types)),
+            ),
+        }
+    }
+}
 
-            _ => None,
+// Works out the types of a formula, where every use of a symbol or definition gets its own copy
+// of its type variables.
+#[derive(Default)]
+struct TypeInference<'a> {
+    unknowns: Vec<Option<TypeSignature<'a>>>,
+}
+
+impl<'a> TypeInference<'a> {
+    fn fresh(&mut self) -> TypeSignature<'a> {
+        self.unknowns.push(None);
+
+        TypeSignature::Unknown(self.unknowns.len() - 1)
+    }
+
+    fn instantiate(
+        &mut self,
+        type_signature: &TypeSignature<'a>,
+        fresh: &mut HashMap<String, TypeSignature<'a>>,
+    ) -> TypeSignature<'a> {
+        match type_signature {
+            TypeSignature::Variable(id) => match fresh.get(id) {
+                Some(unknown) => unknown.clone(),
+
+                None => {
+                    let unknown = self.fresh();
+                    fresh.insert(id.clone(), unknown.clone());
+
+                    unknown
+                }
+            },
+            TypeSignature::Ground(_) | TypeSignature::Unknown(_) => type_signature.clone(),
+
+            TypeSignature::Compound(input, output) => TypeSignature::Compound(
+                Box::new(self.instantiate(input, fresh)),
+                Box::new(self.instantiate(output, fresh)),
+            ),
+        }
+    }
+
+    fn resolve(&self, type_signature: &TypeSignature<'a>) -> TypeSignature<'a> {
+        match type_signature {
+            TypeSignature::Unknown(i) => match &self.unknowns[*i] {
+                Some(solution) => self.resolve(solution),
+                None => type_signature.clone(),
+            },
+            TypeSignature::Ground(_) | TypeSignature::Variable(_) => type_signature.clone(),
+
+            TypeSignature::Compound(input, output) => TypeSignature::Compound(
+                Box::new(self.resolve(input)),
+                Box::new(self.resolve(output)),
+            ),
+        }
+    }
+
+    fn occurs(&self, i: usize, type_signature: &TypeSignature<'a>) -> bool {
+        match self.resolve(type_signature) {
+            TypeSignature::Unknown(j) => i == j,
+            TypeSignature::Ground(_) | TypeSignature::Variable(_) => false,
+
+            TypeSignature::Compound(input, output) => {
+                self.occurs(i, &input) || self.occurs(i, &output)
+            }
+        }
+    }
+
+    // Type variables which aren't unknowns belong to the formula being checked, so they only
+    // equal themselves.
+    fn unify(&mut self, left: &TypeSignature<'a>, right: &TypeSignature<'a>) -> bool {
+        match (self.resolve(left), self.resolve(right)) {
+            (TypeSignature::Unknown(i), TypeSignature::Unknown(j)) if i == j => true,
+            (TypeSignature::Unknown(i), other) | (other, TypeSignature::Unknown(i)) => {
+                if self.occurs(i, &other) {
+                    return false;
+                }
+
+                self.unknowns[i] = Some(other);
+                true
+            }
+
+            (
+                TypeSignature::Compound(left_input, left_output),
+                TypeSignature::Compound(right_input, right_output),
+            ) => self.unify(&left_input, &right_input) && self.unify(&left_output, &right_output),
+
+            (left, right) => left == right,
         }
     }
 }
@@ -78,7 +196,7 @@ impl<'a> TypeSignature<'a> {
 #[derive(Debug)]
 pub struct Symbol<'a> {
     id: String,
-    system_ref: OnceCell<&'a System>,
+    system_ref: OnceCell<&'a System<'a>>,
 
     type_signature: OnceCell<TypeSignature<'a>>,
 }
@@ -93,7 +211,7 @@ impl<'a> Symbol<'a> {
         }
     }
 
-    pub fn set_system(&self, system: &'a System) {
+    pub fn set_system(&self, system: &'a System<'a>) {
         self.system_ref.set(system).unwrap();
     }
 
@@ -116,7 +234,7 @@ impl<'a> Eq for Symbol<'a> {}
 #[derive(Debug)]
 pub struct Definition<'a> {
     id: String,
-    system_ref: OnceCell<&'a System>,
+    system_ref: OnceCell<&'a System<'a>>,
 
     inputs: OnceCell<Vec<&'a Variable<'a>>>,
     expanded: OnceCell<Formula<'a>>,
@@ -133,7 +251,7 @@ impl<'a> Definition<'a> {
         }
     }
 
-    pub fn set_system(&self, system_ref: &'a System) {
+    pub fn set_system(&self, system_ref: &'a System<'a>) {
         self.system_ref.set(system_ref).unwrap();
     }
 
@@ -153,21 +271,37 @@ impl<'a> Definition<'a> {
         self.expanded.get().unwrap().verify()
     }
 
-    fn inputs_match(&self, replacements: &[Formula<'a>]) -> bool {
+    // The type variables of the inputs are shared with the expanded formula, so they're
+    // instantiated together.
+    fn infer(
+        &self,
+        replacements: &[Formula<'a>],
+        bound: &mut Vec<TypeSignature<'a>>,
+        inference: &mut TypeInference<'a>,
+    ) -> Option<TypeSignature<'a>> {
         let inputs = self.inputs.get().unwrap();
+        if inputs.len() != replacements.len() {
+            return None;
+        }
 
-        inputs.len() == replacements.len()
-            && inputs
-                .iter()
-                .zip(replacements)
-                .all(|(input, replacement)| input.type_signature() == &replacement.type_signature())
+        let mut fresh = HashMap::new();
+        for (input, replacement) in inputs.iter().zip(replacements) {
+            let expected = inference.instantiate(input.type_signature(), &mut fresh);
+            let found = replacement.infer(bound, inference)?;
+
+            if !inference.unify(&expected, &found) {
+                return None;
+            }
+        }
+
+        Some(inference.instantiate(&self.type_signature(), &mut fresh))
     }
 
     fn expand(&self, replacements: &[Formula<'a>]) -> Formula<'a> {
         let inputs = self.inputs.get().unwrap();
         let expanded = self.expanded.get().unwrap();
 
-        let substitution = inputs.iter().copied().zip(replacements).collect();
+        let substitution: HashMap<_, _> = inputs.iter().copied().zip(replacements).collect();
 
         expanded.substitute(&substitution).expand_definitions()
     }
@@ -211,9 +345,13 @@ impl<'a> Variable<'a> {
         self.type_signature.set(type_signature).unwrap()
     }
 
-    fn type_signature(&self) -> &TypeSignature<'a> {
+    pub fn type_signature(&self) -> &TypeSignature<'a> {
         self.type_signature.get().unwrap()
     }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -221,40 +359,70 @@ pub enum Formula<'a> {
     Symbol(&'a Symbol<'a>),
     Variable(&'a Variable<'a>),
 
+    // De Bruijn index of a variable bound by an enclosing abstraction.
+    Bound(usize),
+
     Application(Box<Formula<'a>>, Box<Formula<'a>>),
+    Abstraction(TypeSignature<'a>, Box<Formula<'a>>),
 
     Definition(&'a Definition<'a>, Vec<Formula<'a>>),
 }
 
 impl<'a> Formula<'a> {
+    // Panics if the formula isn't well typed, so it should be verified first.
     pub fn type_signature(&self) -> TypeSignature<'a> {
-        match self {
-            Self::Symbol(symbol_ref) => symbol_ref.type_signature().clone(),
-            Self::Variable(variable_ref) => variable_ref.type_signature().clone(),
-
-            Self::Application(function, _) => function.type_signature().apply().unwrap(),
+        let mut inference = TypeInference::default();
+        let type_signature = self.infer(&mut Vec::new(), &mut inference).unwrap();
 
-            Self::Definition(definition_ref, _) => definition_ref.type_signature(),
-        }
+        inference.resolve(&type_signature)
     }
 
     pub fn verify(&self) -> bool {
+        self.infer(&mut Vec::new(), &mut TypeInference::default())
+            .is_some()
+    }
+
+    fn infer(
+        &self,
+        bound: &mut Vec<TypeSignature<'a>>,
+        inference: &mut TypeInference<'a>,
+    ) -> Option<TypeSignature<'a>> {
         match self {
-            Self::Symbol(_) | Self::Variable(_) => true,
+            Self::Symbol(symbol_ref) => {
+                Some(inference.instantiate(symbol_ref.type_signature(), &mut HashMap::new()))
+            }
+            Self::Variable(variable_ref) => Some(variable_ref.type_signature().clone()),
+            Self::Bound(index) => {
+                let position = bound.len().checked_sub(index + 1)?;
+
+                Some(bound[position].clone())
+            }
 
             Self::Application(function, input) => {
-                let signatures_match = || {
-                    if let Some(input_signature) = function.type_signature().input() {
-                        input_signature == &input.type_signature()
-                    } else {
-                        false
-                    }
-                };
+                let function_type = function.infer(bound, inference)?;
+                let input_type = input.infer(bound, inference)?;
+                let output_type = inference.fresh();
+
+                let expected =
+                    TypeSignature::Compound(Box::new(input_type), Box::new(output_type.clone()));
+                if inference.unify(&function_type, &expected) {
+                    Some(output_type)
+                } else {
+                    None
+                }
+            }
+
+            Self::Abstraction(input, body) => {
+                bound.push(input.clone());
+                let output = body.infer(bound, inference);
+                let input = bound.pop().unwrap();
 
-                function.verify() && input.verify() && signatures_match()
+                Some(TypeSignature::Compound(Box::new(input), Box::new(output?)))
             }
 
-            Self::Definition(definition_ref, inputs) => definition_ref.inputs_match(inputs),
+            Self::Definition(definition_ref, inputs) => {
+                definition_ref.infer(inputs, bound, inference)
+            }
         }
     }
 
@@ -262,25 +430,54 @@ impl<'a> Formula<'a> {
         &'a self,
         deductable_ref: &DeductableRef<'a>,
         prev_steps: &'a [ProofStep<'a>],
+        premises: Option<&[&'a Formula<'a>]>,
+        bindings: &[(&'a Variable<'a>, Formula<'a>)],
+        distinct: &[(&'a Variable<'a>, &'a Variable<'a>)],
         i: usize,
-    ) -> Option<CheckingError> {
-        if let Some(assertion_substitution) = Substitution::new(deductable_ref.assertion(), self) {
-            let premise_substitutions = deductable_ref.premise().iter().map(|hypothesis| {
-                SubstitutionList::find(hypothesis, prev_steps.iter().map(ProofStep::formula))
-            });
-
-            let merged_substitutions = premise_substitutions.fold(
-                SubstitutionList::new(assertion_substitution),
-                |curr, next| curr.merge(next),
-            );
-
-            if merged_substitutions.impossible() {
-                Some(CheckingError::DeductableNotSubstitutable(i))
-            } else {
-                None
+    ) -> Option<CheckingError<'a>> {
+        let assertion_substitution =
+            match Substitution::new(bindings, deductable_ref.assertion(), self) {
+                Ok(substitution) => substitution,
+                Err(e) => return Some(CheckingError::DeductableAssertionNotSubstitutable(i, e)),
+            };
+
+        let premise_substitutions =
+            deductable_ref
+                .premise()
+                .iter()
+                .enumerate()
+                .map(|(hypothesis_index, hypothesis)| match premises {
+                    Some(premises) => SubstitutionList::from_result(Substitution::new(
+                        bindings,
+                        hypothesis,
+                        premises[hypothesis_index],
+                    )),
+
+                    None => SubstitutionList::find(
+                        bindings,
+                        hypothesis,
+                        prev_steps.iter().map(ProofStep::formula),
+                        hypothesis_index,
+                    ),
+                });
+
+        let merged_substitutions = premise_substitutions.fold(
+            SubstitutionList::new(assertion_substitution),
+            |curr, next| curr.merge(next),
+        );
+
+        match merged_substitutions.complete() {
+            Ok(complete_substitutions) => {
+                if complete_substitutions.iter().any(|substitution| {
+                    substitution.is_distinct(deductable_ref.distinct(), distinct)
+                }) {
+                    None
+                } else {
+                    Some(CheckingError::DeductableNotDistinct(i))
+                }
             }
-        } else {
-            Some(CheckingError::DeductableAssertionNotSubstitutable(i))
+
+            Err(e) => Some(CheckingError::DeductableNotSubstitutable(i, e)),
         }
     }
 
@@ -300,6 +497,14 @@ impl<'a> Formula<'a> {
         }
     }
 
+    pub fn abstraction(&self) -> Option<(&TypeSignature<'a>, &Formula<'a>)> {
+        match self {
+            Self::Abstraction(input, body) => Some((input, body)),
+
+            _ => None,
+        }
+    }
+
     pub fn definition(&self) -> Option<(&Definition<'a>, &[Formula<'a>])> {
         match self {
             Self::Definition(definition_ref, inputs) => Some((definition_ref, inputs)),
@@ -308,40 +513,510 @@ impl<'a> Formula<'a> {
         }
     }
 
-    pub fn expand_definitions(&self) -> Formula<'a> {
+    // Splits an application of a variable into the variable and its inputs, e.g. `'P('x, 'y)`.
+    pub fn flex(&self) -> Option<(&'a Variable<'a>, Vec<&Formula<'a>>)> {
+        let mut inputs = Vec::new();
+        let mut head = self;
+
+        while let Self::Application(function, input) = head {
+            inputs.push(input.as_ref());
+            head = function;
+        }
+
+        match head {
+            Self::Variable(variable_ref) if !inputs.is_empty() => {
+                inputs.reverse();
+                Some((variable_ref, inputs))
+            }
+
+            _ => None,
+        }
+    }
+
+    pub fn variables(&self) -> Vec<&'a Variable<'a>> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+
+        variables
+    }
+
+    // Adds the variables of the formula which aren't in `variables` yet.
+    pub fn collect_variables(&self, variables: &mut Vec<&'a Variable<'a>>) {
         match self {
+            Self::Variable(variable_ref) => {
+                if !variables.contains(variable_ref) {
+                    variables.push(variable_ref);
+                }
+            }
+            Self::Symbol(_) | Self::Bound(_) => {}
+
+            Self::Application(function, input) => {
+                function.collect_variables(variables);
+                input.collect_variables(variables);
+            }
+            Self::Abstraction(_, body) => body.collect_variables(variables),
+
+            Self::Definition(_, inputs) => {
+                for input in inputs {
+                    input.collect_variables(variables);
+                }
+            }
+        }
+    }
+
+    // True if the formula doesn't mention any variable bound outside of it.
+    pub fn is_closed(&self) -> bool {
+        self.is_closed_at(0)
+    }
+
+    fn is_closed_at(&self, depth: usize) -> bool {
+        match self {
+            Self::Symbol(_) | Self::Variable(_) => true,
+            Self::Bound(index) => *index < depth,
+
+            Self::Application(function, input) => {
+                function.is_closed_at(depth) && input.is_closed_at(depth)
+            }
+            Self::Abstraction(_, body) => body.is_closed_at(depth + 1),
+
+            Self::Definition(_, inputs) => inputs.iter().all(|input| input.is_closed_at(depth)),
+        }
+    }
+
+    fn shift(&self, amount: usize, cutoff: usize) -> Formula<'a> {
+        match self {
+            Self::Bound(index) if *index >= cutoff => Self::Bound(index + amount),
+            Self::Symbol(_) | Self::Variable(_) | Self::Bound(_) => self.clone(),
+
+            Self::Application(function, input) => Self::Application(
+                Box::new(function.shift(amount, cutoff)),
+                Box::new(input.shift(amount, cutoff)),
+            ),
+            Self::Abstraction(input, body) => {
+                Self::Abstraction(input.clone(), Box::new(body.shift(amount, cutoff + 1)))
+            }
+
+            Self::Definition(definition_ref, inputs) => Self::Definition(
+                definition_ref,
+                inputs
+                    .iter()
+                    .map(|input| input.shift(amount, cutoff))
+                    .collect(),
+            ),
+        }
+    }
+
+    // Replaces the outermost bound variable of an abstraction body with `replacement`.
+    fn instantiate(&self, replacement: &Formula<'a>) -> Formula<'a> {
+        self.instantiate_at(replacement, 0)
+    }
+
+    fn instantiate_at(&self, replacement: &Formula<'a>, depth: usize) -> Formula<'a> {
+        match self {
+            Self::Bound(index) => match index.cmp(&depth) {
+                Ordering::Less => self.clone(),
+                Ordering::Equal => replacement.shift(depth, 0),
+                Ordering::Greater => Self::Bound(index - 1),
+            },
             Self::Symbol(_) | Self::Variable(_) => self.clone(),
 
             Self::Application(function, input) => Self::Application(
-                Box::new(function.expand_definitions()),
-                Box::new(input.expand_definitions()),
+                Box::new(function.instantiate_at(replacement, depth)),
+                Box::new(input.instantiate_at(replacement, depth)),
             ),
+            Self::Abstraction(input, body) => Self::Abstraction(
+                input.clone(),
+                Box::new(body.instantiate_at(replacement, depth + 1)),
+            ),
+
+            Self::Definition(definition_ref, inputs) => Self::Definition(
+                definition_ref,
+                inputs
+                    .iter()
+                    .map(|input| input.instantiate_at(replacement, depth))
+                    .collect(),
+            ),
+        }
+    }
+
+    // Solves `variable(#i, #j, ...) = self` for a list of distinct bound variables, by
+    // abstracting them out of `self`. Fails if `self` mentions any other bound variable.
+    pub fn abstract_pattern(
+        &self,
+        type_signature: &TypeSignature<'a>,
+        indices: &[usize],
+    ) -> Option<Formula<'a>> {
+        let body = self.abstract_pattern_at(indices, 0)?;
+        let inputs: Vec<_> = type_signature.inputs().take(indices.len()).collect();
+
+        if inputs.len() < indices.len() {
+            return None;
+        }
+
+        Some(inputs.into_iter().rev().fold(body, |body, input| {
+            Self::Abstraction(input.clone(), Box::new(body))
+        }))
+    }
+
+    fn abstract_pattern_at(&self, indices: &[usize], depth: usize) -> Option<Formula<'a>> {
+        match self {
+            Self::Bound(index) if *index >= depth => {
+                let position = indices.iter().position(|i| *i == index - depth)?;
 
-            Self::Definition(definition_ref, inputs) => definition_ref.expand(inputs),
+                Some(Self::Bound(depth + indices.len() - 1 - position))
+            }
+            Self::Symbol(_) | Self::Variable(_) | Self::Bound(_) => Some(self.clone()),
+
+            Self::Application(function, input) => Some(Self::Application(
+                Box::new(function.abstract_pattern_at(indices, depth)?),
+                Box::new(input.abstract_pattern_at(indices, depth)?),
+            )),
+            Self::Abstraction(input, body) => Some(Self::Abstraction(
+                input.clone(),
+                Box::new(body.abstract_pattern_at(indices, depth + 1)?),
+            )),
+
+            Self::Definition(definition_ref, inputs) => Some(Self::Definition(
+                definition_ref,
+                inputs
+                    .iter()
+                    .map(|input| input.abstract_pattern_at(indices, depth))
+                    .collect::<Option<_>>()?,
+            )),
         }
     }
 
-    fn substitute(&self, substitution: &HashMap<&Variable<'a>, &Formula<'a>>) -> Formula<'a> {
+    pub fn beta_reduce(&self) -> Formula<'a> {
+        self.normalize(false)
+    }
+
+    pub fn expand_definitions(&self) -> Formula<'a> {
+        self.normalize(true)
+    }
+
+    fn normalize(&self, expand_definitions: bool) -> Formula<'a> {
         match self {
-            Self::Symbol(symbol_ref) => Formula::Symbol(*symbol_ref),
-            Self::Variable(variable_ref) => substitution[*variable_ref].clone(),
+            Self::Symbol(_) | Self::Variable(_) | Self::Bound(_) => self.clone(),
+
+            Self::Application(function, input) => {
+                let function = function.normalize(expand_definitions);
+                let input = input.normalize(expand_definitions);
+
+                match function {
+                    Self::Abstraction(_, body) => {
+                        body.instantiate(&input).normalize(expand_definitions)
+                    }
+
+                    function => Self::Application(Box::new(function), Box::new(input)),
+                }
+            }
+            Self::Abstraction(input, body) => {
+                Self::Abstraction(input.clone(), Box::new(body.normalize(expand_definitions)))
+            }
+
+            Self::Definition(definition_ref, inputs) => {
+                if expand_definitions {
+                    definition_ref.expand(inputs)
+                } else {
+                    Self::Definition(
+                        definition_ref,
+                        inputs
+                            .iter()
+                            .map(|input| input.normalize(expand_definitions))
+                            .collect(),
+                    )
+                }
+            }
+        }
+    }
+
+    // Since bound variables are nameless, a replacement can never be captured by a binder in
+    // `self`. It only needs its own bound variables shifted past the binders it ends up under.
+    pub fn substitute<F>(&self, substitution: &HashMap<&Variable<'a>, F>) -> Formula<'a>
+    where
+        F: Borrow<Formula<'a>>,
+    {
+        self.substitute_at(substitution, 0)
+    }
+
+    fn substitute_at<F>(
+        &self,
+        substitution: &HashMap<&Variable<'a>, F>,
+        depth: usize,
+    ) -> Formula<'a>
+    where
+        F: Borrow<Formula<'a>>,
+    {
+        match self {
+            Self::Symbol(symbol_ref) => Formula::Symbol(symbol_ref),
+            Self::Variable(variable_ref) => match substitution.get(*variable_ref) {
+                Some(replacement) => replacement.borrow().shift(depth, 0),
+                None => self.clone(),
+            },
+            Self::Bound(index) => Formula::Bound(*index),
 
             Self::Application(function, input) => Formula::Application(
-                Box::new(function.substitute(substitution)),
-                Box::new(input.substitute(substitution)),
+                Box::new(function.substitute_at(substitution, depth)),
+                Box::new(input.substitute_at(substitution, depth)),
+            ),
+            Self::Abstraction(input, body) => Formula::Abstraction(
+                input.clone(),
+                Box::new(body.substitute_at(substitution, depth + 1)),
             ),
 
             Self::Definition(definition_ref, inputs) => Formula::Definition(
-                *definition_ref,
+                definition_ref,
                 inputs
                     .iter()
-                    .map(|input| input.substitute(substitution))
+                    .map(|input| input.substitute_at(substitution, depth))
                     .collect(),
             ),
         }
     }
 
-    pub fn compatible(&'a self, other: &'a Self) -> bool {
+    // Bound variables are stored as de Bruijn indices, so alpha-equivalent formulas are already
+    // structurally equal.
+    pub fn compatible(&self, other: &Self) -> bool {
         self.expand_definitions() == other.expand_definitions()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Formula, Symbol, Type, TypeSignature, Variable};
+
+    fn function<'a>(input: TypeSignature<'a>, output: TypeSignature<'a>) -> TypeSignature<'a> {
+        TypeSignature::Compound(Box::new(input), Box::new(output))
+    }
+
+    fn apply<'a>(function: Formula<'a>, inputs: Vec<Formula<'a>>) -> Formula<'a> {
+        inputs.into_iter().fold(function, |curr, input| {
+            Formula::Application(Box::new(curr), Box::new(input))
+        })
+    }
+
+    fn lambda<'a>(input: TypeSignature<'a>, body: Formula<'a>) -> Formula<'a> {
+        Formula::Abstraction(input, Box::new(body))
+    }
+
+    #[test]
+    fn beta_reduce() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty.clone());
+
+        // (\x. f x x) 'a
+        let redex = apply(
+            lambda(
+                nat_ty.clone(),
+                apply(
+                    Formula::Symbol(&f),
+                    vec![Formula::Bound(0), Formula::Bound(0)],
+                ),
+            ),
+            vec![Formula::Variable(&a)],
+        );
+        assert_eq!(
+            redex.beta_reduce(),
+            apply(
+                Formula::Symbol(&f),
+                vec![Formula::Variable(&a), Formula::Variable(&a)],
+            )
+        );
+
+        // \z. (\x. \y. x) z reduces to \z. \y. z, so `z` has to be shifted past `y`.
+        let nested = lambda(
+            nat_ty.clone(),
+            apply(
+                lambda(nat_ty.clone(), lambda(nat_ty.clone(), Formula::Bound(1))),
+                vec![Formula::Bound(0)],
+            ),
+        );
+        assert_eq!(
+            nested.beta_reduce(),
+            lambda(nat_ty.clone(), lambda(nat_ty, Formula::Bound(1)))
+        );
+    }
+
+    #[test]
+    fn substitute_under_binder() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty.clone());
+
+        // \y. f 'a y
+        let formula = lambda(
+            nat_ty.clone(),
+            apply(
+                Formula::Symbol(&f),
+                vec![Formula::Variable(&a), Formula::Bound(0)],
+            ),
+        );
+
+        // Replacing 'a with a variable bound outside the formula mustn't capture it as `y`.
+        let mut substitution = HashMap::new();
+        substitution.insert(&a, Formula::Bound(0));
+        assert_eq!(
+            formula.substitute(&substitution),
+            lambda(
+                nat_ty,
+                apply(
+                    Formula::Symbol(&f),
+                    vec![Formula::Bound(1), Formula::Bound(0)],
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn alpha_equivalence() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(nat_ty.clone(), nat_ty.clone()));
+
+        // \x. f x and \y. f y are the same formula, since binders carry no names.
+        let left = lambda(
+            nat_ty.clone(),
+            apply(Formula::Symbol(&f), vec![Formula::Bound(0)]),
+        );
+        let right = lambda(nat_ty, apply(Formula::Symbol(&f), vec![Formula::Bound(0)]));
+
+        assert_eq!(left, right);
+        assert!(left.compatible(&right));
+    }
+
+    #[test]
+    fn closed_and_typed() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(nat_ty.clone(), nat_ty.clone()));
+
+        let body = apply(Formula::Symbol(&f), vec![Formula::Bound(0)]);
+        assert!(!body.is_closed());
+        assert!(!body.verify());
+
+        let abstraction = lambda(nat_ty.clone(), body);
+        assert!(abstraction.is_closed());
+        assert!(abstraction.verify());
+        assert_eq!(
+            abstraction.type_signature(),
+            function(nat_ty.clone(), nat_ty)
+        );
+    }
+
+    #[test]
+    fn abstract_pattern() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let g = Symbol::new("g".to_owned());
+        g.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+
+        let pattern_ty = function(nat_ty.clone(), function(nat_ty.clone(), nat_ty.clone()));
+
+        // Solving 'P(#0, #1) = g #1 #0 swaps the inputs.
+        let target = apply(
+            Formula::Symbol(&g),
+            vec![Formula::Bound(1), Formula::Bound(0)],
+        );
+        assert_eq!(
+            target.abstract_pattern(&pattern_ty, &[0, 1]),
+            Some(lambda(
+                nat_ty.clone(),
+                lambda(
+                    nat_ty.clone(),
+                    apply(
+                        Formula::Symbol(&g),
+                        vec![Formula::Bound(0), Formula::Bound(1)],
+                    ),
+                ),
+            ))
+        );
+
+        // 'P(#0) can't stand for a formula which mentions #1.
+        assert_eq!(
+            target.abstract_pattern(&function(nat_ty.clone(), nat_ty), &[0]),
+            None
+        );
+    }
+
+    #[test]
+    fn type_variables() {
+        let nat = Type::new("Nat".to_owned());
+        let prop = Type::new("Prop".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+        let prop_ty = TypeSignature::Ground(&prop);
+        let var_ty = TypeSignature::Variable("A".to_owned());
+
+        let eq = Symbol::new("eq".to_owned());
+        eq.set_type_signature(function(var_ty.clone(), function(var_ty, prop_ty.clone())));
+        let and = Symbol::new("and".to_owned());
+        and.set_type_signature(function(
+            prop_ty.clone(),
+            function(prop_ty.clone(), prop_ty.clone()),
+        ));
+        let zero = Symbol::new("zero".to_owned());
+        zero.set_type_signature(nat_ty);
+        let top = Symbol::new("top".to_owned());
+        top.set_type_signature(prop_ty.clone());
+
+        let nat_eq = apply(
+            Formula::Symbol(&eq),
+            vec![Formula::Symbol(&zero), Formula::Symbol(&zero)],
+        );
+        let prop_eq = apply(
+            Formula::Symbol(&eq),
+            vec![Formula::Symbol(&top), Formula::Symbol(&top)],
+        );
+        let mixed_eq = apply(
+            Formula::Symbol(&eq),
+            vec![Formula::Symbol(&zero), Formula::Symbol(&top)],
+        );
+
+        // Each use of `eq` picks its own type for 'A, but both inputs of one use share it.
+        let both = apply(Formula::Symbol(&and), vec![nat_eq, prop_eq]);
+        assert!(both.verify());
+        assert_eq!(both.type_signature(), prop_ty);
+        assert!(!mixed_eq.verify());
+    }
+
+    #[test]
+    fn type_occurs_check() {
+        let prop = Type::new("Prop".to_owned());
+        let prop_ty = TypeSignature::Ground(&prop);
+        let a_ty = TypeSignature::Variable("A".to_owned());
+        let b_ty = TypeSignature::Variable("B".to_owned());
+
+        let endo = Symbol::new("endo".to_owned());
+        endo.set_type_signature(function(function(a_ty.clone(), a_ty), prop_ty));
+        let pair = Symbol::new("pair".to_owned());
+        pair.set_type_signature(function(b_ty.clone(), function(b_ty.clone(), b_ty)));
+
+        // `pair` would need 'A = 'A -> 'A to be passed to `endo`, which has no solution.
+        let formula = apply(Formula::Symbol(&endo), vec![Formula::Symbol(&pair)]);
+        assert!(!formula.verify());
+    }
+}
@@ -17,115 +17,469 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::ops::Index;
 
-use super::language::{Formula, Variable};
+use super::errors::SubstitutionError;
+use super::language::{Formula, TypeSignature, Variable};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Substitution<'a> {
-    map: HashMap<&'a Variable<'a>, &'a Formula<'a>>,
+    template_vars: Vec<&'a Variable<'a>>,
+    map: HashMap<&'a Variable<'a>, Formula<'a>>,
+
+    // The types which the type variables of the template stand for.
+    types: HashMap<String, TypeSignature<'a>>,
+
+    // Applications of a variable which is not yet bound, such as `'P('t)`. These are matched once
+    // some other part of the template determines the variable.
+    deferred: Vec<(Formula<'a>, &'a Formula<'a>)>,
 }
 
 impl<'a> Substitution<'a> {
-    pub fn new(template: &Formula<'a>, target: &'a Formula<'a>) -> Option<Self> {
-        let mut stack = vec![(template, target)];
-        let mut map = HashMap::<&Variable, &Formula>::new();
-
-        while let Some((template, target)) = stack.pop() {
-            match template {
-                Formula::Symbol(symbol_ref) => {
-                    if *symbol_ref != target.symbol()? {
-                        return None;
+    // Matches the template against the target, with some of the variables already decided.
+    pub fn new(
+        bindings: &[(&'a Variable<'a>, Formula<'a>)],
+        template: &Formula<'a>,
+        target: &'a Formula<'a>,
+    ) -> Result<Self, SubstitutionError<'a>> {
+        let mut template_vars = template.variables();
+        template_vars.extend(bindings.iter().map(|(variable_ref, _)| *variable_ref));
+
+        let mut substitution = Substitution {
+            template_vars,
+            map: bindings.iter().cloned().collect(),
+            types: HashMap::new(),
+
+            deferred: Vec::new(),
+        };
+
+        for (variable_ref, formula) in bindings {
+            let variable_type = variable_ref.type_signature();
+            if !substitution.unify_type(variable_type, &formula.type_signature())? {
+                return Err(SubstitutionError::Mismatch(
+                    Formula::Variable(variable_ref),
+                    formula.clone(),
+                ));
+            }
+        }
+
+        substitution.unify(template, target)?;
+        substitution.resolve(false)?;
+
+        Ok(substitution)
+    }
+
+    fn mismatch(template: &Formula<'a>, target: &Formula<'a>) -> Result<(), SubstitutionError<'a>> {
+        Err(SubstitutionError::Mismatch(
+            template.clone(),
+            target.clone(),
+        ))
+    }
+
+    // Matches a type from the template against one from the target. A type variable of the
+    // template can stand for any type, but it has to be the same one everywhere.
+    fn unify_type(
+        &mut self,
+        template: &TypeSignature<'a>,
+        target: &TypeSignature<'a>,
+    ) -> Result<bool, SubstitutionError<'a>> {
+        match (template, target) {
+            (TypeSignature::Variable(id), _) => match self.types.entry(id.clone()) {
+                Entry::Occupied(old_target) => {
+                    if old_target.get() == target {
+                        Ok(true)
+                    } else {
+                        Err(SubstitutionError::TypeConflict(
+                            id.clone(),
+                            old_target.get().clone(),
+                            target.clone(),
+                        ))
                     }
                 }
 
-                Formula::Variable(variable_ref) => match map.entry(*variable_ref) {
-                    Entry::Occupied(old_target) => {
-                        if !old_target.get().compatible(target) {
-                            return None;
-                        }
-                    }
+                Entry::Vacant(slot) => {
+                    slot.insert(target.clone());
+                    Ok(true)
+                }
+            },
 
-                    Entry::Vacant(slot) => {
-                        slot.insert(target);
-                    }
-                },
+            (
+                TypeSignature::Compound(template_input, template_output),
+                TypeSignature::Compound(target_input, target_output),
+            ) => Ok(self.unify_type(template_input, target_input)?
+                && self.unify_type(template_output, target_output)?),
 
-                Formula::Application(template_function, template_input) => {
-                    let (target_function, target_input) = target.application()?;
+            _ => Ok(template == target),
+        }
+    }
 
-                    stack.push((template_function, target_function));
-                    stack.push((template_input, target_input));
+    fn unify(
+        &mut self,
+        template: &Formula<'a>,
+        target: &'a Formula<'a>,
+    ) -> Result<(), SubstitutionError<'a>> {
+        match template {
+            Formula::Symbol(symbol_ref) => {
+                if target.symbol() == Some(*symbol_ref) {
+                    Ok(())
+                } else {
+                    Self::mismatch(template, target)
                 }
+            }
 
-                Formula::Definition(definition_ref, inputs) => {
-                    let (target_ref, target_inputs) = target.definition()?;
+            Formula::Variable(variable_ref) => {
+                if !self.template_vars.contains(variable_ref) {
+                    // Left over from instantiating a binding, so it belongs to the target.
+                    return if template == target {
+                        Ok(())
+                    } else {
+                        Self::mismatch(template, target)
+                    };
+                }
 
-                    if *definition_ref != target_ref {
-                        return None;
+                // A variable can't be replaced by something which refers to a binder inside the
+                // template, as that would capture the bound variable.
+                if !target.is_closed() {
+                    return Self::mismatch(template, target);
+                }
+
+                if let Some(old_target) = self.map.get(variable_ref) {
+                    return if old_target.compatible(target) {
+                        Ok(())
+                    } else {
+                        Err(SubstitutionError::Conflict(
+                            variable_ref,
+                            old_target.clone(),
+                            target.clone(),
+                        ))
+                    };
+                }
+
+                if !self.unify_type(variable_ref.type_signature(), &target.type_signature())? {
+                    return Self::mismatch(template, target);
+                }
+
+                self.map.insert(variable_ref, target.clone());
+                Ok(())
+            }
+
+            Formula::Bound(index) => match target {
+                Formula::Bound(target_index) if target_index == index => Ok(()),
+
+                _ => Self::mismatch(template, target),
+            },
+
+            Formula::Application(template_function, template_input) => match template.flex() {
+                Some((variable_ref, inputs)) if self.template_vars.contains(&variable_ref) => {
+                    self.unify_flex(template, variable_ref, &inputs, target)
+                }
+
+                _ => {
+                    if let Some((target_function, target_input)) = target.application() {
+                        // A mismatch between the functions themselves is reported as a mismatch
+                        // of the whole applications, as partial applications are hard to read.
+                        self.unify(template_function, target_function)
+                            .map_err(|e| match e {
+                                SubstitutionError::Mismatch(inner, _)
+                                    if &inner == template_function.as_ref() =>
+                                {
+                                    SubstitutionError::Mismatch(template.clone(), target.clone())
+                                }
+
+                                e => e,
+                            })?;
+                        self.unify(template_input, target_input)
+                    } else {
+                        Self::mismatch(template, target)
                     }
+                }
+            },
 
-                    for next_item in inputs.iter().zip(target_inputs) {
-                        stack.push(next_item);
+            Formula::Abstraction(template_input, template_body) => match target.abstraction() {
+                Some((target_input, target_body)) => {
+                    if !self.unify_type(template_input, target_input)? {
+                        return Self::mismatch(template, target);
                     }
+
+                    // A mismatch which refers to this binder can't be understood without it.
+                    self.unify(template_body, target_body).map_err(|e| match e {
+                        SubstitutionError::Mismatch(inner_template, inner_target)
+                            if !inner_template.is_closed() || !inner_target.is_closed() =>
+                        {
+                            SubstitutionError::Mismatch(template.clone(), target.clone())
+                        }
+
+                        e => e,
+                    })
                 }
+
+                _ => Self::mismatch(template, target),
+            },
+
+            Formula::Definition(definition_ref, inputs) => match target.definition() {
+                Some((target_ref, target_inputs))
+                    if *definition_ref == target_ref && inputs.len() == target_inputs.len() =>
+                {
+                    inputs
+                        .iter()
+                        .zip(target_inputs)
+                        .try_for_each(|(input, target_input)| self.unify(input, target_input))
+                }
+
+                _ => Self::mismatch(template, target),
+            },
+        }
+    }
+
+    fn unify_flex(
+        &mut self,
+        template: &Formula<'a>,
+        variable_ref: &'a Variable<'a>,
+        inputs: &[&Formula<'a>],
+        target: &'a Formula<'a>,
+    ) -> Result<(), SubstitutionError<'a>> {
+        if let Some(function) = self.map.get(variable_ref) {
+            let applied = inputs.iter().fold(function.clone(), |curr, input| {
+                Formula::Application(Box::new(curr), Box::new((*input).clone()))
+            });
+
+            return match self.unify(&applied.beta_reduce(), target) {
+                // The reduced formula doesn't appear in the template, so report the application
+                // the user actually wrote instead.
+                Err(SubstitutionError::Mismatch(_, _)) => Self::mismatch(template, target),
+                result => result,
+            };
+        }
+
+        // A variable applied to distinct bound variables has exactly one solution.
+        let indices: Option<Vec<_>> = inputs
+            .iter()
+            .map(|input| match input {
+                Formula::Bound(index) => Some(*index),
+
+                _ => None,
+            })
+            .collect();
+        if let Some(indices) = indices {
+            let distinct = indices
+                .iter()
+                .enumerate()
+                .all(|(i, index)| !indices[..i].contains(index));
+
+            if distinct {
+                let variable_type = variable_ref.type_signature().substitute(&self.types);
+
+                return match target.abstract_pattern(&variable_type, &indices) {
+                    Some(function) => {
+                        if !self
+                            .unify_type(variable_ref.type_signature(), &function.type_signature())?
+                        {
+                            return Self::mismatch(template, target);
+                        }
+
+                        self.map.insert(variable_ref, function);
+                        Ok(())
+                    }
+
+                    None => Self::mismatch(template, target),
+                };
             }
         }
 
-        Some(Substitution { map })
+        self.deferred.push((template.clone(), target));
+        Ok(())
     }
 
-    fn merge(&self, other: &Self) -> Option<Self> {
-        let merge_possible = other.map.iter().all(|(other_var, other_formula)| {
-            if let Some(self_formula) = self.map.get(other_var) {
-                self_formula.compatible(other_formula)
-            } else {
-                true
+    // Retries the deferred applications until no more progress is made. When `force` is set, a
+    // stuck application is matched structurally instead, treating the variable as opaque.
+    fn resolve(&mut self, force: bool) -> Result<(), SubstitutionError<'a>> {
+        loop {
+            let map_len = self.map.len();
+            let deferred_len = self.deferred.len();
+
+            for (template, target) in std::mem::take(&mut self.deferred) {
+                self.unify(&template, target)?;
             }
-        });
 
-        if merge_possible {
-            let mut map = self.map.clone();
-            map.extend(&other.map);
+            let progress = self.map.len() > map_len || self.deferred.len() < deferred_len;
+            if progress {
+                continue;
+            }
 
-            Some(Substitution { map })
-        } else {
-            None
+            if !force || self.deferred.is_empty() {
+                return Ok(());
+            }
+
+            let (template, target) = self.deferred.remove(0);
+            self.unify_opaque(&template, target)?;
         }
     }
+
+    fn unify_opaque(
+        &mut self,
+        template: &Formula<'a>,
+        target: &'a Formula<'a>,
+    ) -> Result<(), SubstitutionError<'a>> {
+        match (template, target) {
+            (Formula::Application(template_function, template_input), _) => {
+                if let Some((target_function, target_input)) = target.application() {
+                    self.unify_opaque(template_function, target_function)?;
+                    self.unify(template_input, target_input)
+                } else {
+                    Self::mismatch(template, target)
+                }
+            }
+
+            _ => self.unify(template, target),
+        }
+    }
+
+    fn merge(&self, other: &Self) -> Result<Self, SubstitutionError<'a>> {
+        let mut merged = self.clone();
+        merged.template_vars.extend(&other.template_vars);
+
+        for (other_var, other_formula) in &other.map {
+            match merged.map.entry(other_var) {
+                Entry::Occupied(self_formula) => {
+                    if !self_formula.get().compatible(other_formula) {
+                        return Err(SubstitutionError::Conflict(
+                            other_var,
+                            self_formula.get().clone(),
+                            other_formula.clone(),
+                        ));
+                    }
+                }
+
+                Entry::Vacant(slot) => {
+                    slot.insert(other_formula.clone());
+                }
+            }
+        }
+
+        for (other_id, other_type) in &other.types {
+            match merged.types.entry(other_id.clone()) {
+                Entry::Occupied(self_type) => {
+                    if self_type.get() != other_type {
+                        return Err(SubstitutionError::TypeConflict(
+                            other_id.clone(),
+                            self_type.get().clone(),
+                            other_type.clone(),
+                        ));
+                    }
+                }
+
+                Entry::Vacant(slot) => {
+                    slot.insert(other_type.clone());
+                }
+            }
+        }
+
+        merged.deferred.extend(other.deferred.iter().cloned());
+        merged.resolve(false)?;
+
+        Ok(merged)
+    }
+
+    fn complete(&self) -> Result<Self, SubstitutionError<'a>> {
+        let mut substitution = self.clone();
+        substitution.resolve(true)?;
+
+        Ok(substitution)
+    }
+
+    // Checks that each pair of variables in `distinct` is replaced by formulas with no variables in
+    // common. The variables which do appear must in turn be declared distinct by `allowed`.
+    pub fn is_distinct(
+        &self,
+        distinct: &[(&'a Variable<'a>, &'a Variable<'a>)],
+        allowed: &[(&'a Variable<'a>, &'a Variable<'a>)],
+    ) -> bool {
+        let is_allowed = |left: &Variable<'a>, right: &Variable<'a>| {
+            allowed.iter().any(|(allowed_left, allowed_right)| {
+                (*allowed_left == left && *allowed_right == right)
+                    || (*allowed_left == right && *allowed_right == left)
+            })
+        };
+
+        distinct.iter().all(|(left, right)| {
+            let (left_vars, right_vars) = match (self.map.get(left), self.map.get(right)) {
+                (Some(left), Some(right)) => (left.variables(), right.variables()),
+
+                // A variable which doesn't appear anywhere can be replaced by anything.
+                _ => return true,
+            };
+
+            left_vars.iter().all(|left| {
+                right_vars
+                    .iter()
+                    .all(|right| left != right && is_allowed(left, right))
+            })
+        })
+    }
 }
 
 impl<'a> Index<&Variable<'a>> for Substitution<'a> {
     type Output = Formula<'a>;
 
     fn index(&self, variable_ref: &Variable<'a>) -> &Self::Output {
-        self.map[variable_ref]
+        &self.map[variable_ref]
     }
 }
 
 #[derive(Debug)]
 pub struct SubstitutionList<'a> {
     subs: Vec<Substitution<'a>>,
+
+    // Why there are no substitutions left, if that's the case.
+    error: Option<SubstitutionError<'a>>,
 }
 
 impl<'a> SubstitutionList<'a> {
     pub fn new(substitution: Substitution<'a>) -> Self {
         SubstitutionList {
             subs: vec![substitution],
+            error: None,
+        }
+    }
+
+    pub fn from_result(result: Result<Substitution<'a>, SubstitutionError<'a>>) -> Self {
+        match result {
+            Ok(substitution) => Self::new(substitution),
+            Err(e) => SubstitutionList {
+                subs: Vec::new(),
+                error: Some(e),
+            },
         }
     }
 
-    pub fn find<I>(template: &Formula<'a>, possibilities: I) -> Self
+    pub fn find<I>(
+        bindings: &[(&'a Variable<'a>, Formula<'a>)],
+        template: &Formula<'a>,
+        possibilities: I,
+        hypothesis_index: usize,
+    ) -> Self
     where
         I: IntoIterator<Item = &'a Formula<'a>>,
     {
-        let subs = possibilities
+        let subs: Vec<_> = possibilities
             .into_iter()
-            .filter_map(|target| Substitution::new(template, target))
+            .filter_map(|target| Substitution::new(bindings, template, target).ok())
             .collect();
 
-        SubstitutionList { subs }
+        let error = if subs.is_empty() {
+            Some(SubstitutionError::HypothesisNotFound(hypothesis_index))
+        } else {
+            None
+        };
+
+        SubstitutionList { subs, error }
     }
 
     pub fn merge(self, other: Self) -> Self {
+        if self.subs.is_empty() {
+            return self;
+        } else if other.subs.is_empty() {
+            return other;
+        }
+
         // All possible pairs of subs between self and other.
         let comparisons = self.subs.iter().flat_map(|self_sub| {
             other
@@ -134,14 +488,344 @@ impl<'a> SubstitutionList<'a> {
                 .map(move |other_sub| (self_sub, other_sub))
         });
 
-        SubstitutionList {
-            subs: comparisons
-                .filter_map(|(self_sub, other_sub)| self_sub.merge(other_sub))
-                .collect(),
+        let mut subs = Vec::new();
+        let mut error = None;
+        for result in comparisons.map(|(self_sub, other_sub)| self_sub.merge(other_sub)) {
+            match result {
+                Ok(sub) => subs.push(sub),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
         }
+
+        if !subs.is_empty() {
+            error = None;
+        }
+
+        SubstitutionList { subs, error }
+    }
+
+    pub fn complete(self) -> Result<Vec<Substitution<'a>>, SubstitutionError<'a>> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut first_error = None;
+        let complete: Vec<_> = self
+            .subs
+            .iter()
+            .filter_map(|sub| match sub.complete() {
+                Ok(sub) => Some(sub),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                    None
+                }
+            })
+            .collect();
+
+        match first_error {
+            Some(e) if complete.is_empty() => Err(e),
+            _ => Ok(complete),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Substitution;
+    use crate::core::errors::SubstitutionError;
+    use crate::core::language::{Formula, Symbol, Type, TypeSignature, Variable};
+
+    fn function<'a>(input: TypeSignature<'a>, output: TypeSignature<'a>) -> TypeSignature<'a> {
+        TypeSignature::Compound(Box::new(input), Box::new(output))
+    }
+
+    fn apply<'a>(function: Formula<'a>, inputs: Vec<Formula<'a>>) -> Formula<'a> {
+        inputs.into_iter().fold(function, |curr, input| {
+            Formula::Application(Box::new(curr), Box::new(input))
+        })
+    }
+
+    fn lambda<'a>(input: TypeSignature<'a>, body: Formula<'a>) -> Formula<'a> {
+        Formula::Abstraction(input, Box::new(body))
+    }
+
+    #[test]
+    fn flex_rigid() {
+        let nat = Type::new("Nat".to_owned());
+        let prop = Type::new("Prop".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+        let prop_ty = TypeSignature::Ground(&prop);
+
+        let le = Symbol::new("le".to_owned());
+        le.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), prop_ty.clone()),
+        ));
+        let p = Variable::new("P".to_owned());
+        p.set_type_signature(function(nat_ty.clone(), prop_ty));
+
+        // \x. 'P x against \x. le x x solves 'P as \x. le x x.
+        let template = lambda(
+            nat_ty.clone(),
+            apply(Formula::Variable(&p), vec![Formula::Bound(0)]),
+        );
+        let body = apply(
+            Formula::Symbol(&le),
+            vec![Formula::Bound(0), Formula::Bound(0)],
+        );
+        let target = lambda(nat_ty, body);
+
+        let substitution = Substitution::new(&[], &template, &target).unwrap();
+        assert_eq!(substitution[&p], target);
+    }
+
+    #[test]
+    fn flex_deferred() {
+        let nat = Type::new("Nat".to_owned());
+        let prop = Type::new("Prop".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+        let prop_ty = TypeSignature::Ground(&prop);
+        let pred_ty = function(nat_ty.clone(), prop_ty.clone());
+
+        let both = Symbol::new("both".to_owned());
+        both.set_type_signature(function(
+            prop_ty.clone(),
+            function(pred_ty.clone(), prop_ty),
+        ));
+        let even = Symbol::new("even".to_owned());
+        even.set_type_signature(pred_ty.clone());
+        let zero = Symbol::new("zero".to_owned());
+        zero.set_type_signature(nat_ty.clone());
+
+        let p = Variable::new("P".to_owned());
+        p.set_type_signature(pred_ty);
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty);
+
+        // 'P('a) can't be solved on its own, but it can once 'P is matched against `even`.
+        let template = apply(
+            Formula::Symbol(&both),
+            vec![
+                apply(Formula::Variable(&p), vec![Formula::Variable(&a)]),
+                Formula::Variable(&p),
+            ],
+        );
+        let target = apply(
+            Formula::Symbol(&both),
+            vec![
+                apply(Formula::Symbol(&even), vec![Formula::Symbol(&zero)]),
+                Formula::Symbol(&even),
+            ],
+        );
+
+        let substitution = Substitution::new(&[], &template, &target).unwrap();
+        assert_eq!(substitution[&p], Formula::Symbol(&even));
+        assert_eq!(substitution[&a], Formula::Symbol(&zero));
+    }
+
+    #[test]
+    fn flex_flex() {
+        let nat = Type::new("Nat".to_owned());
+        let prop = Type::new("Prop".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+        let pred_ty = function(nat_ty.clone(), TypeSignature::Ground(&prop));
+
+        let p = Variable::new("P".to_owned());
+        p.set_type_signature(pred_ty.clone());
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty.clone());
+        let q = Variable::new("Q".to_owned());
+        q.set_type_signature(pred_ty);
+        let b = Variable::new("b".to_owned());
+        b.set_type_signature(nat_ty);
+
+        // Nothing else decides 'P, so completing the substitution matches the applications
+        // structurally.
+        let template = apply(Formula::Variable(&p), vec![Formula::Variable(&a)]);
+        let target = apply(Formula::Variable(&q), vec![Formula::Variable(&b)]);
+
+        let substitution = Substitution::new(&[], &template, &target).unwrap();
+        assert!(substitution.map.is_empty());
+
+        let complete = substitution.complete().unwrap();
+        assert_eq!(complete[&p], Formula::Variable(&q));
+        assert_eq!(complete[&a], Formula::Variable(&b));
+    }
+
+    #[test]
+    fn bound_variable_escape() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty.clone());
+        let p = Variable::new("P".to_owned());
+        p.set_type_signature(function(nat_ty.clone(), nat_ty.clone()));
+
+        let target = lambda(
+            nat_ty.clone(),
+            lambda(
+                nat_ty.clone(),
+                apply(
+                    Formula::Symbol(&f),
+                    vec![Formula::Bound(1), Formula::Bound(0)],
+                ),
+            ),
+        );
+
+        // \x. \y. 'a can't take `f x y`, as 'a would refer to the binders.
+        let template = lambda(
+            nat_ty.clone(),
+            lambda(nat_ty.clone(), Formula::Variable(&a)),
+        );
+        assert!(matches!(
+            Substitution::new(&[], &template, &target),
+            Err(SubstitutionError::Mismatch(_, _))
+        ));
+
+        // \x. \y. 'P x can't either, as 'P isn't given `y`.
+        let template = lambda(
+            nat_ty.clone(),
+            lambda(
+                nat_ty,
+                apply(Formula::Variable(&p), vec![Formula::Bound(1)]),
+            ),
+        );
+        assert!(matches!(
+            Substitution::new(&[], &template, &target),
+            Err(SubstitutionError::Mismatch(_, _))
+        ));
     }
 
-    pub fn impossible(&self) -> bool {
-        self.subs.is_empty()
+    #[test]
+    fn conflict() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+        let zero = Symbol::new("zero".to_owned());
+        zero.set_type_signature(nat_ty.clone());
+        let one = Symbol::new("one".to_owned());
+        one.set_type_signature(nat_ty.clone());
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(nat_ty);
+
+        let template = apply(
+            Formula::Symbol(&f),
+            vec![Formula::Variable(&a), Formula::Variable(&a)],
+        );
+        let target = apply(
+            Formula::Symbol(&f),
+            vec![Formula::Symbol(&zero), Formula::Symbol(&one)],
+        );
+
+        assert!(matches!(
+            Substitution::new(&[], &template, &target),
+            Err(SubstitutionError::Conflict(variable_ref, _, _)) if variable_ref == &a
+        ));
+    }
+
+    #[test]
+    fn distinct() {
+        let nat = Type::new("Nat".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+
+        let f = Symbol::new("f".to_owned());
+        f.set_type_signature(function(
+            nat_ty.clone(),
+            function(nat_ty.clone(), nat_ty.clone()),
+        ));
+        let vars: Vec<_> = ["x", "y", "a", "b"]
+            .iter()
+            .map(|id| {
+                let variable = Variable::new((*id).to_owned());
+                variable.set_type_signature(nat_ty.clone());
+
+                variable
+            })
+            .collect();
+        let (x, y, a, b) = (&vars[0], &vars[1], &vars[2], &vars[3]);
+
+        let template = apply(
+            Formula::Symbol(&f),
+            vec![Formula::Variable(x), Formula::Variable(y)],
+        );
+        let apart = apply(
+            Formula::Symbol(&f),
+            vec![Formula::Variable(a), Formula::Variable(b)],
+        );
+        let shared = apply(
+            Formula::Symbol(&f),
+            vec![Formula::Variable(a), Formula::Variable(a)],
+        );
+
+        // 'x and 'y have to be replaced by formulas whose variables are declared distinct.
+        let substitution = Substitution::new(&[], &template, &apart).unwrap();
+        assert!(substitution.is_distinct(&[(x, y)], &[(a, b)]));
+        assert!(substitution.is_distinct(&[(x, y)], &[(b, a)]));
+        assert!(!substitution.is_distinct(&[(x, y)], &[]));
+
+        // No declaration can make a variable distinct from itself.
+        let substitution = Substitution::new(&[], &template, &shared).unwrap();
+        assert!(!substitution.is_distinct(&[(x, y)], &[(a, a)]));
+        assert!(substitution.is_distinct(&[], &[]));
+    }
+
+    #[test]
+    fn type_variables() {
+        let nat = Type::new("Nat".to_owned());
+        let prop = Type::new("Prop".to_owned());
+        let nat_ty = TypeSignature::Ground(&nat);
+        let prop_ty = TypeSignature::Ground(&prop);
+        let var_ty = TypeSignature::Variable("A".to_owned());
+
+        let pair = Symbol::new("pair".to_owned());
+        pair.set_type_signature(function(
+            TypeSignature::Variable("X".to_owned()),
+            function(TypeSignature::Variable("Y".to_owned()), prop_ty.clone()),
+        ));
+        let zero = Symbol::new("zero".to_owned());
+        zero.set_type_signature(nat_ty.clone());
+        let top = Symbol::new("top".to_owned());
+        top.set_type_signature(prop_ty.clone());
+
+        let a = Variable::new("a".to_owned());
+        a.set_type_signature(var_ty.clone());
+        let b = Variable::new("b".to_owned());
+        b.set_type_signature(var_ty);
+
+        let template = apply(
+            Formula::Symbol(&pair),
+            vec![Formula::Variable(&a), Formula::Variable(&b)],
+        );
+
+        // 'A can stand for any type, as long as it's the same one for 'a and 'b.
+        let target = apply(
+            Formula::Symbol(&pair),
+            vec![Formula::Symbol(&zero), Formula::Symbol(&zero)],
+        );
+        let substitution = Substitution::new(&[], &template, &target).unwrap();
+        assert_eq!(substitution.types["A"], nat_ty);
+
+        let target = apply(
+            Formula::Symbol(&pair),
+            vec![Formula::Symbol(&zero), Formula::Symbol(&top)],
+        );
+        assert!(matches!(
+            Substitution::new(&[], &template, &target),
+            Err(SubstitutionError::TypeConflict(id, first, second))
+                if id == "A" && first == nat_ty && second == prop_ty
+        ));
     }
 }
@@ -16,26 +16,38 @@
 use std::lazy::OnceCell;
 
 use super::errors::CheckingError;
-use super::language::Formula;
+use super::language::{Formula, Variable};
 
 #[derive(Debug)]
-pub struct System {
+pub struct System<'a> {
     id: String,
+
+    // The systems which this one extends, whose children it can use as its own.
+    parents: OnceCell<Vec<&'a System<'a>>>,
 }
 
-impl System {
+impl<'a> System<'a> {
     pub fn new(id: String) -> Self {
-        System { id }
+        System {
+            id,
+
+            parents: OnceCell::new(),
+        }
+    }
+
+    pub fn set_parents(&self, parents: Vec<&'a System<'a>>) {
+        self.parents.set(parents).unwrap();
     }
 }
 
 #[derive(Debug)]
 pub struct Axiom<'a> {
     id: String,
-    system_ref: OnceCell<&'a System>,
+    system_ref: OnceCell<&'a System<'a>>,
 
     premise: OnceCell<Vec<Formula<'a>>>,
     assertion: OnceCell<Formula<'a>>,
+    distinct: OnceCell<Vec<(&'a Variable<'a>, &'a Variable<'a>)>>,
 }
 
 impl<'a> Axiom<'a> {
@@ -46,10 +58,11 @@ impl<'a> Axiom<'a> {
 
             premise: OnceCell::new(),
             assertion: OnceCell::new(),
+            distinct: OnceCell::new(),
         }
     }
 
-    pub fn set_system(&self, system_ref: &'a System) {
+    pub fn set_system(&self, system_ref: &'a System<'a>) {
         self.system_ref.set(system_ref).unwrap()
     }
 
@@ -61,6 +74,10 @@ impl<'a> Axiom<'a> {
         self.assertion.set(assertion).unwrap()
     }
 
+    pub fn set_distinct(&self, distinct: Vec<(&'a Variable<'a>, &'a Variable<'a>)>) {
+        self.distinct.set(distinct).unwrap()
+    }
+
     fn premise(&self) -> &[Formula<'a>] {
         self.premise.get().unwrap()
     }
@@ -69,6 +86,10 @@ impl<'a> Axiom<'a> {
         self.assertion.get().unwrap()
     }
 
+    fn distinct(&self) -> &[(&'a Variable<'a>, &'a Variable<'a>)] {
+        self.distinct.get().unwrap()
+    }
+
     pub fn verify(&self) -> bool {
         let premise = self.premise.get().unwrap();
         let assertion = self.assertion.get().unwrap();
@@ -80,10 +101,11 @@ impl<'a> Axiom<'a> {
 #[derive(Debug)]
 pub struct Theorem<'a> {
     id: String,
-    system_ref: OnceCell<&'a System>,
+    system_ref: OnceCell<&'a System<'a>>,
 
     premise: OnceCell<Vec<Formula<'a>>>,
     assertion: OnceCell<Formula<'a>>,
+    distinct: OnceCell<Vec<(&'a Variable<'a>, &'a Variable<'a>)>>,
 }
 
 impl<'a> Theorem<'a> {
@@ -94,10 +116,11 @@ impl<'a> Theorem<'a> {
 
             premise: OnceCell::new(),
             assertion: OnceCell::new(),
+            distinct: OnceCell::new(),
         }
     }
 
-    pub fn set_system(&self, system_ref: &'a System) {
+    pub fn set_system(&self, system_ref: &'a System<'a>) {
         self.system_ref.set(system_ref).unwrap()
     }
 
@@ -109,6 +132,10 @@ impl<'a> Theorem<'a> {
         self.assertion.set(assertion).unwrap()
     }
 
+    pub fn set_distinct(&self, distinct: Vec<(&'a Variable<'a>, &'a Variable<'a>)>) {
+        self.distinct.set(distinct).unwrap()
+    }
+
     fn premise(&self) -> &[Formula<'a>] {
         self.premise.get().unwrap()
     }
@@ -117,6 +144,10 @@ impl<'a> Theorem<'a> {
         self.assertion.get().unwrap()
     }
 
+    fn distinct(&self) -> &[(&'a Variable<'a>, &'a Variable<'a>)] {
+        self.distinct.get().unwrap()
+    }
+
     pub fn verify(&self) -> bool {
         let premise = self.premise.get().unwrap();
         let assertion = self.assertion.get().unwrap();
@@ -145,11 +176,32 @@ impl<'a> DeductableRef<'a> {
             Self::Theorem(theorem_ref) => theorem_ref.assertion(),
         }
     }
+
+    pub fn distinct(&self) -> &[(&'a Variable<'a>, &'a Variable<'a>)] {
+        match self {
+            Self::Axiom(axiom_ref) => axiom_ref.distinct(),
+            Self::Theorem(theorem_ref) => theorem_ref.distinct(),
+        }
+    }
+}
+
+// A previous proof step, or a hypothesis of the theorem being proven.
+#[derive(Clone, Copy, Debug)]
+pub enum ProofPremise {
+    Step(usize),
+    Hypothesis(usize),
 }
 
 #[derive(Debug)]
 pub enum ProofJustification<'a> {
-    Deductable(DeductableRef<'a>),
+    // If the premises are given, the nth one must match the nth hypothesis of the deductable.
+    // Otherwise all previous steps are searched. The substitution fixes some variables of the
+    // deductable before anything is matched.
+    Deductable(
+        DeductableRef<'a>,
+        Option<Vec<ProofPremise>>,
+        Vec<(&'a Variable<'a>, Formula<'a>)>,
+    ),
     Hypothesis(usize),
 
     Definition,
@@ -176,16 +228,34 @@ impl<'a> ProofStep<'a> {
     fn check(
         &'a self,
         prev_steps: &'a [ProofStep<'a>],
-        premise: &[Formula<'a>],
+        theorem_ref: &'a Theorem<'a>,
+        distinct: &[(&'a Variable<'a>, &'a Variable<'a>)],
         i: usize,
-    ) -> Option<CheckingError> {
+    ) -> Option<CheckingError<'a>> {
         match &self.justification {
-            ProofJustification::Deductable(deductable_ref) => {
-                self.formula.check_deductable(deductable_ref, prev_steps, i)
+            ProofJustification::Deductable(deductable_ref, premises, substitution) => {
+                let premises = premises.as_ref().map(|premises| {
+                    premises
+                        .iter()
+                        .map(|premise| match premise {
+                            ProofPremise::Step(j) => prev_steps[*j].formula(),
+                            ProofPremise::Hypothesis(j) => &theorem_ref.premise()[*j],
+                        })
+                        .collect::<Vec<_>>()
+                });
+
+                self.formula.check_deductable(
+                    deductable_ref,
+                    prev_steps,
+                    premises.as_deref(),
+                    substitution,
+                    distinct,
+                    i,
+                )
             }
 
             ProofJustification::Hypothesis(hypothesis_index) => {
-                if self.formula == premise[*hypothesis_index] {
+                if self.formula == theorem_ref.premise()[*hypothesis_index] {
                     None
                 } else {
                     Some(CheckingError::HypothesisMismatch(i))
@@ -208,33 +278,85 @@ impl<'a> ProofStep<'a> {
     pub fn formula(&self) -> &Formula<'a> {
         &self.formula
     }
+
+    fn collect_variables(&self, variables: &mut Vec<&'a Variable<'a>>) {
+        self.formula.collect_variables(variables);
+
+        if let ProofJustification::Deductable(_, _, substitution) = &self.justification {
+            for (_, formula) in substitution {
+                formula.collect_variables(variables);
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Proof<'a> {
     theorem_ref: &'a Theorem<'a>,
+
+    // The variables declared by the proof itself, rather than by the theorem.
+    local_vars: Vec<&'a Variable<'a>>,
     steps: Vec<ProofStep<'a>>,
 }
 
 impl<'a> Proof<'a> {
-    pub fn new(theorem_ref: &'a Theorem<'a>, steps: Vec<ProofStep<'a>>) -> Self {
-        Proof { theorem_ref, steps }
+    pub fn new(
+        theorem_ref: &'a Theorem<'a>,
+        local_vars: Vec<&'a Variable<'a>>,
+        steps: Vec<ProofStep<'a>>,
+    ) -> Self {
+        Proof {
+            theorem_ref,
+
+            local_vars,
+            steps,
+        }
     }
 
     pub fn verify(&self) -> bool {
         self.steps.iter().all(ProofStep::verify)
     }
 
-    pub fn check(&'a self) -> Box<dyn Iterator<Item = CheckingError> + '_> {
+    // The pairs of variables which the proof may assume are distinct. A variable declared by the
+    // proof can't appear in the theorem, so it's distinct from every other variable.
+    fn distinct(&self) -> Vec<(&'a Variable<'a>, &'a Variable<'a>)> {
+        let mut distinct = self.theorem_ref.distinct().to_vec();
+        if self.local_vars.is_empty() {
+            return distinct;
+        }
+
+        let mut variables = self.local_vars.clone();
+        for hypothesis in self.theorem_ref.premise() {
+            hypothesis.collect_variables(&mut variables);
+        }
+        self.theorem_ref
+            .assertion()
+            .collect_variables(&mut variables);
+        for step in &self.steps {
+            step.collect_variables(&mut variables);
+        }
+
+        for local_var in &self.local_vars {
+            for var in &variables {
+                if local_var != var {
+                    distinct.push((local_var, var));
+                }
+            }
+        }
+
+        distinct
+    }
+
+    pub fn check(&'a self) -> Box<dyn Iterator<Item = CheckingError<'a>> + '_> {
         if self.steps.is_empty() {
             return Box::new(std::iter::once(CheckingError::EmptyProof));
         }
 
-        let premise = self.theorem_ref.premise();
+        let distinct = self.distinct();
         let step_errors = (0..self.steps.len()).filter_map(move |i| {
             let prev_steps = &self.steps[0..i];
 
-            self.steps[i].check(prev_steps, premise, i)
+            self.steps[i].check(prev_steps, self.theorem_ref, &distinct, i)
         });
 
         Box::new(step_errors.chain(
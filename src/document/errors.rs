@@ -13,18 +13,48 @@
 // You should have received a copy of the GNU Affero General Public License along with ckproof. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use crate::eprint;
+use crate::core::errors::SubstitutionError;
+use crate::Diagnostic;
 
-use super::system::{ProofBlock, ProofBlockStep};
+use super::system::{ProofBlock, ProofBlockSmallJustification, ProofBlockStep};
+use super::Document;
 
+// Steps which are justified by a macro expand into several small steps, and the usize in each of
+// the step variants is the index of the small step which failed.
 pub enum DocumentCheckingError<'a> {
+    EmptyProof(&'a ProofBlock<'a>),
     AssertionMismatch(&'a ProofBlock<'a>),
 
-    DeductableAssertionNotSubstitutable(&'a ProofBlock<'a>, &'a ProofBlockStep<'a>),
+    DeductableAssertionNotSubstitutable(
+        &'a ProofBlock<'a>,
+        &'a ProofBlockStep<'a>,
+        usize,
+        SubstitutionError<'a>,
+    ),
+    DeductableNotSubstitutable(
+        &'a ProofBlock<'a>,
+        &'a ProofBlockStep<'a>,
+        usize,
+        SubstitutionError<'a>,
+    ),
+    DeductableNotDistinct(&'a ProofBlock<'a>, &'a ProofBlockStep<'a>, usize),
+
+    HypothesisMismatch(&'a ProofBlock<'a>, &'a ProofBlockStep<'a>, usize),
+
+    DefinitionMismatch(&'a ProofBlock<'a>, &'a ProofBlockStep<'a>, usize),
 }
 
 impl<'a> DocumentCheckingError<'a> {
-    fn eprint_assertion_mismatch(proof: &ProofBlock) {
+    fn diagnostic_empty_proof(proof: &ProofBlock) -> Diagnostic {
+        let message = format!(
+            "A proof for `{}` doesn't contain any steps.",
+            proof.theorem_name()
+        );
+
+        Diagnostic::error("proof-empty", message).or_location(proof.file_location())
+    }
+
+    fn diagnostic_assertion_mismatch(proof: &ProofBlock) -> Diagnostic {
         let last_step = proof.last_step().unwrap();
 
         let message = format!(
@@ -32,41 +62,172 @@ impl<'a> DocumentCheckingError<'a> {
             proof.theorem_name()
         );
 
-        eprint(&message, last_step.file_location());
+        Diagnostic::error("proof-assertion-mismatch", message)
+            .or_location(last_step.file_location())
     }
 
-    fn eprint_deductable_assertion_not_substitutable(proof: &ProofBlock, step: &ProofBlockStep) {
-        let justification = step.justification().deductable().unwrap();
+    fn describe_justification(justification: &ProofBlockSmallJustification) -> String {
+        match justification {
+            ProofBlockSmallJustification::Deductable(deductable_ref, _, _) => {
+                format!(
+                    "the {} `{}`",
+                    deductable_ref.kind_str(),
+                    deductable_ref.name()
+                )
+            }
+            ProofBlockSmallJustification::Hypothesis(i) => format!("hypothesis {}", i),
 
-        let message = format!(
-            "A step of a proof for `{}` does not match the assertion of `{}`, the {} meant to justify it.",
-            proof.theorem_name(),
-            justification.name(),
-            justification.kind_str()
-        );
+            ProofBlockSmallJustification::Definition => "a definition".to_owned(),
+        }
+    }
 
-        eprint(&message, step.file_location());
+    fn describe_substitution_error(
+        document: &'a Document<'a>,
+        e: &SubstitutionError<'a>,
+    ) -> String {
+        match e {
+            SubstitutionError::Mismatch(template, target) => format!(
+                "`{}` in the justification can't be matched with `{}`",
+                document.render_formula(template),
+                document.render_formula(target)
+            ),
+            SubstitutionError::Conflict(variable_ref, first, second) => format!(
+                "`{}` would have to be replaced by both `{}` and `{}`",
+                variable_ref.id(),
+                document.render_formula(first),
+                document.render_formula(second)
+            ),
+            SubstitutionError::TypeConflict(id, first, second) => format!(
+                "the type `'{}` would have to be both `{}` and `{}`",
+                id,
+                Document::render_type_signature(first),
+                Document::render_type_signature(second)
+            ),
+            SubstitutionError::HypothesisNotFound(i) => {
+                format!("no earlier step matches hypothesis {}", i + 1)
+            }
+        }
     }
 
-    fn eprint(&self) {
-        match self {
-            Self::AssertionMismatch(proof) => Self::eprint_assertion_mismatch(proof),
+    fn diagnostic_step(
+        code: &'static str,
+        document: &'a Document<'a>,
+        proof: &ProofBlock,
+        step: &'a ProofBlockStep<'a>,
+        small_step: usize,
+        reason: &str,
+    ) -> Diagnostic {
+        let num_small_steps = step.small_steps().len();
+        let justification = step.small_steps()[small_step].justification();
+
+        // The last small step is the one which derives the formula written in the step itself.
+        // Anything before it was generated by a macro, so describe which of those failed, and
+        // what it was meant to derive, since that isn't written anywhere.
+        let message = if small_step + 1 == num_small_steps {
+            format!(
+                "A step of a proof for `{}` is justified by {}, but {}.",
+                proof.theorem_name(),
+                Self::describe_justification(justification),
+                reason,
+            )
+        } else {
+            let formula = step.small_steps()[small_step].formula().checkable();
+
+            format!(
+                "A step of a proof for `{}` expands into {} steps, and step {} of these, which derives `{}` from {}, fails: {}.",
+                proof.theorem_name(),
+                num_small_steps,
+                small_step + 1,
+                document.render_formula(&formula),
+                Self::describe_justification(justification),
+                reason,
+            )
+        };
+
+        Diagnostic::error(code, message).or_location(step.file_location())
+    }
 
-            Self::DeductableAssertionNotSubstitutable(proof, step) => {
-                Self::eprint_deductable_assertion_not_substitutable(proof, step)
+    fn diagnostic(&self, document: &'a Document<'a>) -> Diagnostic {
+        match self {
+            Self::EmptyProof(proof) => Self::diagnostic_empty_proof(proof),
+            Self::AssertionMismatch(proof) => Self::diagnostic_assertion_mismatch(proof),
+
+            Self::DeductableAssertionNotSubstitutable(proof, step, small_step, e) => {
+                let reason = format!(
+                    "its formula does not match the assertion of that justification: {}",
+                    Self::describe_substitution_error(document, e)
+                );
+
+                Self::diagnostic_step(
+                    "step-not-substitutable",
+                    document,
+                    proof,
+                    step,
+                    *small_step,
+                    &reason,
+                )
             }
+            Self::DeductableNotSubstitutable(proof, step, small_step, e) => {
+                let cited = step.small_steps()[*small_step]
+                    .justification()
+                    .premises()
+                    .is_some();
+                let reason = format!(
+                    "the hypotheses of that justification don't all appear in {} under a single substitution: {}",
+                    if cited { "the cited premises" } else { "earlier steps" },
+                    Self::describe_substitution_error(document, e)
+                );
+
+                Self::diagnostic_step(
+                    "step-hypotheses-not-found",
+                    document,
+                    proof,
+                    step,
+                    *small_step,
+                    &reason,
+                )
+            }
+            Self::DeductableNotDistinct(proof, step, small_step) => Self::diagnostic_step(
+                "step-not-distinct",
+                document,
+                proof,
+                step,
+                *small_step,
+                "every substitution which matches it breaks a `distinct` condition",
+            ),
+
+            Self::HypothesisMismatch(proof, step, small_step) => Self::diagnostic_step(
+                "step-hypothesis-mismatch",
+                document,
+                proof,
+                step,
+                *small_step,
+                "its formula does not match that hypothesis",
+            ),
+
+            Self::DefinitionMismatch(proof, step, small_step) => Self::diagnostic_step(
+                "step-definition-mismatch",
+                document,
+                proof,
+                step,
+                *small_step,
+                "no earlier step is equal to it after expanding definitions",
+            ),
         }
     }
 }
 
-#[derive(Default)]
 pub struct DocumentCheckingErrorContext<'a> {
+    document: &'a Document<'a>,
     errors: Vec<DocumentCheckingError<'a>>,
 }
 
 impl<'a> DocumentCheckingErrorContext<'a> {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(document: &'a Document<'a>) -> Self {
+        DocumentCheckingErrorContext {
+            document,
+            errors: Vec::new(),
+        }
     }
 
     pub fn err<E: Into<DocumentCheckingError<'a>>>(&mut self, e: E) {
@@ -77,11 +238,16 @@ impl<'a> DocumentCheckingErrorContext<'a> {
         !self.errors.is_empty()
     }
 
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors
+            .iter()
+            .map(|error| error.diagnostic(self.document))
+            .collect()
+    }
+
     pub fn eprint(&self) {
-        for error in &self.errors {
-            error.eprint();
+        for diagnostic in self.diagnostics() {
+            diagnostic.eprint();
         }
-
-        eprintln!("Checker exited with errors.");
     }
 }
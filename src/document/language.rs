@@ -99,6 +99,7 @@ impl<'a> std::fmt::Debug for TypeBlock<'a> {
 
 pub enum TypeSignatureBlock<'a> {
     Ground(TypeBlockRef<'a>),
+    Variable(String),
     Compound(Box<TypeSignatureBlock<'a>>, Box<TypeSignatureBlock<'a>>),
 }
 
@@ -106,6 +107,7 @@ impl<'a> TypeSignatureBlock<'a> {
     fn crosslink(&'a self, document: &'a Document<'a>) {
         match self {
             Self::Ground(type_ref) => type_ref.crosslink(document),
+            Self::Variable(_) => {}
             Self::Compound(left, right) => {
                 left.crosslink(document);
                 right.crosslink(document);
@@ -115,7 +117,7 @@ impl<'a> TypeSignatureBlock<'a> {
 
     fn is_compound(&self) -> bool {
         match self {
-            Self::Ground(_) => false,
+            Self::Ground(_) | Self::Variable(_) => false,
             Self::Compound(_, _) => true,
         }
     }
@@ -123,6 +125,7 @@ impl<'a> TypeSignatureBlock<'a> {
     fn checkable(&'a self) -> TypeSignature {
         match self {
             Self::Ground(type_ref) => TypeSignature::Ground(type_ref.checkable()),
+            Self::Variable(id) => TypeSignature::Variable(id.clone()),
             Self::Compound(input, output) => {
                 TypeSignature::Compound(Box::new(input.checkable()), Box::new(output.checkable()))
             }
@@ -134,6 +137,7 @@ impl<'a> TypeSignatureBlock<'a> {
         // TODO: Render without so many parentheses.
         match self {
             Self::Ground(type_ref) => type_ref.id().to_owned(),
+            Self::Variable(id) => id.clone(),
 
             Self::Compound(input, output) => {
                 if input.is_compound() {
@@ -146,15 +150,18 @@ impl<'a> TypeSignatureBlock<'a> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum DisplayStyle {
     Prefix,
     Infix,
     Suffix,
     Standard,
+
+    // The delimiters around and between the inputs, like `⟨`, `,` and `⟩` for a pair.
+    Mixfix(Vec<String>),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Display {
     style: DisplayStyle,
     id: String,
@@ -165,30 +172,142 @@ impl Display {
         Display { style, id }
     }
 
-    fn example<'a, I>(&self, mut inputs: I) -> MathBlock
+    fn example<'a, I>(&self, inputs: I) -> MathBlock
     where
         I: ExactSizeIterator<Item = &'a str>,
     {
-        match self.style {
-            DisplayStyle::Prefix => todo!(),
-            DisplayStyle::Infix => {
-                assert_eq!(inputs.len(), 2);
-                let first = map_ident(inputs.next().unwrap()).to_owned();
-                let second = map_ident(inputs.next().unwrap()).to_owned();
-
-                MathBlock::new(vec![
-                    MathElement::Variable(first.to_owned()),
-                    MathElement::Operator(map_ident(&self.id).to_owned()),
-                    MathElement::Variable(second.to_owned()),
-                ])
+        let inputs = inputs
+            .map(|input| vec![MathElement::Variable(map_ident(input).to_owned())])
+            .collect();
+
+        MathBlock::new(self.math_applied(inputs))
+    }
+
+    // The number of inputs the style lays out, or `None` if it writes any number like `f(x, y)`.
+    pub fn arity(&self) -> Option<usize> {
+        match &self.style {
+            DisplayStyle::Prefix | DisplayStyle::Suffix => Some(1),
+            DisplayStyle::Infix => Some(2),
+            DisplayStyle::Mixfix(delimiters) => Some(delimiters.len() - 1),
+            DisplayStyle::Standard => None,
+        }
+    }
+
+    // Whether the symbol applied to this many inputs is written with an operator beside them, so
+    // it needs parentheses as the input of another operator.
+    pub fn is_operator(&self, inputs: usize) -> bool {
+        matches!(
+            (&self.style, inputs),
+            (DisplayStyle::Prefix, 1) | (DisplayStyle::Infix, 2) | (DisplayStyle::Suffix, 1)
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.style {
+            DisplayStyle::Prefix => format!("the prefix operator `{}`", self.id),
+            DisplayStyle::Infix => format!("the infix operator `{}`", self.id),
+            DisplayStyle::Suffix => format!("the suffix operator `{}`", self.id),
+            DisplayStyle::Mixfix(delimiters) => format!("the notation `{}`", delimiters.join(" ")),
+            DisplayStyle::Standard => format!("`{}`", self.id),
+        }
+    }
+
+    // The symbol applied to the given inputs, as it's written in formulas. Inputs which need
+    // parentheses should already be fenced.
+    pub fn math_applied(&self, inputs: Vec<Vec<MathElement>>) -> Vec<MathElement> {
+        let operator = map_ident(&self.id).to_owned();
+        let mut inputs = inputs.into_iter();
+
+        match (&self.style, inputs.len()) {
+            (DisplayStyle::Mixfix(delimiters), n) if delimiters.len() == n + 1 => {
+                let last = delimiters.len() - 1;
+
+                let mut elements = Vec::new();
+                for (i, delimiter) in delimiters.iter().enumerate() {
+                    elements.push(if i == 0 || i == last {
+                        MathElement::Fence(delimiter.clone())
+                    } else if delimiter == "," {
+                        MathElement::Separator
+                    } else {
+                        MathElement::Operator(delimiter.clone())
+                    });
+
+                    if let Some(input) = inputs.next() {
+                        elements.extend(input);
+                    }
+                }
+
+                elements
+            }
+
+            (_, 0) => vec![MathElement::Symbol(operator)],
+
+            (DisplayStyle::Prefix, 1) => {
+                let mut elements = vec![MathElement::Operator(operator)];
+                elements.extend(inputs.next().unwrap());
+
+                elements
+            }
+            (DisplayStyle::Infix, 2) => {
+                let mut elements = inputs.next().unwrap();
+                elements.push(MathElement::Operator(operator));
+                elements.extend(inputs.next().unwrap());
+
+                elements
+            }
+            (DisplayStyle::Suffix, 1) => {
+                let mut elements = inputs.next().unwrap();
+                elements.push(MathElement::Operator(operator));
+
+                elements
             }
 
-            _ => todo!(),
+            _ => {
+                let mut fenced = Vec::new();
+                for (i, input) in inputs.enumerate() {
+                    if i > 0 {
+                        fenced.push(MathElement::Separator);
+                    }
+
+                    fenced.extend(input);
+                }
+
+                vec![
+                    MathElement::Symbol(operator),
+                    MathElement::Fenced(MathBlock::new(fenced)),
+                ]
+            }
+        }
+    }
+
+    // Plain text for the symbol applied to the given inputs, used in error messages. Inputs which
+    // aren't atomic should already be parenthesized.
+    pub fn render_applied(&self, inputs: &[String]) -> String {
+        let operator = map_ident(&self.id);
+
+        match (&self.style, inputs) {
+            (DisplayStyle::Mixfix(delimiters), _) => {
+                let mut rendered = delimiters[0].clone();
+                for (input, delimiter) in inputs.iter().zip(&delimiters[1..]) {
+                    rendered.push_str(input);
+                    rendered.push_str(delimiter);
+                }
+
+                rendered
+            }
+
+            (_, []) => operator.to_owned(),
+
+            (DisplayStyle::Prefix, [input]) => format!("{}{}", operator, input),
+            (DisplayStyle::Infix, [left, right]) => format!("{} {} {}", left, operator, right),
+            (DisplayStyle::Suffix, [input]) => format!("{}{}", input, operator),
+
+            _ => format!("{}({})", operator, inputs.join(", ")),
         }
     }
 
     fn render(&self) -> Option<Denoted> {
-        match self.style {
+        match &self.style {
             DisplayStyle::Prefix => Some(Denoted::new(
                 DenotedStyle::Prefix,
                 map_ident(&self.id).to_owned(),
@@ -197,8 +316,16 @@ impl Display {
                 DenotedStyle::Infix,
                 map_ident(&self.id).to_owned(),
             )),
+            DisplayStyle::Suffix => Some(Denoted::new(
+                DenotedStyle::Suffix,
+                map_ident(&self.id).to_owned(),
+            )),
+
+            DisplayStyle::Mixfix(delimiters) => {
+                Some(Denoted::new(DenotedStyle::Mixfix, delimiters.join("_")))
+            }
 
-            _ => todo!(),
+            DisplayStyle::Standard => None,
         }
     }
 }
@@ -301,6 +428,10 @@ impl<'a> SymbolBlock<'a> {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
 }
 
 impl<'a> std::fmt::Debug for SymbolBlock<'a> {
@@ -381,7 +512,8 @@ impl<'a> DefinitionBlock<'a> {
 
         self.type_signature.crosslink(document);
 
-        self.expanded.crosslink(document, &self.inputs);
+        self.expanded
+            .crosslink(document, VariableBlockScope::new(&self.inputs));
         self.checkable.set_expanded(self.expanded.checkable());
     }
 
@@ -431,6 +563,10 @@ impl<'a> DefinitionBlock<'a> {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
 }
 
 impl<'a> std::fmt::Debug for DefinitionBlock<'a> {
@@ -464,7 +600,7 @@ impl<'a> VariableBlock<'a> {
             .set_type_signature(self.type_signature.checkable());
     }
 
-    fn checkable(&'a self) -> &Variable {
+    pub fn checkable(&'a self) -> &'a Variable<'a> {
         &self.checkable
     }
 }
@@ -475,8 +611,30 @@ impl<'a> std::fmt::Debug for VariableBlock<'a> {
     }
 }
 
+// The variables which the formulas of a block can refer to. A proof can declare variables of its
+// own, on top of those of the theorem it proves.
+#[derive(Clone, Copy)]
+pub struct VariableBlockScope<'a> {
+    vars: &'a [VariableBlock<'a>],
+    local_vars: &'a [VariableBlock<'a>],
+}
+
+impl<'a> VariableBlockScope<'a> {
+    pub fn new(vars: &'a [VariableBlock<'a>]) -> Self {
+        VariableBlockScope {
+            vars,
+            local_vars: &[],
+        }
+    }
+
+    pub fn with_local(vars: &'a [VariableBlock<'a>], local_vars: &'a [VariableBlock<'a>]) -> Self {
+        VariableBlockScope { vars, local_vars }
+    }
+}
+
 pub struct VariableBlockRef<'a> {
     index: usize,
+    local: bool,
     var: OnceCell<&'a VariableBlock<'a>>,
 }
 
@@ -484,12 +642,27 @@ impl<'a> VariableBlockRef<'a> {
     pub fn new(index: usize) -> Self {
         VariableBlockRef {
             index,
+            local: false,
             var: OnceCell::new(),
         }
     }
 
-    fn crosslink(&'a self, vars: &'a [VariableBlock<'a>]) {
-        self.var.set(&vars[self.index]).unwrap();
+    pub fn local(index: usize) -> Self {
+        VariableBlockRef {
+            index,
+            local: true,
+            var: OnceCell::new(),
+        }
+    }
+
+    fn crosslink(&'a self, vars: VariableBlockScope<'a>) {
+        let var = if self.local {
+            &vars.local_vars[self.index]
+        } else {
+            &vars.vars[self.index]
+        };
+
+        self.var.set(var).unwrap();
     }
 
     fn checkable(&'a self) -> &Variable {
@@ -500,22 +673,29 @@ impl<'a> VariableBlockRef<'a> {
 pub enum FormulaBlock<'a> {
     Symbol(SymbolBlockRef<'a>),
     Variable(VariableBlockRef<'a>),
+    Bound(usize),
 
     Application(Box<FormulaBlock<'a>>, Box<FormulaBlock<'a>>),
+    Abstraction(TypeSignatureBlock<'a>, Box<FormulaBlock<'a>>),
 
     Definition(DefinitionBlockRef<'a>, Vec<FormulaBlock<'a>>),
 }
 
 impl<'a> FormulaBlock<'a> {
-    pub fn crosslink(&'a self, document: &'a Document<'a>, vars: &'a [VariableBlock<'a>]) {
+    pub fn crosslink(&'a self, document: &'a Document<'a>, vars: VariableBlockScope<'a>) {
         match self {
             Self::Symbol(symbol_ref) => symbol_ref.crosslink(document),
             Self::Variable(variable_ref) => variable_ref.crosslink(vars),
+            Self::Bound(_) => {}
 
             Self::Application(left, right) => {
                 left.crosslink(document, vars);
                 right.crosslink(document, vars);
             }
+            Self::Abstraction(input, body) => {
+                input.crosslink(document);
+                body.crosslink(document, vars);
+            }
 
             Self::Definition(definition_ref, inputs) => {
                 definition_ref.crosslink(document);
@@ -530,10 +710,14 @@ impl<'a> FormulaBlock<'a> {
         match self {
             Self::Symbol(symbol_ref) => Formula::Symbol(symbol_ref.checkable()),
             Self::Variable(variable_ref) => Formula::Variable(variable_ref.checkable()),
+            Self::Bound(index) => Formula::Bound(*index),
 
             Self::Application(left, right) => {
                 Formula::Application(Box::new(left.checkable()), Box::new(right.checkable()))
             }
+            Self::Abstraction(input, body) => {
+                Formula::Abstraction(input.checkable(), Box::new(body.checkable()))
+            }
 
             Self::Definition(definition_ref, inputs) => Formula::Definition(
                 definition_ref.checkable(),
@@ -553,7 +737,7 @@ impl<'a> DisplayFormulaBlock<'a> {
         DisplayFormulaBlock { display, contents }
     }
 
-    pub fn crosslink(&'a self, document: &'a Document<'a>, vars: &'a [VariableBlock<'a>]) {
+    pub fn crosslink(&'a self, document: &'a Document<'a>, vars: VariableBlockScope<'a>) {
         self.contents.crosslink(document, vars);
     }
 
@@ -16,6 +16,7 @@
 use std::lazy::OnceCell;
 use std::ops::Index;
 
+use crate::core::language::{Definition, Formula, Symbol, TypeSignature};
 use crate::rendered::DocumentRendered;
 
 pub(crate) mod bibliography;
@@ -28,6 +29,7 @@ pub mod errors;
 
 use bibliography::Bibliography;
 use errors::DocumentCheckingErrorContext;
+use language::Display;
 use structure::{Block, BlockLocation, Book};
 
 pub struct Document<'a> {
@@ -55,7 +57,7 @@ impl<'a> Document<'a> {
 
     pub fn check(&'a self) -> Result<(), &DocumentCheckingErrorContext> {
         let errors = self.errors.get_or_init(|| {
-            let mut errors = DocumentCheckingErrorContext::new();
+            let mut errors = DocumentCheckingErrorContext::new(self);
 
             for book in &self.books {
                 book.verify();
@@ -75,6 +77,121 @@ impl<'a> Document<'a> {
         }
     }
 
+    fn symbol_display(&'a self, symbol_ref: &Symbol<'a>) -> &'a Display {
+        self.books
+            .iter()
+            .flat_map(Book::blocks)
+            .find_map(|block| match block {
+                Block::Symbol(symbol) if symbol.checkable() == symbol_ref => Some(symbol.display()),
+
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn definition_display(&'a self, definition_ref: &Definition<'a>) -> &'a Display {
+        self.books
+            .iter()
+            .flat_map(Book::blocks)
+            .find_map(|block| match block {
+                Block::Definition(definition) if definition.checkable() == definition_ref => {
+                    Some(definition.display())
+                }
+
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn render_type_signature(type_signature: &TypeSignature) -> String {
+        match type_signature {
+            TypeSignature::Ground(type_ref) => type_ref.id().to_owned(),
+            TypeSignature::Variable(id) => id.clone(),
+            TypeSignature::Unknown(i) => format!("?{}", i),
+
+            TypeSignature::Compound(input, output) => {
+                let rendered_input = Self::render_type_signature(input);
+                let rendered_output = Self::render_type_signature(output);
+
+                match **input {
+                    TypeSignature::Ground(_)
+                    | TypeSignature::Variable(_)
+                    | TypeSignature::Unknown(_) => {
+                        format!("{} \u{2192} {}", rendered_input, rendered_output)
+                    }
+                    TypeSignature::Compound(_, _) => {
+                        format!("({}) \u{2192} {}", rendered_input, rendered_output)
+                    }
+                }
+            }
+        }
+    }
+
+    // Renders a formula from the checker as plain text for error messages. Variables bound by
+    // abstractions are named by their depth, as the checker doesn't keep their original names.
+    pub fn render_formula(&'a self, formula: &Formula<'a>) -> String {
+        self.render_formula_at(formula, 0)
+    }
+
+    fn render_formula_at(&'a self, formula: &Formula<'a>, depth: usize) -> String {
+        match formula {
+            Formula::Symbol(symbol_ref) => self.symbol_display(symbol_ref).render_applied(&[]),
+            Formula::Variable(variable_ref) => variable_ref.id().to_owned(),
+            Formula::Bound(index) if *index < depth => format!("x{}", depth - index),
+            Formula::Bound(index) => format!("#{}", index),
+
+            Formula::Application(_, _) => {
+                let mut inputs = Vec::new();
+                let mut head = formula;
+                while let Formula::Application(function, input) = head {
+                    inputs.push(self.render_input_at(input, depth));
+                    head = function;
+                }
+                inputs.reverse();
+
+                match head {
+                    Formula::Symbol(symbol_ref) => {
+                        self.symbol_display(symbol_ref).render_applied(&inputs)
+                    }
+
+                    _ => format!(
+                        "{}({})",
+                        self.render_input_at(head, depth),
+                        inputs.join(", ")
+                    ),
+                }
+            }
+
+            Formula::Abstraction(input, body) => format!(
+                "\u{03BB}x{} : {}. {}",
+                depth + 1,
+                Self::render_type_signature(input),
+                self.render_formula_at(body, depth + 1)
+            ),
+
+            Formula::Definition(definition_ref, inputs) => {
+                let inputs: Vec<_> = inputs
+                    .iter()
+                    .map(|input| self.render_input_at(input, depth))
+                    .collect();
+
+                self.definition_display(definition_ref)
+                    .render_applied(&inputs)
+            }
+        }
+    }
+
+    fn render_input_at(&'a self, formula: &Formula<'a>, depth: usize) -> String {
+        let rendered = self.render_formula_at(formula, depth);
+
+        match formula {
+            Formula::Symbol(_) | Formula::Variable(_) | Formula::Bound(_) => rendered,
+            Formula::Definition(_, inputs) if inputs.is_empty() => rendered,
+
+            _ => format!("({})", rendered),
+        }
+    }
+
     // TODO: Remove.
     pub fn render(&self) -> DocumentRendered {
         let books = self
@@ -332,7 +332,7 @@ impl<'a> SystemBlockRef<'a> {
         self.block.set(block).unwrap();
     }
 
-    pub fn checkable(&self) -> &System {
+    pub fn checkable(&self) -> &'a System<'a> {
         self.block.get().unwrap().checkable()
     }
 
@@ -449,6 +449,10 @@ impl<'a> AxiomBlockRef<'a> {
         self.block.set(block).unwrap();
     }
 
+    pub fn vars(&self) -> &[VariableBlock<'a>] {
+        self.block.get().unwrap().vars()
+    }
+
     pub fn checkable(&'a self) -> &Axiom {
         self.block.get().unwrap().checkable()
     }
@@ -517,6 +521,13 @@ impl<'a> DeductableBlockRef<'a> {
         }
     }
 
+    pub fn vars(&self) -> &[VariableBlock<'a>] {
+        match self {
+            Self::Axiom(axiom_ref) => axiom_ref.vars(),
+            Self::Theorem(theorem_ref) => theorem_ref.vars(),
+        }
+    }
+
     pub fn kind_str(&self) -> &str {
         match self {
             Self::Axiom(_) => "axiom",
@@ -590,6 +601,10 @@ impl<'a> Page<'a> {
         }
     }
 
+    fn blocks(&self) -> impl Iterator<Item = &Block<'a>> {
+        self.blocks.iter()
+    }
+
     // TODO: Remove.
     fn render(
         &self,
@@ -682,6 +697,10 @@ impl<'a> Chapter<'a> {
         }
     }
 
+    fn blocks(&self) -> impl Iterator<Item = &Block<'a>> {
+        self.pages.iter().flat_map(Page::blocks)
+    }
+
     // TODO: Remove.
     fn render(&self, chapter_num: usize, next_chapter_href: Option<&str>) -> ChapterRendered {
         let id = self.id.clone();
@@ -779,6 +798,10 @@ impl<'a> Book<'a> {
         }
     }
 
+    pub fn blocks(&self) -> impl Iterator<Item = &Block<'a>> {
+        self.chapters.iter().flat_map(Chapter::blocks)
+    }
+
     // TODO: Remove.
     pub fn render(&self, book_num: usize) -> BookRendered {
         let id = self.id.clone();
@@ -23,11 +23,13 @@ use crate::rendered::{
 };
 
 use crate::core::errors::CheckingError;
-use crate::core::system::{Axiom, Proof, ProofJustification, ProofStep, System, Theorem};
+use crate::core::system::{
+    Axiom, Proof, ProofJustification, ProofPremise, ProofStep, System, Theorem,
+};
 use crate::rendered::TheoremRendered;
 
 use super::errors::{DocumentCheckingError, DocumentCheckingErrorContext};
-use super::language::{DisplayFormulaBlock, FormulaBlock, VariableBlock};
+use super::language::{DisplayFormulaBlock, FormulaBlock, VariableBlock, VariableBlockScope};
 use super::structure::{DeductableBlockRef, SystemBlockRef, TheoremBlockRef};
 use super::text::{BareText, MathBlock, Paragraph, Text};
 use super::Document;
@@ -37,8 +39,9 @@ pub struct SystemBlock<'a> {
     name: String,
     tagline: Paragraph<'a>,
     description: Vec<Text<'a>>,
+    parents: Vec<SystemBlockRef<'a>>,
 
-    checkable: System,
+    checkable: System<'a>,
 
     // TODO: Remove.
     href: String,
@@ -50,6 +53,7 @@ impl<'a> SystemBlock<'a> {
         name: String,
         tagline: Paragraph<'a>,
         description: Vec<Text<'a>>,
+        parents: Vec<SystemBlockRef<'a>>,
         href: String,
     ) -> Self {
         let checkable = System::new(id.clone());
@@ -59,6 +63,7 @@ impl<'a> SystemBlock<'a> {
             name,
             tagline,
             description,
+            parents,
 
             checkable,
 
@@ -72,9 +77,15 @@ impl<'a> SystemBlock<'a> {
         for text in &self.description {
             text.crosslink(document);
         }
+
+        for parent in &self.parents {
+            parent.crosslink(document);
+        }
+        self.checkable
+            .set_parents(self.parents.iter().map(SystemBlockRef::checkable).collect());
     }
 
-    pub fn checkable(&self) -> &System {
+    pub fn checkable(&'a self) -> &'a System<'a> {
         &self.checkable
     }
 
@@ -118,6 +129,7 @@ pub struct AxiomBlock<'a> {
     description: Vec<Text<'a>>,
 
     vars: Vec<VariableBlock<'a>>,
+    distinct: Vec<(usize, usize)>,
     premise: Vec<DisplayFormulaBlock<'a>>,
     assertion: DisplayFormulaBlock<'a>,
 
@@ -135,6 +147,7 @@ impl<'a> AxiomBlock<'a> {
         tagline: Paragraph<'a>,
         description: Vec<Text<'a>>,
         vars: Vec<VariableBlock<'a>>,
+        distinct: Vec<(usize, usize)>,
         premise: Vec<DisplayFormulaBlock<'a>>,
         assertion: DisplayFormulaBlock<'a>,
         href: String,
@@ -151,6 +164,7 @@ impl<'a> AxiomBlock<'a> {
             description,
 
             vars,
+            distinct,
             premise,
             assertion,
 
@@ -172,9 +186,15 @@ impl<'a> AxiomBlock<'a> {
         for var in &self.vars {
             var.crosslink(document);
         }
+        self.checkable.set_distinct(
+            self.distinct
+                .iter()
+                .map(|(left, right)| (self.vars[*left].checkable(), self.vars[*right].checkable()))
+                .collect(),
+        );
 
         for hypothesis in &self.premise {
-            hypothesis.crosslink(document, &self.vars);
+            hypothesis.crosslink(document, VariableBlockScope::new(&self.vars));
         }
         self.checkable.set_premise(
             self.premise
@@ -183,7 +203,8 @@ impl<'a> AxiomBlock<'a> {
                 .collect(),
         );
 
-        self.assertion.crosslink(document, &self.vars);
+        self.assertion
+            .crosslink(document, VariableBlockScope::new(&self.vars));
         self.checkable.set_assertion(self.assertion.checkable());
     }
 
@@ -191,6 +212,10 @@ impl<'a> AxiomBlock<'a> {
         assert!(self.checkable.verify());
     }
 
+    pub fn vars(&self) -> &[VariableBlock<'a>] {
+        &self.vars
+    }
+
     pub fn checkable(&'a self) -> &Axiom {
         &self.checkable
     }
@@ -270,6 +295,7 @@ pub struct TheoremBlock<'a> {
     description: Vec<Text<'a>>,
 
     vars: Vec<VariableBlock<'a>>,
+    distinct: Vec<(usize, usize)>,
     premise: Vec<DisplayFormulaBlock<'a>>,
     assertion: DisplayFormulaBlock<'a>,
 
@@ -288,6 +314,7 @@ impl<'a> TheoremBlock<'a> {
         tagline: Paragraph<'a>,
         description: Vec<Text<'a>>,
         vars: Vec<VariableBlock<'a>>,
+        distinct: Vec<(usize, usize)>,
         premise: Vec<DisplayFormulaBlock<'a>>,
         assertion: DisplayFormulaBlock<'a>,
         href: String,
@@ -305,6 +332,7 @@ impl<'a> TheoremBlock<'a> {
             description,
 
             vars,
+            distinct,
             premise,
             assertion,
 
@@ -326,9 +354,15 @@ impl<'a> TheoremBlock<'a> {
         for var in &self.vars {
             var.crosslink(document);
         }
+        self.checkable.set_distinct(
+            self.distinct
+                .iter()
+                .map(|(left, right)| (self.vars[*left].checkable(), self.vars[*right].checkable()))
+                .collect(),
+        );
 
         for hypothesis in &self.premise {
-            hypothesis.crosslink(document, &self.vars);
+            hypothesis.crosslink(document, VariableBlockScope::new(&self.vars));
         }
         self.checkable.set_premise(
             self.premise
@@ -337,7 +371,8 @@ impl<'a> TheoremBlock<'a> {
                 .collect(),
         );
 
-        self.assertion.crosslink(document, &self.vars);
+        self.assertion
+            .crosslink(document, VariableBlockScope::new(&self.vars));
         self.checkable.set_assertion(self.assertion.checkable());
     }
 
@@ -406,11 +441,15 @@ pub enum ProofBlockJustification<'a> {
     Definition,
     FunctionApplication,
     Substitution,
+    Commutativity,
+    AssociativityCommutativity,
+    Calculation,
+    Rewrite(DeductableBlockRef<'a>),
 }
 
 impl<'a> ProofBlockJustification<'a> {
     fn crosslink(&'a self, document: &'a Document<'a>) {
-        if let Self::Deductable(deductable_ref) = self {
+        if let Self::Deductable(deductable_ref) | Self::Rewrite(deductable_ref) = self {
             deductable_ref.crosslink(document);
         }
     }
@@ -430,6 +469,17 @@ impl<'a> ProofBlockJustification<'a> {
             Self::Definition => ProofRenderedJustification::Definition,
             Self::FunctionApplication => ProofRenderedJustification::FunctionApplication,
             Self::Substitution => ProofRenderedJustification::Substitution,
+            Self::Commutativity => ProofRenderedJustification::Commutativity,
+            Self::AssociativityCommutativity => {
+                ProofRenderedJustification::AssociativityCommutativity
+            }
+            Self::Calculation => ProofRenderedJustification::Calculation,
+            Self::Rewrite(deductable_ref) => {
+                let name = deductable_ref.name().to_owned();
+                let href = deductable_ref.href().to_owned();
+
+                ProofRenderedJustification::Rewrite(name, href)
+            }
         }
     }
 
@@ -442,30 +492,84 @@ impl<'a> ProofBlockJustification<'a> {
     }
 }
 
+// Steps are indexed among all small steps of the proof, and hypotheses are numbered from 1.
+#[derive(Clone, Copy, Debug)]
+pub enum ProofBlockPremise {
+    Step(usize),
+    Hypothesis(usize),
+}
+
+impl ProofBlockPremise {
+    fn checkable(&self) -> ProofPremise {
+        match self {
+            Self::Step(i) => ProofPremise::Step(*i),
+            Self::Hypothesis(i) => ProofPremise::Hypothesis(*i - 1),
+        }
+    }
+}
+
 pub enum ProofBlockSmallJustification<'a> {
-    Deductable(DeductableBlockRef<'a>),
+    // The substitution pairs the index of a variable of the deductable with its replacement.
+    Deductable(
+        DeductableBlockRef<'a>,
+        Option<Vec<ProofBlockPremise>>,
+        Vec<(usize, FormulaBlock<'a>)>,
+    ),
     Hypothesis(usize),
 
     Definition,
 }
 
 impl<'a> ProofBlockSmallJustification<'a> {
-    fn crosslink(&'a self, document: &'a Document<'a>) {
-        if let Self::Deductable(deductable_ref) = self {
+    fn crosslink(&'a self, document: &'a Document<'a>, vars: VariableBlockScope<'a>) {
+        if let Self::Deductable(deductable_ref, _, substitution) = self {
             deductable_ref.crosslink(document);
+
+            for (_, formula) in substitution {
+                formula.crosslink(document, vars);
+            }
         }
     }
 
     fn checkable(&'a self) -> ProofJustification {
         match self {
-            Self::Deductable(deductable_ref) => {
-                ProofJustification::Deductable(deductable_ref.checkable())
+            Self::Deductable(deductable_ref, premises, substitution) => {
+                let premises = premises
+                    .as_ref()
+                    .map(|premises| premises.iter().map(ProofBlockPremise::checkable).collect());
+                let substitution = substitution
+                    .iter()
+                    .map(|(index, formula)| {
+                        (
+                            deductable_ref.vars()[*index].checkable(),
+                            formula.checkable(),
+                        )
+                    })
+                    .collect();
+
+                ProofJustification::Deductable(deductable_ref.checkable(), premises, substitution)
             }
             Self::Hypothesis(i) => ProofJustification::Hypothesis(*i - 1),
 
             Self::Definition => ProofJustification::Definition,
         }
     }
+
+    pub fn deductable(&self) -> Option<&DeductableBlockRef<'a>> {
+        match self {
+            Self::Deductable(deductable_ref, _, _) => Some(deductable_ref),
+
+            _ => None,
+        }
+    }
+
+    pub fn premises(&self) -> Option<&[ProofBlockPremise]> {
+        match self {
+            Self::Deductable(_, premises, _) => premises.as_deref(),
+
+            _ => None,
+        }
+    }
 }
 
 pub struct ProofBlockSmallStep<'a> {
@@ -481,8 +585,8 @@ impl<'a> ProofBlockSmallStep<'a> {
         }
     }
 
-    fn crosslink(&'a self, document: &'a Document<'a>, vars: &'a [VariableBlock<'a>]) {
-        self.justification.crosslink(document);
+    fn crosslink(&'a self, document: &'a Document<'a>, vars: VariableBlockScope<'a>) {
+        self.justification.crosslink(document, vars);
         self.formula.crosslink(document, vars);
     }
 
@@ -492,6 +596,14 @@ impl<'a> ProofBlockSmallStep<'a> {
 
         ProofStep::new(justification, formula)
     }
+
+    pub fn justification(&self) -> &ProofBlockSmallJustification<'a> {
+        &self.justification
+    }
+
+    pub fn formula(&self) -> &FormulaBlock<'a> {
+        &self.formula
+    }
 }
 
 pub struct ProofBlockStep<'a> {
@@ -539,7 +651,7 @@ impl<'a> ProofBlockStep<'a> {
         }
     }
 
-    fn crosslink(&'a self, document: &'a Document<'a>, vars: &'a [VariableBlock<'a>]) {
+    fn crosslink(&'a self, document: &'a Document<'a>, vars: VariableBlockScope<'a>) {
         self.justification.crosslink(document);
 
         for step in &self.small_steps {
@@ -575,8 +687,8 @@ impl<'a> ProofBlockStep<'a> {
         &self.justification
     }
 
-    fn num_small_steps(&self) -> usize {
-        self.small_steps.len()
+    pub fn small_steps(&self) -> &[ProofBlockSmallStep<'a>] {
+        &self.small_steps
     }
 }
 
@@ -623,7 +735,7 @@ impl<'a> ProofBlockElement<'a> {
     fn crosslink(
         &'a self,
         document: &'a Document<'a>,
-        vars: &'a [VariableBlock<'a>],
+        vars: VariableBlockScope<'a>,
         proof_ref: &'a ProofBlock<'a>,
     ) {
         match self {
@@ -654,18 +766,27 @@ impl<'a> ProofBlockElement<'a> {
 }
 
 pub struct ProofBlock<'a> {
+    file_location: FileLocation,
     theorem_ref: TheoremBlockRef<'a>,
 
+    vars: Vec<VariableBlock<'a>>,
     elements: Vec<ProofBlockElement<'a>>,
 
     checkable: OnceCell<Proof<'a>>,
 }
 
 impl<'a> ProofBlock<'a> {
-    pub fn new(theorem_ref: TheoremBlockRef<'a>, elements: Vec<ProofBlockElement<'a>>) -> Self {
+    pub fn new(
+        file_location: FileLocation,
+        theorem_ref: TheoremBlockRef<'a>,
+        vars: Vec<VariableBlock<'a>>,
+        elements: Vec<ProofBlockElement<'a>>,
+    ) -> Self {
         ProofBlock {
+            file_location,
             theorem_ref,
 
+            vars,
             elements,
 
             checkable: OnceCell::new(),
@@ -674,59 +795,83 @@ impl<'a> ProofBlock<'a> {
 
     pub fn crosslink(&'a self, document: &'a Document<'a>) {
         self.theorem_ref.crosslink(document);
-        let vars = self.theorem_ref.vars();
+
+        for var in &self.vars {
+            var.crosslink(document);
+        }
+        let vars = VariableBlockScope::with_local(self.theorem_ref.vars(), &self.vars);
 
         for element in &self.elements {
             element.crosslink(document, vars, self);
         }
 
         let theorem = self.theorem_ref.checkable();
+        let local_vars = self.vars.iter().map(VariableBlock::checkable).collect();
         let steps = self
             .elements
             .iter()
             .filter_map(ProofBlockElement::checkable)
             .flatten()
             .collect();
-        self.checkable.set(Proof::new(theorem, steps)).unwrap();
+        self.checkable
+            .set(Proof::new(theorem, local_vars, steps))
+            .unwrap();
     }
 
     pub fn verify(&self) {
         assert!(self.checkable.get().unwrap().verify());
     }
 
-    fn get_step(&self, i: usize) -> Result<&ProofBlockStep<'a>, Option<&ProofBlockStep<'a>>> {
+    // Finds the step which generated the small step at index `i` of the checkable proof, along
+    // with the index of the small step within that step.
+    fn get_step(&'a self, i: usize) -> (&'a ProofBlockStep<'a>, usize) {
         let mut counter = 0;
         for element in self.elements.iter().filter_map(ProofBlockElement::step) {
-            let num_small_steps = element.num_small_steps();
+            let num_small_steps = element.small_steps().len();
 
-            if counter == i {
-                return Ok(element);
-            } else if counter + num_small_steps > i {
-                return Err(Some(element));
+            if counter + num_small_steps > i {
+                return (element, i - counter);
             } else {
                 counter += num_small_steps
             }
         }
 
-        Err(None)
+        unreachable!()
     }
 
     pub fn check(&'a self, errors: &mut DocumentCheckingErrorContext<'a>) {
         for error in self.checkable.get().unwrap().check() {
-            match error {
-                CheckingError::AssertionMismatch => {
-                    errors.err(DocumentCheckingError::AssertionMismatch(self))
+            let e = match error {
+                CheckingError::EmptyProof => DocumentCheckingError::EmptyProof(self),
+                CheckingError::AssertionMismatch => DocumentCheckingError::AssertionMismatch(self),
+
+                CheckingError::DeductableAssertionNotSubstitutable(i, e) => {
+                    let (step, small_step) = self.get_step(i);
+                    DocumentCheckingError::DeductableAssertionNotSubstitutable(
+                        self, step, small_step, e,
+                    )
+                }
+                CheckingError::DeductableNotSubstitutable(i, e) => {
+                    let (step, small_step) = self.get_step(i);
+                    DocumentCheckingError::DeductableNotSubstitutable(self, step, small_step, e)
+                }
+                CheckingError::DeductableNotDistinct(i) => {
+                    let (step, small_step) = self.get_step(i);
+                    DocumentCheckingError::DeductableNotDistinct(self, step, small_step)
                 }
 
-                CheckingError::DeductableAssertionNotSubstitutable(i) => match self.get_step(i) {
-                    Ok(step) => errors.err(
-                        DocumentCheckingError::DeductableAssertionNotSubstitutable(self, step),
-                    ),
-                    Err(step) => todo!(),
-                },
+                CheckingError::HypothesisMismatch(i) => {
+                    let (step, small_step) = self.get_step(i);
+                    DocumentCheckingError::HypothesisMismatch(self, step, small_step)
+                }
 
-                _ => todo!("{:#?}", error),
-            }
+                CheckingError::DefinitionMismatch(i) => {
+                    let (step, small_step) = self.get_step(i);
+                    DocumentCheckingError::DefinitionMismatch(self, step, small_step)
+                }
+            };
+
+            errors.err(e);
         }
     }
 
@@ -749,4 +894,8 @@ impl<'a> ProofBlock<'a> {
     pub fn theorem_name(&self) -> &str {
         self.theorem_ref.name()
     }
+
+    pub fn file_location(&self) -> &FileLocation {
+        &self.file_location
+    }
 }
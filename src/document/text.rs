@@ -267,6 +267,7 @@ impl Sublist {
 
 pub enum MathElement {
     Fenced(MathBlock),
+    Fence(String),
 
     SquareRoot(MathBlock),
     Power(MathBlock, MathBlock),
@@ -286,6 +287,7 @@ impl MathElement {
                 "<mo class=\"paren\">(</mo>{}<mo class=\"paren\">)</mo>",
                 math.render()
             ),
+            Self::Fence(fence) => format!("<mo class=\"paren\" fence=\"true\">{}</mo>", fence),
 
             Self::SquareRoot(math) => format!("<msqrt>{}</msqrt>", math.render()),
             Self::Power(base, exp) => format!(
@@ -301,6 +303,22 @@ impl MathElement {
             Self::Number(n) => format!("<mn>{}</mn>", n),
         }
     }
+
+    fn render_plain(&self) -> String {
+        match self {
+            Self::Fenced(math) => format!("({})", math.render_plain()),
+            Self::Fence(fence) => fence.clone(),
+
+            Self::SquareRoot(math) => format!("\u{221A}({})", math.render_plain()),
+            Self::Power(base, exp) => format!("{}^{}", base.render_plain(), exp.render_plain()),
+
+            Self::Operator(op) => op.clone(),
+            Self::Separator => ",".to_owned(),
+            Self::Symbol(s) => s.clone(),
+            Self::Variable(v) => format!("'{}", v),
+            Self::Number(n) => n.clone(),
+        }
+    }
 }
 
 pub struct MathBlock {
@@ -319,6 +337,23 @@ impl MathBlock {
             .chain(std::iter::once("</mrow>".to_owned()))
             .collect()
     }
+
+    // Plain text for error messages, with spaces between the elements except before
+    // parentheses and commas.
+    pub fn render_plain(&self) -> String {
+        let mut rendered = String::new();
+
+        for (i, element) in self.elements.iter().enumerate() {
+            let spaced = !matches!(element, MathElement::Fenced(_) | MathElement::Separator);
+            if i > 0 && spaced {
+                rendered.push(' ');
+            }
+
+            rendered.push_str(&element.render_plain());
+        }
+
+        rendered
+    }
 }
 
 pub struct DisplayMathBlock {
@@ -17,7 +17,9 @@
 #![feature(nll)]
 #![feature(once_cell)]
 
+use pest::error::{Error as PestError, InputLocation};
 use pest::Span;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 
 pub mod builders;
@@ -53,17 +55,28 @@ fn map_ident(ident: &str) -> &str {
         "psi" => "\u{03C8}",
         "omega" => "\u{03C9}",
 
+        "forall" => "\u{2200}",
+        "exists" => "\u{2203}",
+
         _ => ident,
     }
 }
 
-#[derive(Clone, Debug)]
+// The byte offset of the character at a 1-based column of a line.
+fn byte_offset(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column - 1)
+        .map_or(line.len(), |(offset, _)| offset)
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct FileLocation {
     path: PathBuf,
     start_line: usize,
     start_column: usize,
     end_line: usize,
     end_column: usize,
+    #[serde(skip)]
     preview: Vec<String>,
 }
 
@@ -76,21 +89,17 @@ impl FileLocation {
             .lines()
             .enumerate()
             .map(|(i, line)| {
+                // Columns count characters, so they're turned into byte offsets before slicing.
+                let start = byte_offset(line, start_column);
+                let end = byte_offset(line, end_column);
+
                 let (before, inside, after) = if start_line == end_line {
-                    (
-                        &line[..start_column - 1],
-                        &line[start_column - 1..end_column - 1],
-                        &line[end_column - 1..],
-                    )
+                    (&line[..start], &line[start..end], &line[end..])
                 } else {
                     if i == 0 {
-                        (&line[..start_column - 1], &line[start_column - 1..], "")
+                        (&line[..start], &line[start..], "")
                     } else if i == end_line - start_line {
-                        (
-                            "",
-                            &line[start_column - 1..end_column - 1],
-                            &line[end_column - 1..],
-                        )
+                        ("", &line[..end], &line[end..])
                     } else {
                         ("", line, "")
                     }
@@ -109,24 +118,101 @@ impl FileLocation {
             preview,
         }
     }
-}
 
-fn eprint(message: &str, file_location: &FileLocation) {
-    eprintln!("\u{1B}[91;1mError\u{1B}[97;1m: {}\u{1B}[0m", message);
-    eprintln!(
-        "    \u{1B}[94m-->\u{1B}[0m {}:{}:{}",
-        file_location.path.to_str().unwrap(),
-        file_location.start_line,
-        file_location.start_column
-    );
-    eprintln!("\u{1B}[94m     |\u{1B}[0m");
-    for (i, line) in file_location.preview.iter().enumerate() {
-        eprint!(
-            "\u{1B}[94m{:>4} |\u{1B}[0m {}",
-            i + file_location.start_line,
-            line
+    // A location for errors about a file as a whole, like failing to read it.
+    fn file(path: &Path) -> Self {
+        FileLocation {
+            path: path.to_owned(),
+            start_line: 1,
+            start_column: 1,
+            end_line: 1,
+            end_column: 1,
+            preview: Vec::new(),
+        }
+    }
+
+    fn from_pest_error<R>(path: &Path, contents: &str, e: &PestError<R>) -> Self {
+        let (start, end) = match e.location {
+            InputLocation::Pos(pos) => (pos, pos),
+            InputLocation::Span(span) => span,
+        };
+
+        Self::new(path, Span::new(contents, start, end).unwrap())
+    }
+
+    fn eprint(&self) {
+        eprintln!(
+            "    \u{1B}[94m-->\u{1B}[0m {}:{}:{}",
+            self.path.to_str().unwrap(),
+            self.start_line,
+            self.start_column
         );
+        eprintln!("\u{1B}[94m     |\u{1B}[0m");
+        for (i, line) in self.preview.iter().enumerate() {
+            eprint!("\u{1B}[94m{:>4} |\u{1B}[0m {}", i + self.start_line, line);
+        }
+        eprintln!("\u{1B}[94m     |\u{1B}[0m");
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+}
+
+// A single message about the library, detached from the builders and blocks it was found in. These
+// are printed to the terminal, or emitted as json for editors and other tooling.
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+    severity: Severity,
+    code: &'static str,
+    message: String,
+    location: Option<FileLocation>,
+    related: Vec<FileLocation>,
+}
+
+impl Diagnostic {
+    fn error(code: &'static str, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            location: None,
+            related: Vec::new(),
+        }
+    }
+
+    // Errors are built from the inside out, so the innermost builder with a known location wins.
+    fn or_location(mut self, file_location: &FileLocation) -> Self {
+        if self.location.is_none() {
+            self.location = Some(file_location.clone());
+        }
+
+        self
+    }
+
+    fn with_related(mut self, file_location: &FileLocation) -> Self {
+        self.related.push(file_location.clone());
+        self
+    }
+
+    pub fn eprint(&self) {
+        match self.severity {
+            Severity::Error => {
+                eprintln!("\u{1B}[91;1mError\u{1B}[97;1m: {}\u{1B}[0m", self.message)
+            }
+        }
+
+        if let Some(location) = &self.location {
+            location.eprint();
+        }
+
+        for related in &self.related {
+            eprintln!("\u{1B}[97;1mNote\u{1B}[0m: Related to");
+            related.eprint();
+        }
+
+        eprintln!();
     }
-    eprintln!("\u{1B}[94m     |\u{1B}[0m");
-    eprintln!();
 }
@@ -17,15 +17,57 @@ use std::env;
 
 use ckproof::builders::ManifestBuilder;
 use ckproof::document::Document;
+use ckproof::Diagnostic;
 
+const RET_USAGE_ERR: i32 = 64;
 const RET_BUILDER_ERR: i32 = 1;
 const RET_CHECKER_ERR: i32 = 2;
 
-fn get_document(path: &str) -> Result<Document, i32> {
+#[derive(Clone, Copy)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl MessageFormat {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "human" => Some(Self::Human),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    // Diagnostics go to stdout as one JSON object per line in the machine-readable format, so
+    // that tools don't need to separate them from anything else the checker prints. The summary
+    // names the phase which failed, and is only printed for people.
+    fn emit(self, diagnostics: Vec<Diagnostic>, summary: &str) {
+        match self {
+            Self::Human => {
+                for diagnostic in diagnostics {
+                    diagnostic.eprint();
+                }
+
+                eprintln!("{}", summary);
+            }
+
+            Self::Json => {
+                for diagnostic in diagnostics {
+                    println!("{}", serde_json::to_string(&diagnostic).unwrap());
+                }
+            }
+        }
+    }
+}
+
+fn get_document(path: &str, message_format: MessageFormat) -> Result<Document, i32> {
     let builder = ManifestBuilder::from_lib(path);
 
     builder.build().map_err(|errors| {
-        errors.eprint();
+        message_format.emit(
+            errors.diagnostics(),
+            "Parsing the library failed with errors.",
+        );
 
         RET_BUILDER_ERR
     })
@@ -34,18 +76,42 @@ fn get_document(path: &str) -> Result<Document, i32> {
 // Using std::process::exit doesn't call destructors. Wrapping the main function like this makes
 // sure they're called before the process exits.
 fn main_real() -> Result<(), i32> {
-    let args: Vec<String> = env::args().collect();
+    let mut message_format = MessageFormat::Human;
+    let mut args = Vec::new();
+
+    for arg in env::args().skip(1) {
+        match arg.strip_prefix("--message-format=") {
+            Some(format) => {
+                message_format = MessageFormat::from_arg(format).ok_or_else(|| {
+                    eprintln!("Unknown message format `{}`.", format);
+
+                    RET_USAGE_ERR
+                })?;
+            }
+
+            None => args.push(arg),
+        }
+    }
+
+    if args.len() != 2 {
+        eprintln!("Usage: ckproof [--message-format=human|json] <library> <output>");
+
+        return Err(RET_USAGE_ERR);
+    }
 
-    let document = get_document(&args[1])?;
+    let document = get_document(&args[0], message_format)?;
     document.crosslink();
     document.check().map_err(|errors| {
-        errors.eprint();
+        message_format.emit(
+            errors.diagnostics(),
+            "Checking the library failed with errors.",
+        );
 
         RET_CHECKER_ERR
     })?;
 
     let rendered = document.render();
-    let out_file = std::fs::File::create(&args[2]).unwrap();
+    let out_file = std::fs::File::create(&args[1]).unwrap();
     serde_json::to_writer_pretty(out_file, &rendered).unwrap();
 
     Ok(())
@@ -22,6 +22,7 @@ pub enum DenotedStyle {
     Prefix,
     Infix,
     Suffix,
+    Mixfix,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -251,6 +252,10 @@ pub enum ProofRenderedJustification {
     Definition,
     FunctionApplication,
     Substitution,
+    Commutativity,
+    AssociativityCommutativity,
+    Calculation,
+    Rewrite(String, String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]